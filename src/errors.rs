@@ -1,9 +1,11 @@
 use ariadne::{Color, Label, Report, ReportKind, Source};
 
 // To see about ParsingErrors:
-use super::parser::error::AspenError;
+use super::parser::error::{AspenError, AspenWarning};
 
 // To see about evaluation errors:
+use super::evaluate::{err::AspenErr, error::EvaluateError};
+use std::ops::Range;
 
 pub fn build_error(source: &str, err: AspenError, file_name: &str) {
     let err_string = err.to_string();
@@ -11,6 +13,11 @@ pub fn build_error(source: &str, err: AspenError, file_name: &str) {
         AspenError::IoError(_e) => {
             return;
         }
+        AspenError::AssignToConstant {
+            assignment,
+            declaration,
+            ..
+        } => return build_constant_error(source, &err_string, assignment, declaration, file_name),
         AspenError::Lexing { start, length, .. } => (
             &err_string,
             "Remove unsupported or invalid ASCII Chars".to_owned(),
@@ -59,6 +66,13 @@ pub fn build_error(source: &str, err: AspenError, file_name: &str) {
             length,
             3,
         ),
+        AspenError::InvalidString { start, length, .. } => (
+            &err_string,
+            "Fix the string, or use a single-quoted or raw string to keep it as is".to_owned(),
+            start,
+            length,
+            7,
+        ),
     };
 
     let red = Color::Red;
@@ -72,3 +86,95 @@ pub fn build_error(source: &str, err: AspenError, file_name: &str) {
         .print((file_name, Source::from(source)))
         .unwrap();
 }
+
+pub fn build_warning(source: &str, warning: &AspenWarning, file_name: &str) {
+    let AspenWarning {
+        message,
+        note,
+        start,
+        length,
+    } = warning;
+
+    Report::build(ReportKind::Warning, file_name, *start)
+        .with_message(message)
+        .with_label(Label::new((file_name, *start..start + length)).with_color(Color::Yellow))
+        .with_help(note)
+        .finish()
+        .print((file_name, Source::from(source)))
+        .unwrap();
+}
+
+/// Reports an error that ended the evaluation of the script.
+pub fn build_evaluate_error(source: &str, err: EvaluateError, file_name: &str) {
+    match err {
+        EvaluateError::AssignToConstant {
+            ref assignment,
+            ref declaration,
+            ..
+        } => build_constant_error(
+            source,
+            &err.to_string(),
+            assignment.to_owned(),
+            declaration.to_owned(),
+            file_name,
+        ),
+        err => println!("{}", err),
+    }
+}
+
+/// Reports an assignment to a constant, pointing at both the assignment and the declaration.
+fn build_constant_error(
+    source: &str,
+    message: &str,
+    assignment: Range<usize>,
+    declaration: Range<usize>,
+    file_name: &str,
+) {
+    Report::build(ReportKind::Error, file_name, assignment.start)
+        .with_code(8)
+        .with_message(message)
+        .with_label(
+            Label::new((file_name, assignment))
+                .with_message("assigned here")
+                .with_color(Color::Red),
+        )
+        .with_label(
+            Label::new((file_name, declaration))
+                .with_message("declared as a constant here")
+                .with_color(Color::Blue),
+        )
+        .with_help("Declare it with 'let' to be able to change its value")
+        .finish()
+        .print((file_name, Source::from(source)))
+        .unwrap();
+}
+
+/// Reports an error propagated with `?` up to the top level of the script.
+pub fn build_propagated_error(source: &str, err: &AspenErr, file_name: &str) {
+    let offset = err.span.as_ref().map_or(0, |span| span.start);
+    let mut report = Report::build(ReportKind::Custom(&err.kind, Color::Red), file_name, offset)
+        .with_message(err.description());
+
+    if let Some(span) = &err.span {
+        report = report.with_label(
+            Label::new((file_name, span.to_owned()))
+                .with_message("propagated up to the top level of the script")
+                .with_color(Color::Red),
+        );
+    }
+
+    let causes = err
+        .chain()
+        .skip(1)
+        .map(|cause| format!("caused by {}", cause))
+        .collect::<Vec<_>>();
+    if !causes.is_empty() {
+        report = report.with_note(causes.join("\n"));
+    }
+
+    report
+        .with_help("Handle the error with 'try ... catch' or '??' instead of propagating it")
+        .finish()
+        .print((file_name, Source::from(source)))
+        .unwrap();
+}
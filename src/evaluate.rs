@@ -1,25 +1,58 @@
 use self::{
-    error::EvaluateError, func::AspenFn, globals::set_up_globals, types::AspenType,
-    value::AspenValue,
+    binary::evaluate_binary,
+    err::AspenErr,
+    error::EvaluateError,
+    func::AspenFn,
+    globals::set_up_globals,
+    hooks::find_hook,
+    iterator::AspenIterator,
+    methods::{find_method, Method},
+    pattern::match_pattern,
+    range::AspenRange,
+    record::{AspenRecord, RecordType},
+    types::AspenType,
+    value::{AspenObject, AspenValue},
 };
 use crate::{
     evaluate::import::import_module,
     parser::{
+        conditional::{If, IfOther},
+        for_loop::For,
         func::Func,
-        operator::AssignOperator,
+        loop_control::{Break, Continue},
+        match_expr::{Match, MatchArm},
+        operator::BinaryOperator,
+        record::Record,
         return_stmt::Return,
+        try_catch::Try,
+        utils::Block,
         value::Value,
-        var::{Var, Variables},
+        var::{Destructured, Var, Variables},
+        while_loop::While,
         Container, Expr, Statement,
     },
 };
 use hashbrown::HashMap;
-use rug::Integer;
+use std::{cell::RefCell, ops::Range, rc::Rc};
+use utils::{find_callable_property, find_property, format_value, index_value, properties_mut};
 
+mod binary;
+pub mod bridge;
+pub mod enums;
+pub mod err;
 pub mod error;
 pub mod func;
+mod generator;
 mod globals;
+mod hooks;
 mod import;
+mod iterator;
+mod macros;
+mod methods;
+pub mod native;
+mod pattern;
+mod range;
+pub mod record;
 pub mod types;
 mod utils;
 mod value;
@@ -33,21 +66,86 @@ pub enum ValueWrapper<'a> {
 #[derive(Debug, Clone)]
 pub struct AspenTable<'a> {
     values: HashMap<&'a str, ValueWrapper<'a>>,
+    /// Names declared in each block being evaluated, innermost block last.
+    scopes: Vec<Vec<Declared<'a>>>,
+    /// The error propagated with `?` until it is caught, shared with the sub contexts.
+    propagated: Rc<RefCell<Option<AspenErr<'a>>>>,
+    /// Names declared with `const`, with the position of their declaration.
+    constants: HashMap<&'a str, Range<usize>>,
 }
 
 pub type EvaluateResult<T> = Result<T, EvaluateError>;
 
+/// A name declared in a block, with the binding it shadows, restored at the end of the block.
+#[derive(Debug, Clone)]
+struct Declared<'a> {
+    name: &'a str,
+    shadowed: Option<ValueWrapper<'a>>,
+    /// Position of the declaration of the shadowed binding, if it is a constant.
+    shadowed_constant: Option<Range<usize>>,
+}
+
+/// How the evaluation of a statement ends.
+#[derive(Debug)]
+pub enum Flow<'a> {
+    /// The evaluation goes on with the next statement.
+    Next,
+    /// The last statement of a block is an expression, its value is the value of the block.
+    Value(AspenValue<'a>),
+    /// A `return` was evaluated, the enclosing function ends with this value.
+    Return(AspenValue<'a>),
+    /// A `break` was evaluated, it ends the innermost loop or the one with this label.
+    Break {
+        label: Option<&'a str>,
+        value: AspenValue<'a>,
+    },
+    /// A `continue` was evaluated, it goes to the next iteration of the innermost loop or of the one with this label.
+    Continue { label: Option<&'a str> },
+}
+
+/// How the evaluation of a loop ends.
+enum LoopEnd<'a> {
+    /// The loop is over, with the value given to the `break` ending it or nil.
+    Value(AspenValue<'a>),
+    /// A `return`, or a `break` or `continue` targeting an outer loop, leaves the loop.
+    Exit(Flow<'a>),
+}
+
+impl<'a> LoopEnd<'a> {
+    /// Returns what the loop labelled `label` does once an iteration ended with `flow`,
+    /// `None` when it goes on with its next iteration.
+    fn after_iteration(flow: Flow<'a>, label: Option<&'a str>) -> Option<Self> {
+        match flow {
+            Flow::Next | Flow::Value(_) => None,
+            Flow::Continue { label: target } if target.is_none() || target == label => None,
+            Flow::Break {
+                label: target,
+                value,
+            } if target.is_none() || target == label => Some(LoopEnd::Value(value)),
+            flow => Some(LoopEnd::Exit(flow)),
+        }
+    }
+}
+
 impl<'a> AspenTable<'a> {
     pub fn new() -> Self {
         AspenTable {
             values: HashMap::new(),
+            scopes: Vec::new(),
+            propagated: Rc::default(),
+            constants: HashMap::new(),
         }
     }
 
     pub fn global() -> Self {
         let mut values = HashMap::new();
         set_up_globals(&mut values);
-        Self { values }
+        Self {
+            values,
+            scopes: Vec::new(),
+            propagated: Rc::default(),
+            constants: HashMap::new(),
+        }
     }
 
     pub fn create_sub_ctx(&self) -> Self {
@@ -56,6 +154,7 @@ impl<'a> AspenTable<'a> {
         for val in new_table.values.values_mut() {
             val.change_to_sub()
         }
+        new_table.scopes.clear();
 
         new_table
     }
@@ -64,53 +163,403 @@ impl<'a> AspenTable<'a> {
         &mut self,
         stmts: Container<Statement<'a>>,
     ) -> EvaluateResult<AspenValue<'a>> {
-        for stmt in stmts.into_iter() {
-            match *stmt {
-                Statement::Func(f) => {
-                    self.insert_fn(f)?;
+        if let Flow::Return(value) | Flow::Value(value) = self.execute_block(stmts)? {
+            return Ok(value);
+        }
+
+        Ok(AspenValue::Nil)
+    }
+
+    /// Evaluates statements until the end of the block or until one of them ends the enclosing function.
+    fn execute_block(&mut self, stmts: Container<Statement<'a>>) -> EvaluateResult<Flow<'a>> {
+        let mut stmts = stmts.into_iter().peekable();
+
+        while let Some(stmt) = stmts.next() {
+            match self.execute_statement(*stmt)? {
+                Flow::Next => (),
+                // e.g an 'if' followed by other statements, only the last statement gives its value to the block
+                Flow::Value(_) if stmts.peek().is_some() => (),
+                flow => return Ok(flow),
+            }
+        }
+
+        Ok(Flow::Next)
+    }
+
+    /// Evaluates the body of a loop or of a condition, the names declared in it are removed at its end.
+    ///
+    /// `bindings` are declared in the scope of the block before evaluating it.
+    fn execute_scoped_block(
+        &mut self,
+        block: &Block<'a>,
+        bindings: Option<(&Variables<'a>, AspenValue<'a>)>,
+    ) -> EvaluateResult<Flow<'a>> {
+        self.push_scope();
+
+        let result = match bindings {
+            Some((variables, value)) => self.bind_variables(variables.clone(), value),
+            None => Ok(()),
+        }
+        .and_then(|_| self.execute_block(block.statements()));
+
+        self.pop_scope();
+        result
+    }
+
+    pub fn execute_statement(&mut self, stmt: Statement<'a>) -> EvaluateResult<Flow<'a>> {
+        match stmt {
+            Statement::Func(f) => {
+                self.insert_fn(f)?;
+            }
+            Statement::Var(var) => {
+                self.insert_var(var)?;
+            }
+            Statement::Record(record) => {
+                self.insert_record(record)?;
+            }
+            Statement::Enum(enum_decl) => {
+                let name = enum_decl.name;
+                self.declare(name, AspenValue::EnumType(enum_decl.into()))?;
+            }
+            Statement::Return(Return(value)) => {
+                return Ok(Flow::Return(self.evaluate_expr(*value)?))
+            }
+            Statement::Yield(_) => {
+                return Err(EvaluateError::Custom(
+                    "'yield' can only be used inside a function".to_owned(),
+                ))
+            }
+            Statement::Break(Break { label, value }) => {
+                let value = match value {
+                    Some(value) => self.evaluate_expr(*value)?,
+                    None => AspenValue::Nil,
+                };
+
+                return Ok(Flow::Break { label, value });
+            }
+            Statement::Continue(Continue { label }) => return Ok(Flow::Continue { label }),
+            stmt @ (Statement::For(_) | Statement::While(_)) => {
+                if let LoopEnd::Exit(flow) = self.execute_loop(stmt)? {
+                    return Ok(flow);
                 }
-                Statement::Var(var) => {
-                    self.insert_var(var)?;
+            }
+            Statement::If(if_stmt) => {
+                if let Some(block) = self.select_branch(if_stmt)? {
+                    return self.execute_scoped_block(&block, None);
                 }
-                Statement::Return(Return(value)) => return Ok(self.evaluate_expr(*value)?),
-                Statement::Expr(expr) => match *expr {
-                    Expr::Assign {
-                        target,
-                        operator,
-                        value,
-                        // no need to pay attention to it cause we are at the end of the ctx an assignment is useless
-                    } => {
-                        let name = match *target {
-                            Expr::Id(name) => name,
-                            Expr::ObjIndexing { indexed, indexer } => {
-                                todo!()
-                            }
-                            expr => {
-                                return Err(EvaluateError::Custom(format!(
-                                    "Value can only be assigned to variable, not value '{}'",
-                                    expr
-                                )));
-                            }
-                        };
+            }
+            Statement::Expr { value, is_returned } => match *value {
+                Expr::Assign {
+                    target,
+                    operator,
+                    value,
+                    span,
+                    // no need to pay attention to it cause we are at the end of the ctx an assignment is useless
+                } => {
+                    // 'a += b' is evaluated like 'a = a + b'
+                    let value = match operator.binary_operator() {
+                        Some(operator) => Box::new(Expr::Binary {
+                            lhs: target.clone(),
+                            operator,
+                            rhs: value,
+                        }),
+                        None => value,
+                    };
 
-                        match operator {
-                            AssignOperator::Equal => {
-                                self.update_value(name, self.evaluate_expr(*value)?)?;
+                    match *target {
+                        Expr::Id(name) => {
+                            let value = self.evaluate_expr(*value)?;
+                            self.update_value(name, value, span)?;
+                        }
+                        Expr::ObjIndexing { indexed, indexer } => {
+                            if let Some(name) = indexed.assigned_name() {
+                                self.check_not_constant(name, span)?;
                             }
-                            _ => todo!(),
-                        };
+
+                            let value = self.evaluate_expr(*value)?;
+                            self.assign_property(*indexed, *indexer, value)?;
+                        }
+                        expr => return Err(not_assignable(expr)),
+                    };
+                }
+                Expr::Match(match_expr) => match self.execute_match(match_expr)? {
+                    Flow::Next => (),
+                    Flow::Value(_) if !is_returned => (),
+                    flow => return Ok(flow),
+                },
+                Expr::Try(try_expr) => match self.execute_try(*try_expr)? {
+                    Flow::Next => (),
+                    Flow::Value(_) if !is_returned => (),
+                    flow => return Ok(flow),
+                },
+                expr => {
+                    let value = self.evaluate_expr(expr)?;
+
+                    if is_returned {
+                        return Ok(Flow::Value(value));
                     }
-                    expr => {
-                        self.evaluate_expr(expr)?;
+                }
+            },
+        }
+
+        Ok(Flow::Next)
+    }
+
+    /// Evaluates a `for` or a `while` loop.
+    fn execute_loop(&mut self, stmt: Statement<'a>) -> EvaluateResult<LoopEnd<'a>> {
+        match stmt {
+            Statement::For(For {
+                label,
+                args,
+                indexed,
+                body,
+            }) => {
+                let iterable = self.evaluate_expr(*indexed)?;
+
+                for value in self.iterate(iterable)? {
+                    let flow = self.execute_scoped_block(&body, Some((&args, value?)))?;
+
+                    if let Some(end) = LoopEnd::after_iteration(flow, label) {
+                        return Ok(end);
                     }
-                },
-                _ => todo!(),
+                }
             }
+            Statement::While(While {
+                label,
+                condition,
+                body,
+            }) => {
+                while self.evaluate_condition(*condition.clone())? {
+                    let flow = self.execute_scoped_block(&body, None)?;
+
+                    if let Some(end) = LoopEnd::after_iteration(flow, label) {
+                        return Ok(end);
+                    }
+                }
+            }
+            _ => unreachable!("only called on loops"),
         }
 
-        println!("{:?}", self);
+        Ok(LoopEnd::Value(AspenValue::Nil))
+    }
 
-        Ok(AspenValue::Nil)
+    /// Evaluates the body of the first arm of a match whose pattern matches the value.
+    fn execute_match(&mut self, match_expr: Match<'a>) -> EvaluateResult<Flow<'a>> {
+        let Match { value, arms } = match_expr;
+        let value = self.evaluate_expr(*value)?;
+
+        let body = self.enter_arm(&value, arms)?;
+        let result = self.execute_block(body.statements());
+        self.pop_scope();
+
+        result
+    }
+
+    /// Selects the first arm whose pattern matches `value` and whose guard holds.
+    ///
+    /// The names bound by the pattern are declared in a new scope, it is left open for the body of the arm.
+    fn enter_arm(
+        &mut self,
+        value: &AspenValue<'a>,
+        arms: Vec<MatchArm<'a>>,
+    ) -> EvaluateResult<Block<'a>> {
+        for MatchArm {
+            pattern,
+            guard,
+            body,
+        } in arms
+        {
+            let mut bindings = vec![];
+            if !match_pattern(&pattern, value, &mut bindings)? {
+                continue;
+            }
+
+            // the bound names are only visible in the guard and the body of the arm
+            self.push_scope();
+            match self.bind_arm(bindings, guard) {
+                Ok(true) => return Ok(body),
+                result => {
+                    self.pop_scope();
+                    result?;
+                }
+            }
+        }
+
+        Err(EvaluateError::Custom(format!(
+            "No arm of the match matches the value '{}'",
+            value
+        )))
+    }
+
+    /// Declares the names bound by the pattern of an arm, returns false if its guard does not hold.
+    fn bind_arm(
+        &mut self,
+        bindings: Vec<(&'a str, AspenValue<'a>)>,
+        guard: Option<Box<Expr<'a>>>,
+    ) -> EvaluateResult<bool> {
+        for (name, value) in bindings {
+            self.insert_value(name, value)?;
+        }
+
+        match guard {
+            Some(guard) => self.evaluate_condition(*guard),
+            None => Ok(true),
+        }
+    }
+
+    /// Evaluates the body of a `try`, or its handler if an error is raised or propagated in the body.
+    fn execute_try(&mut self, try_expr: Try<'a>) -> EvaluateResult<Flow<'a>> {
+        let Try {
+            body,
+            binding,
+            handler,
+        } = try_expr;
+
+        let err = match self.execute_scoped_block(&body, None) {
+            Err(error) => self.catch_error(error)?,
+            flow => return flow,
+        };
+
+        // the caught error is only visible in the handler
+        self.push_scope();
+        let result = match binding {
+            Some(name) => self.insert_value(name, err.into()),
+            None => Ok(()),
+        }
+        .and_then(|_| self.execute_block(handler.statements()));
+        self.pop_scope();
+
+        result
+    }
+
+    /// Turns a raised error into an error value, an error propagated with `?` is taken back as it was.
+    fn catch_error(&mut self, error: EvaluateError) -> EvaluateResult<AspenErr<'a>> {
+        match error {
+            EvaluateError::ErrorPropagated => {
+                self.propagated.borrow_mut().take().ok_or_else(|| {
+                    EvaluateError::Internal("the error propagated with '?' was lost".to_owned())
+                })
+            }
+            error => Ok(error.into()),
+        }
+    }
+
+    /// Takes back the error value propagated with `?` up to the top level, other errors are returned as is.
+    pub fn propagated_error(
+        &mut self,
+        error: EvaluateError,
+    ) -> Result<AspenErr<'a>, EvaluateError> {
+        match error {
+            EvaluateError::ErrorPropagated => self.catch_error(error),
+            error => Err(error),
+        }
+    }
+
+    fn push_scope(&mut self) {
+        self.scopes.push(Vec::new());
+    }
+
+    /// Removes the names declared since the matching [`AspenTable::push_scope`], the bindings they shadow are visible again.
+    fn pop_scope(&mut self) {
+        let declared = self.scopes.pop().unwrap_or_default();
+
+        for Declared {
+            name,
+            shadowed,
+            shadowed_constant,
+        } in declared.into_iter().rev()
+        {
+            match shadowed {
+                Some(value) => self.values.insert(name, value),
+                None => self.values.remove(name),
+            };
+            match shadowed_constant {
+                Some(declaration) => self.constants.insert(name, declaration),
+                None => self.constants.remove(name),
+            };
+        }
+    }
+
+    /// Evaluates the condition of an `if` or of a `while`, it must be a boolean.
+    fn evaluate_condition(&mut self, condition: Expr<'a>) -> EvaluateResult<bool> {
+        match self.evaluate_expr(condition)? {
+            AspenValue::Bool(b) => Ok(b),
+            x => Err(EvaluateError::InvalidType {
+                expected: AspenType::Bool,
+                found: x.into(),
+            }),
+        }
+    }
+
+    /// Returns the block of the first branch of an `if` whose condition is true.
+    fn select_branch(&mut self, if_stmt: If<'a>) -> EvaluateResult<Option<Block<'a>>> {
+        let If {
+            condition,
+            body,
+            other,
+        } = if_stmt;
+
+        if self.evaluate_condition(*condition)? {
+            return Ok(Some(body));
+        }
+
+        match other.map(|other| *other) {
+            Some(IfOther::If(other)) => self.select_branch(other),
+            Some(IfOther::Else(body)) => Ok(Some(body)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns an iterator over the values of an array, a range, the chars of a string,
+    /// or over an object implementing the iterator protocol: a `next` method returning `Done` once it is exhausted.
+    pub fn iterate(&self, value: AspenValue<'a>) -> EvaluateResult<AspenIterator<'a>> {
+        let iterator = match value {
+            AspenValue::Array(values) => AspenIterator::new(values.into_iter().map(Ok)),
+            AspenValue::Range(range) => AspenIterator::new(range.iter().map(Ok)),
+            AspenValue::Str(s) => {
+                let chars: Vec<_> = s.chars().collect();
+                AspenIterator::new(
+                    chars
+                        .into_iter()
+                        .map(|c| Ok(AspenValue::Str(c.to_string()))),
+                )
+            }
+            AspenValue::Iterator(iterator) => iterator,
+            AspenValue::Object(_) => match find_callable_property(&value, "next").cloned() {
+                Some(next) => {
+                    let ctx = self.create_sub_ctx();
+                    // the object is bound to `self`, the state its `next` method updates is kept between the steps
+                    let mut receiver = value;
+
+                    AspenIterator::new(std::iter::from_fn(move || {
+                        let result = match &next {
+                            AspenValue::Func(func) => {
+                                func.call_method_mut(&ctx, &mut receiver, vec![])
+                            }
+                            next => ctx.call_value(next.clone(), vec![]),
+                        };
+
+                        match result {
+                            Ok(AspenValue::Done) => None,
+                            result => Some(result),
+                        }
+                    }))
+                }
+                None => {
+                    return Err(EvaluateError::Custom(
+                        "Only an object with a 'next' method can be iterated".to_owned(),
+                    ))
+                }
+            },
+            x => {
+                return Err(EvaluateError::Custom(format!(
+                    "Type '{}' cannot be iterated",
+                    AspenType::from(x)
+                )))
+            }
+        };
+
+        Ok(iterator)
     }
 
     pub fn get_ref_value(&self, name: &'a str) -> EvaluateResult<&AspenValue<'a>> {
@@ -122,6 +571,15 @@ impl<'a> AspenTable<'a> {
         }
     }
 
+    pub fn get_mut_value(&mut self, name: &'a str) -> EvaluateResult<&mut AspenValue<'a>> {
+        let opt_value = self.values.get_mut(name);
+
+        match opt_value {
+            Some(value) => Ok(value.inside_value_mut()),
+            None => Err(EvaluateError::UndefinedIdentifier(name.to_owned())),
+        }
+    }
+
     pub fn get_wrapped_value(&self, name: &'a str) -> EvaluateResult<&ValueWrapper<'a>> {
         let opt_value = self.values.get(name);
 
@@ -146,51 +604,92 @@ impl<'a> AspenTable<'a> {
             .unwrap_or(false)
     }
 
-    pub fn evaluate_expr(&self, expr: Expr<'a>) -> EvaluateResult<AspenValue<'a>> {
+    pub fn evaluate_expr(&mut self, expr: Expr<'a>) -> EvaluateResult<AspenValue<'a>> {
         match expr {
             Expr::Value(val) => Ok(val.into()),
             Expr::Id(name) => {
                 let value = self.get_ref_value(name)?.to_owned();
                 Ok(value)
             }
-            Expr::PropagatedFailible(expr) => match self.evaluate_expr(*expr)? {
-                AspenValue::Error(x) => {
-                    print!("{}", x);
-                    Err(EvaluateError::ProgramEndErrorPropagated)
-                }
+            Expr::PropagatedFailible { value, span } => match self.evaluate_expr(*value)? {
+                AspenValue::Variant(variant) => match variant.into_result()? {
+                    Ok(value) => Ok(value),
+                    Err(mut err) => {
+                        // the error keeps the position where it was first propagated, along with its causes
+                        err.span.get_or_insert(span);
+                        *self.propagated.borrow_mut() = Some(err);
+                        Err(EvaluateError::ErrorPropagated)
+                    }
+                },
                 x => Ok(x),
             },
             Expr::Import(name) => {
                 import_module(name).ok_or_else(|| EvaluateError::UnknownModule(name.to_owned()))
             }
-            Expr::FuncCall { callee, args } => {
-                let func_name = match *callee {
-                    Expr::Id(id) => id,
-                    Expr::ObjIndexing { indexed, indexer } => {
-                        todo!()
-                    }
-                    Expr::ArrayIndexing { indexed, indexer } => {
-                        todo!()
-                    }
-                    x => return Err(EvaluateError::OnlyFuncsCanBeCalled(x.to_string())),
-                };
+            Expr::FuncCall { callee, args } => match *callee {
+                Expr::ObjIndexing { indexed, indexer } => {
+                    self.call_method(*indexed, *indexer, args)
+                }
+                callee @ (Expr::ArrayIndexing { .. }
+                | Expr::FuncCall { .. }
+                | Expr::Parenthesized(_)
+                | Expr::PropagatedFailible { .. }) => {
+                    let func = self.evaluate_expr(callee)?;
+                    let args = self.evaluate_args(args)?;
 
-                let func = self.get_value(func_name.to_owned())?;
+                    self.call_value(func, args)
+                }
+                Expr::Id(func_name) => {
+                    let func = self.get_value(func_name.to_owned())?;
+                    let args = self.evaluate_args(args)?;
 
-                let args_result: Result<Vec<_>, _> =
-                    args.into_iter().map(|e| self.evaluate_expr(*e)).collect();
-                let args = args_result?;
+                    match func {
+                        AspenValue::Func(_)
+                        | AspenValue::RustBindFn { .. }
+                        | AspenValue::RecordType(_) => self.call_value(func, args),
+                        _ => Err(EvaluateError::IdentifierIsNotValidFn(func_name.to_owned())),
+                    }
+                }
+                x => Err(EvaluateError::OnlyFuncsCanBeCalled(x.to_string())),
+            },
+            Expr::ArrayIndexing { indexed, indexer } => {
+                let indexed = self.evaluate_expr(*indexed)?;
+                let indexer = self.evaluate_expr(*indexer)?;
 
-                match func {
-                    AspenValue::Func(f) => return Ok(f.call(self, args)?),
-                    AspenValue::RustBindFn { code, name } => return Ok(code(args)?),
-                    _ => return Err(EvaluateError::IdentifierIsNotValidFn(func_name.to_owned())),
+                match self.call_hook(&indexed, hooks::INDEX, vec![indexer.to_owned()])? {
+                    Some(value) => Ok(value),
+                    None => index_value(indexed, indexer),
+                }
+            }
+            Expr::ObjIndexing { indexed, indexer } => {
+                let name = property_name(*indexer)?;
+
+                match self.evaluate_expr(*indexed)? {
+                    AspenValue::Object(obj) => find_property(&obj, name)
+                        .cloned()
+                        .ok_or_else(|| EvaluateError::UnknownProperty(name.to_owned())),
+                    AspenValue::Record(mut record) => record
+                        .fields
+                        .swap_remove(name)
+                        .ok_or_else(|| EvaluateError::UnknownProperty(name.to_owned())),
+                    AspenValue::Variant(variant) => variant
+                        .property(name)
+                        .ok_or_else(|| EvaluateError::UnknownProperty(name.to_owned())),
+                    AspenValue::EnumType(enum_type) => enum_type.unit(name),
+                    x => Err(EvaluateError::InvalidType {
+                        expected: AspenType::Object,
+                        found: x.into(),
+                    }),
                 }
             }
             Expr::StringConcatenation { left, right } => {
                 let left = self.evaluate_expr(*left)?;
                 let right = self.evaluate_expr(*right)?;
 
+                if let Some(value) = self.call_hook(&left, hooks::CONCAT, vec![right.to_owned()])? {
+                    return Ok(value);
+                }
+
                 match (left, right) {
                     (AspenValue::Str(l), AspenValue::Str(r)) => Ok(AspenValue::Str(l + &r)),
 
@@ -208,18 +707,104 @@ impl<'a> AspenTable<'a> {
                     }
                 }
             }
+            Expr::Formatted { value, spec } => {
+                let value = match self.evaluate_expr(*value)? {
+                    value if find_hook(&value, hooks::STR).is_some() => {
+                        AspenValue::Str(self.stringify(&value)?)
+                    }
+                    value => value,
+                };
+
+                match spec {
+                    Some(spec) => Ok(AspenValue::Str(format_value(&value, &spec)?)),
+                    None => Ok(AspenValue::Str(value.to_string())),
+                }
+            }
+            Expr::If(if_expr) => {
+                let flow = match self.select_branch(*if_expr)? {
+                    Some(block) => self.execute_scoped_block(&block, None)?,
+                    None => Flow::Next,
+                };
+
+                value_of_flow(flow, "An 'if'")
+            }
+            Expr::Match(match_expr) => {
+                let flow = self.execute_match(match_expr)?;
+                value_of_flow(flow, "A 'match'")
+            }
+            Expr::Try(try_expr) => {
+                let flow = self.execute_try(*try_expr)?;
+                value_of_flow(flow, "A 'try'")
+            }
+            Expr::Loop(stmt) => match self.execute_loop(*stmt)? {
+                LoopEnd::Value(value) => Ok(value),
+                LoopEnd::Exit(_) => Err(EvaluateError::Custom(
+                    "A loop used as a value cannot be left with 'return' or by breaking an outer loop"
+                        .to_owned(),
+                )),
+            },
+            // '??' evaluates its right side when its left side is an error or raises one,
+            // the value of an 'Ok' is unwrapped like with '?'
+            Expr::Binary {
+                lhs,
+                operator: BinaryOperator::Coalesce,
+                rhs,
+            } => match self.evaluate_expr(*lhs) {
+                Ok(AspenValue::Variant(variant)) => match variant.into_result()? {
+                    Ok(value) => Ok(value),
+                    Err(_) => self.evaluate_expr(*rhs),
+                },
+                Err(error) => {
+                    self.catch_error(error)?;
+                    self.evaluate_expr(*rhs)
+                }
+                value => value,
+            },
+            Expr::Binary { lhs, operator, rhs } => {
+                let lhs = self.evaluate_expr(*lhs)?;
+
+                // '&&' and '||' do not evaluate their right side when the left side is enough
+                match (&operator, &lhs) {
+                    (BinaryOperator::And, AspenValue::Bool(false)) => {
+                        return Ok(AspenValue::Bool(false))
+                    }
+                    (BinaryOperator::Or, AspenValue::Bool(true)) => {
+                        return Ok(AspenValue::Bool(true))
+                    }
+                    _ => (),
+                }
+
+                let rhs = self.evaluate_expr(*rhs)?;
+
+                match self.call_binary_hook(&lhs, &operator, &rhs)? {
+                    Some(value) => Ok(value),
+                    None => evaluate_binary(lhs, operator, rhs),
+                }
+            }
             Expr::Parenthesized(expr) => self.evaluate_expr(*expr),
-            Expr::Range { start, end, step } => {
+            Expr::BitNot(expr) => match self.evaluate_expr(*expr)? {
+                AspenValue::Int(i) => Ok(AspenValue::Int(!i)),
+                x => Err(EvaluateError::InvalidType {
+                    expected: AspenType::Int,
+                    found: x.into(),
+                }),
+            },
+            Expr::Range {
+                start,
+                end,
+                step,
+                inclusive,
+            } => {
+                let start = self.evaluate_expr(*start)?;
+                let end = self.evaluate_expr(*end)?;
                 let step = match step {
                     None => None,
-                    Some(expr) => Some(self.evaluate_expr(*expr).map(Box::new)?),
+                    Some(expr) => Some(self.evaluate_expr(*expr)?),
                 };
 
-                Ok(AspenValue::Range {
-                    start: Box::new(self.evaluate_expr(*start)?),
-                    end: Box::new(self.evaluate_expr(*end)?),
-                    step,
-                })
+                Ok(AspenValue::Range(AspenRange::new(
+                    start, end, step, inclusive,
+                )?))
             }
             Expr::Array(exprs) => {
                 let mut args = Vec::with_capacity(exprs.len());
@@ -228,38 +813,13 @@ impl<'a> AspenTable<'a> {
                     match *expr {
                         Expr::SpeadId(id) => match self.get_value(id.to_owned())? {
                             AspenValue::Array(vals) => args.extend(vals),
-                            AspenValue::Range { start, end, step } => match (*start, *end) {
-                                (AspenValue::Int(i), AspenValue::Int(i2)) => {
-                                    //need to limit values like in 'extract_args'
-                                    let vals: Vec<_> = (i.to_i128().unwrap()
-                                        ..=i2.to_i128().unwrap())
-                                        .map(|i| AspenValue::Int(Integer::from(i)))
-                                        .collect();
-                                    args.extend(vals)
-                                }
-                                (AspenValue::Str(s), AspenValue::Str(s2)) => {
-                                    if s.len() == 1 && s2.len() == 1 {
-                                        let vals: Vec<_> = (s.chars().next().unwrap()
-                                            ..=s2.chars().next().unwrap())
-                                            .map(|s| AspenValue::Str(s.to_string()))
-                                            .collect();
-
-                                        args.extend(vals)
-                                    } else {
-                                        return Err(EvaluateError::Custom(format!(
-                                            "A range can only be generated from two numbers or two characters, \"{}\" and \"{}\" must be one character long",
-                                            s, s2
-                                        )));
-                                    }
-                                }
-                                (a, b) => {
-                                    return Err(EvaluateError::Custom(format!(
-                                        "A range cannot be created from type '{}' and type '{}'",
-                                        AspenType::from(a),
-                                        AspenType::from(b)
-                                    )));
+                            // a range is only materialised when it is spread
+                            AspenValue::Range(range) => args.extend(range.iter()),
+                            AspenValue::Iterator(iterator) => {
+                                for value in iterator {
+                                    args.push(value?);
                                 }
-                            },
+                            }
                             _ => {
                                 return Err(EvaluateError::Custom(format!(
                                     "Only arrays can be spread, '{}' is not a valid array",
@@ -273,10 +833,170 @@ impl<'a> AspenTable<'a> {
 
                 Ok(AspenValue::Array(args))
             }
-            _ => todo!(),
+            Expr::Object(props) => {
+                let mut values = AspenObject::with_capacity(props.len());
+
+                for (key, expr) in props.into_iter() {
+                    match *expr {
+                        Expr::SpeadId(id) => match self.get_value(id.to_owned())? {
+                            AspenValue::Object(obj) => values.extend(obj),
+                            _ => {
+                                return Err(EvaluateError::Custom(format!(
+                                    "Only objects can be spread in an object, '{}' is not a valid object",
+                                    id
+                                )))
+                            }
+                        },
+                        e => {
+                            values.insert(key.to_owned(), self.evaluate_expr(e)?);
+                        }
+                    }
+                }
+
+                Ok(AspenValue::Object(values))
+            }
+            Expr::SpeadId(id) => Err(EvaluateError::Custom(format!(
+                "'...{}' can only be used in an array or an object",
+                id
+            ))),
+            Expr::Assign { target, .. } => Err(EvaluateError::Custom(format!(
+                "An assignment to '{}' cannot be used as a value",
+                target
+            ))),
+        }
+    }
+
+    fn evaluate_args(
+        &mut self,
+        args: impl IntoIterator<Item = Box<Expr<'a>>>,
+    ) -> EvaluateResult<Vec<AspenValue<'a>>> {
+        args.into_iter().map(|e| self.evaluate_expr(*e)).collect()
+    }
+
+    /// Calls a function value with already evaluated arguments.
+    pub fn call_value(
+        &self,
+        func: AspenValue<'a>,
+        args: Vec<AspenValue<'a>>,
+    ) -> EvaluateResult<AspenValue<'a>> {
+        match func {
+            AspenValue::Func(f) => f.call(self, args),
+            AspenValue::RustBindFn { code, .. } => code(self, args),
+            AspenValue::RecordType(record_type) => {
+                record_type.construct(args).map(AspenValue::Record)
+            }
+            x => Err(EvaluateError::OnlyFuncsCanBeCalled(x.to_string())),
+        }
+    }
+
+    /// Evaluates `indexed.indexer(args)`.
+    ///
+    /// The function properties of an object, along its prototype chain, or of a record are looked up first,
+    /// they are called with the receiver bound to `self`. The builtin methods of the type of `indexed` come next.
+    fn call_method(
+        &mut self,
+        indexed: Expr<'a>,
+        indexer: Expr<'a>,
+        args: impl IntoIterator<Item = Box<Expr<'a>>>,
+    ) -> EvaluateResult<AspenValue<'a>> {
+        let name = property_name(indexer)?;
+        let args = self.evaluate_args(args)?;
+
+        // a method modifying a variable, or a property of a variable, changes it in place
+        if let Some((root, properties)) = property_path(&indexed) {
+            let is_constant = self.constants.contains_key(root);
+
+            if let Ok(value) = self.get_mut_property(root, &properties) {
+                if let (None, Some(Method::Mut(code))) = (
+                    find_callable_property(value, name),
+                    find_method(value, name),
+                ) {
+                    if is_constant {
+                        return Err(EvaluateError::FrozenValue {
+                            name: root.to_owned(),
+                            method: name.to_owned(),
+                        });
+                    }
+
+                    return code(value, args);
+                }
+            }
+        }
+
+        let place = property_path(&indexed);
+        let mut receiver = self.evaluate_expr(indexed)?;
+
+        match find_callable_property(&receiver, name) {
+            Some(AspenValue::Func(func)) => {
+                let func = func.to_owned();
+                let original = receiver.clone();
+                let result = func.call_method_mut(self, &mut receiver, args);
+
+                // the changes made to 'self' are stored back in the variable the method was called on
+                if let Some((root, properties)) = place {
+                    if receiver != original {
+                        if self.constants.contains_key(root) {
+                            return Err(EvaluateError::FrozenValue {
+                                name: root.to_owned(),
+                                method: name.to_owned(),
+                            });
+                        }
+
+                        // an inherited receiver is not owned by the variable, it's left untouched
+                        if let Ok(value) = self.get_mut_property(root, &properties) {
+                            *value = receiver;
+                        }
+                    }
+                }
+
+                return result;
+            }
+            Some(func) => return self.call_value(func.to_owned(), args),
+            None => (),
+        }
+
+        if let Some(method) = find_method(&receiver, name) {
+            return method.call(self, receiver, args);
+        }
+
+        match receiver {
+            AspenValue::Object(ref obj) => match find_property(obj, name) {
+                Some(value) => self.call_value(value.to_owned(), args),
+                None => Err(EvaluateError::UnknownProperty(name.to_owned())),
+            },
+            AspenValue::Record(ref record) => match record.fields.get(name) {
+                Some(value) => self.call_value(value.to_owned(), args),
+                None => Err(EvaluateError::UnknownProperty(name.to_owned())),
+            },
+            AspenValue::EnumType(enum_type) => enum_type.construct(name, args),
+            x => Err(EvaluateError::UnknownMethod {
+                ty: x.into(),
+                name: name.to_owned(),
+            }),
         }
     }
 
+    /// Declares a record type, the default values of its fields are evaluated once here.
+    pub fn insert_record(&mut self, record: Record<'a>) -> EvaluateResult<()> {
+        let Record { name, fields } = record;
+        let mut defaults = AspenObject::new();
+
+        for (field, default) in &fields {
+            if let Some(default) = default {
+                defaults.insert(field.to_string(), self.evaluate_expr(*default.to_owned())?);
+            }
+        }
+
+        self.declare(
+            name,
+            AspenValue::RecordType(RecordType {
+                name,
+                fields: fields.into_iter().map(|(field, _)| field).collect(),
+                defaults,
+            }),
+        )
+    }
+
     pub fn insert_fn(&mut self, f: Func<'a>) -> EvaluateResult<()> {
         let Func {
             name,
@@ -284,128 +1004,291 @@ impl<'a> AspenTable<'a> {
             body,
         } = f;
 
-        if self.is_identifier_used(name) {
-            return Err(EvaluateError::IdentifierAlreadyUsed(name.to_owned()));
-        }
-
-        self.values.insert(
+        self.declare(
             name,
-            ValueWrapper::CurrentContext(AspenValue::Func(AspenFn {
+            AspenValue::Func(AspenFn {
                 args: arguments,
+                is_generator: body.yields(),
                 body,
                 name,
-            })),
-        );
+            }),
+        )
+    }
+
+    pub fn insert_var(&mut self, v: Var<'a>) -> EvaluateResult<()> {
+        let Var {
+            variables,
+            value,
+            is_const,
+            span,
+        } = v;
+
+        let names = variables.names();
+        let value = self.evaluate_expr(*value)?;
+        self.bind_variables(variables, value)?;
+
+        if is_const {
+            for name in names {
+                self.constants.insert(name, span.to_owned());
+            }
+        }
 
         Ok(())
     }
 
-    pub fn insert_var(&mut self, v: Var<'a>) -> EvaluateResult<()> {
-        let Var { variables, value } = v;
+    /// Returns an error if `name` is a constant, `assignment` is the position of the assignment to it.
+    fn check_not_constant(&self, name: &str, assignment: Range<usize>) -> EvaluateResult<()> {
+        match self.constants.get(name) {
+            Some(declaration) => Err(EvaluateError::AssignToConstant {
+                name: name.to_owned(),
+                assignment,
+                declaration: declaration.to_owned(),
+            }),
+            None => Ok(()),
+        }
+    }
 
-        let true_value = Box::new(self.evaluate_expr(*value)?);
-        let vars_len = variables.len();
+    /// Declares the variables of a `let`, a `for` loop head or a function parameter, destructuring the value if needed.
+    fn bind_variables(
+        &mut self,
+        variables: Variables<'a>,
+        value: AspenValue<'a>,
+    ) -> EvaluateResult<()> {
+        match (variables, value) {
+            (Variables::Unique(name), value) => self.insert_value(name, value),
+            (
+                Variables::Destructuring { elements, rest } | Variables::Array { elements, rest },
+                AspenValue::Array(values),
+            ) => {
+                let mut values = values.into_iter();
 
-        match variables {
-            Variables::Unique(name) => {
-                if self.is_identifier_used(name) {
-                    return Err(EvaluateError::IdentifierAlreadyUsed(name.to_string()));
+                for (i, element) in elements.into_iter().enumerate() {
+                    self.bind_element(element, values.next(), || {
+                        format!("Array does not have anything at index '{}'", i)
+                    })?;
                 }
-                self.insert_value(name, *true_value)?;
-            }
-            Variables::Destructuring(names) => {
-                for (i, name) in names.into_iter().enumerate() {
-                    if self.is_identifier_used(name) {
-                        return Err(EvaluateError::IdentifierAlreadyUsed(name.to_string()));
-                    }
 
-                    match *(true_value.clone()) {
-                        AspenValue::Object(obj) => {
-                            if let Some(v) = obj.get(name) {
-                                self.insert_value(name, v.to_owned())?;
-                            } else {
-                                return Err(EvaluateError::Custom(format!(
-                                    "Object does not have a '{}' property",
-                                    name
-                                )));
-                            }
-                        }
-                        AspenValue::Array(mut arr) => {
-                            if let Some(v) = arr.get(i) {
-                                if i == vars_len - 1 {
-                                    let new_collection = arr.split_off(i);
-                                    if new_collection.len() == 1 {
-                                        self.insert_value(name, new_collection[0].to_owned())?;
-                                    } else {
-                                        self.insert_value(name, AspenValue::Array(new_collection))?;
-                                    }
-                                } else {
-                                    self.insert_value(name, v.to_owned())?;
-                                }
-                            } else {
-                                return Err(EvaluateError::Custom(format!(
-                                    "Array does not have anything at index '{}'",
-                                    i
-                                )));
-                            }
-                        }
-                        value => {
-                            if vars_len != 0 {
-                                return Err(EvaluateError::CannotUseDestructuring);
-                            }
-                            self.insert_value(name, value)?;
+                match rest {
+                    Some(name) => self.bind_variables(
+                        Variables::Unique(name),
+                        AspenValue::Array(values.collect()),
+                    ),
+                    None => Ok(()),
+                }
+            }
+            (Variables::Destructuring { elements, rest }, AspenValue::Object(mut obj)) => {
+                for element in elements {
+                    let name = match element.variables {
+                        Variables::Unique(name) => name,
+                        _ => {
+                            return Err(EvaluateError::Custom(
+                                "Only names can take the properties of an object in '(...)', use '{name! ...}' to destructure them".to_owned(),
+                            ))
                         }
+                    };
+
+                    let value = obj.shift_remove(name);
+                    self.bind_element(element, value, || {
+                        format!("Object does not have a '{}' property", name)
+                    })?;
+                }
+
+                match rest {
+                    Some(name) => {
+                        self.bind_variables(Variables::Unique(name), AspenValue::Object(obj))
                     }
+                    None => Ok(()),
                 }
             }
-        };
+            (
+                Variables::Object(properties),
+                AspenValue::Object(obj) | AspenValue::Record(AspenRecord { fields: obj, .. }),
+            ) => {
+                for (key, element) in properties {
+                    self.bind_element(element, obj.get(key).cloned(), || {
+                        format!("Object does not have a '{}' property", key)
+                    })?;
+                }
 
-        Ok(())
+                Ok(())
+            }
+            _ => Err(EvaluateError::CannotUseDestructuring),
+        }
     }
 
-    pub fn update_value(&mut self, name: &'a str, value: AspenValue<'a>) -> EvaluateResult<()> {
-        let mut previous_value = self
-            .values
-            .insert(name, ValueWrapper::CurrentContext(value));
+    /// Declares a destructured element, its default value is evaluated only if `value` is missing.
+    fn bind_element(
+        &mut self,
+        element: Destructured<'a>,
+        value: Option<AspenValue<'a>>,
+        missing_msg: impl FnOnce() -> String,
+    ) -> EvaluateResult<()> {
+        let Destructured { variables, default } = element;
 
-        if previous_value.is_none() {
-            return Err(EvaluateError::Custom(format!(
-                "Cannot assign value to undefined variable '{}'",
-                name
-            )));
-        }
+        let value = match (value, default) {
+            (Some(value), _) => value,
+            (None, Some(default)) => self.evaluate_expr(*default)?,
+            (None, None) => return Err(EvaluateError::Custom(missing_msg())),
+        };
 
-        match previous_value.take().unwrap().take() {
-            AspenValue::Func(_) => {
+        self.bind_variables(variables, value)
+    }
+
+    /// Assigns a new value to an existing variable, `assignment` is the position of the assignment operator.
+    ///
+    /// Nothing is modified if the variable is undefined, is a function or is a constant.
+    pub fn update_value(
+        &mut self,
+        name: &'a str,
+        value: AspenValue<'a>,
+        assignment: Range<usize>,
+    ) -> EvaluateResult<()> {
+        match self.values.get(name).map(ValueWrapper::inside_value) {
+            None => {
+                return Err(EvaluateError::Custom(format!(
+                    "Cannot assign value to undefined variable '{}'",
+                    name
+                )))
+            }
+            Some(AspenValue::Func(_)) => {
                 return Err(EvaluateError::Custom(format!(
                     "Cannot assign value to function '{}'",
                     name
-                )));
+                )))
             }
-            _ => (),
+            Some(_) => self.check_not_constant(name, assignment)?,
+        }
+
+        self.values
+            .insert(name, ValueWrapper::CurrentContext(value));
+
+        Ok(())
+    }
+
+    /// Sets the property `indexer` of `indexed`, a variable or a property of a variable, e.g `self.count = 1`.
+    fn assign_property(
+        &mut self,
+        indexed: Expr<'a>,
+        indexer: Expr<'a>,
+        value: AspenValue<'a>,
+    ) -> EvaluateResult<()> {
+        let property = property_name(indexer)?;
+        let (name, parents) = match property_path(&indexed) {
+            Some(path) => path,
+            None => return Err(not_assignable(indexed)),
         };
 
+        let target = self.get_mut_property(name, &parents)?;
+        properties_mut(target, property)?.insert(property.to_owned(), value);
+
         Ok(())
     }
 
+    /// Returns the variable `name`, or the property reached from it through `properties`, to modify it in place.
+    fn get_mut_property(
+        &mut self,
+        name: &'a str,
+        properties: &[&'a str],
+    ) -> EvaluateResult<&mut AspenValue<'a>> {
+        let mut target = self.get_mut_value(name)?;
+
+        for property in properties {
+            target = properties_mut(target, property)?
+                .get_mut(*property)
+                .ok_or_else(|| EvaluateError::UnknownProperty(property.to_string()))?;
+        }
+
+        Ok(target)
+    }
+
     pub fn insert_value(&mut self, name: &'a str, value: AspenValue<'a>) -> EvaluateResult<()> {
-        if self.is_identifier_used(name) {
+        self.declare(name, value)
+    }
+
+    /// Declares a name in the innermost block, it shadows a binding of an enclosing block or context.
+    ///
+    /// A name cannot be declared twice in the same block, nor twice outside of any block of a context.
+    fn declare(&mut self, name: &'a str, value: AspenValue<'a>) -> EvaluateResult<()> {
+        let is_redeclared = match self.scopes.last() {
+            Some(declared) => declared.iter().any(|d| d.name == name),
+            None => self.is_identifier_used(name),
+        };
+
+        if is_redeclared {
             return Err(EvaluateError::IdentifierAlreadyUsed(name.to_string()));
         }
 
-        self.values
+        let shadowed = self
+            .values
             .insert(name, ValueWrapper::CurrentContext(value));
+        // the new binding is not a constant, even if it shadows one
+        let shadowed_constant = self.constants.remove(name);
+
+        if let Some(declared) = self.scopes.last_mut() {
+            declared.push(Declared {
+                name,
+                shadowed,
+                shadowed_constant,
+            });
+        }
 
         Ok(())
     }
 }
 
+/// Returns the value of an `if` or a `match` used as a value, from the flow of its selected block.
+fn value_of_flow<'a>(flow: Flow<'a>, construct: &str) -> EvaluateResult<AspenValue<'a>> {
+    match flow {
+        Flow::Value(value) => Ok(value),
+        Flow::Next => Ok(AspenValue::Nil),
+        _ => Err(EvaluateError::Custom(format!(
+            "{} used as a value cannot be left with 'return', 'break' or 'continue'",
+            construct
+        ))),
+    }
+}
+
+fn not_assignable(expr: Expr<'_>) -> EvaluateError {
+    EvaluateError::Custom(format!(
+        "Value can only be assigned to variable, not value '{}'",
+        expr
+    ))
+}
+
+/// Splits a variable or a property of a variable, e.g `o.a.b`, into the name of the variable and the
+/// properties leading from it, outermost first. Returns `None` for any other expression.
+fn property_path<'a>(expr: &Expr<'a>) -> Option<(&'a str, Vec<&'a str>)> {
+    match expr {
+        Expr::Id(name) => Some((name, vec![])),
+        Expr::ObjIndexing { indexed, indexer } => match indexer.as_ref() {
+            Expr::Id(property) => {
+                let (name, mut properties) = property_path(indexed)?;
+                properties.push(property);
+                Some((name, properties))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns the name of the property accessed with `obj.name`, the parser ensures it is an identifier.
+fn property_name(indexer: Expr<'_>) -> EvaluateResult<&str> {
+    match indexer {
+        Expr::Id(name) => Ok(name),
+        x => Err(EvaluateError::Custom(format!(
+            "An object can only be accessed with an identifier, not '{}'",
+            x
+        ))),
+    }
+}
+
 impl<'a> From<Value<'a>> for AspenValue<'a> {
     fn from(value: Value<'a>) -> Self {
         match value {
             Value::Nil => AspenValue::Nil,
             Value::Int(i) => AspenValue::Int(i),
-            Value::Str(s) => AspenValue::Str(s.to_owned()),
+            Value::Str(s) => AspenValue::Str(s.to_string()),
             Value::Bool(b) => AspenValue::Bool(b),
             Value::Float(f) => AspenValue::Float(f),
         }
@@ -419,13 +1302,12 @@ impl<'a> ValueWrapper<'a> {
             Self::CurrentContext(c) => c,
         }
     }
-    pub fn take(self) -> AspenValue<'a> {
+    pub fn inside_value_mut(&mut self) -> &mut AspenValue<'a> {
         match self {
             Self::OtherContext(c) => c,
             Self::CurrentContext(c) => c,
         }
     }
-
     pub fn is_current_ctx(&self) -> bool {
         match self {
             Self::OtherContext(_) => false,
@@ -436,7 +1318,73 @@ impl<'a> ValueWrapper<'a> {
     pub fn change_to_sub(&mut self) {
         match self {
             Self::OtherContext(_) => (),
-            Self::CurrentContext(v) => *self = Self::CurrentContext(v.to_owned()),
+            Self::CurrentContext(v) => *self = Self::OtherContext(v.to_owned()),
         };
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{error_of, run, value_of};
+
+    #[test]
+    fn failed_assignments_leave_no_binding_behind() {
+        let code = r#"
+let caught try {
+    y = 1
+} catch e {
+    e
+}
+@f {
+    >> 1
+}
+let not_fn try {
+    f = 2
+} catch e {
+    e
+}
+"#;
+        let table = run(code).unwrap();
+
+        assert!(table.get_value("y".to_owned()).is_err());
+        assert_eq!(value_of(code, "f"), "Func<f>");
+        assert!(value_of(code, "not_fn").contains("function 'f'"));
+        assert!(value_of(code, "caught").contains("undefined variable 'y'"));
+    }
+
+    #[test]
+    fn try_catches_raised_and_propagated_errors() {
+        let code = r#"
+@fails {
+    let x Err("boom")?
+    >> 1
+}
+let a try {
+    fails()?
+} catch e {
+    e.message
+}
+let b 1 % 0 ?? 5
+"#;
+
+        assert_eq!(value_of(code, "a"), "boom");
+        assert_eq!(value_of(code, "b"), "5");
+    }
+
+    #[test]
+    fn assigns_properties_of_objects_and_records() {
+        let code = r#"
+let o {a! {b! 1}, c! 2}
+o.a.b = 3
+o.d = 4
+$type Point { x!, y! }
+let p Point({x! 1, y! 2})
+p.x = 5
+"#;
+
+        assert_eq!(value_of(code, "o"), "{a! {b! 3}, c! 2, d! 4}");
+        assert_eq!(value_of(code, "p"), "Point {x! 5, y! 2}");
+        assert!(error_of("$type Point { x! }\nlet p Point({x! 1})\np.z = 1").contains("'z'"));
+        assert!(error_of("let o {a! 1}\no.b.c = 1").contains("'b'"));
+    }
+}
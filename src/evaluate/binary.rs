@@ -0,0 +1,255 @@
+use super::{error::EvaluateError, value::AspenValue, EvaluateResult};
+use crate::parser::operator::BinaryOperator;
+use rug::{
+    float::OrdFloat,
+    ops::{DivRounding, Pow},
+    Float, Integer,
+};
+use std::cmp::Ordering;
+
+/// Precision of the float resulting from the division of two integers.
+const DIVISION_PRECISION: u32 = 53;
+
+/// Evaluates a binary operation, except for `&&` and `||` which are short-circuited by the caller.
+pub fn evaluate_binary<'a>(
+    lhs: AspenValue<'a>,
+    operator: BinaryOperator,
+    rhs: AspenValue<'a>,
+) -> EvaluateResult<AspenValue<'a>> {
+    match operator {
+        BinaryOperator::Equal => Ok(AspenValue::Bool(are_equal(&lhs, &rhs))),
+        BinaryOperator::NotEqual => Ok(AspenValue::Bool(!are_equal(&lhs, &rhs))),
+        BinaryOperator::GreaterThan
+        | BinaryOperator::GreaterThanOrEqual
+        | BinaryOperator::LessThan
+        | BinaryOperator::LessThanOrEqual => {
+            let ordering = match compare(&lhs, &rhs) {
+                Some(ordering) => ordering,
+                None => return Err(unsupported(operator, lhs, rhs)),
+            };
+
+            Ok(AspenValue::Bool(match operator {
+                BinaryOperator::GreaterThan => ordering.is_gt(),
+                BinaryOperator::GreaterThanOrEqual => ordering.is_ge(),
+                BinaryOperator::LessThan => ordering.is_lt(),
+                _ => ordering.is_le(),
+            }))
+        }
+        BinaryOperator::And | BinaryOperator::Or => match (lhs, rhs) {
+            (AspenValue::Bool(l), AspenValue::Bool(r)) => {
+                Ok(AspenValue::Bool(if operator == BinaryOperator::And {
+                    l && r
+                } else {
+                    l || r
+                }))
+            }
+            (lhs, rhs) => Err(unsupported(operator, lhs, rhs)),
+        },
+        BinaryOperator::BitAnd
+        | BinaryOperator::BitOr
+        | BinaryOperator::BitXor
+        | BinaryOperator::ShiftLeft
+        | BinaryOperator::ShiftRight => match (lhs, rhs) {
+            (AspenValue::Int(l), AspenValue::Int(r)) => evaluate_bitwise(l, operator, r),
+            (lhs, rhs) => Err(unsupported(operator, lhs, rhs)),
+        },
+        _ => evaluate_arithmetic(lhs, operator, rhs),
+    }
+}
+
+fn evaluate_arithmetic<'a>(
+    lhs: AspenValue<'a>,
+    operator: BinaryOperator,
+    rhs: AspenValue<'a>,
+) -> EvaluateResult<AspenValue<'a>> {
+    let result = match (lhs, rhs) {
+        (AspenValue::Int(l), AspenValue::Int(r)) => match operator {
+            BinaryOperator::Plus => AspenValue::Int(l + r),
+            BinaryOperator::Sub => AspenValue::Int(l - r),
+            BinaryOperator::Times => AspenValue::Int(l * r),
+            BinaryOperator::Divide => {
+                check_not_zero(r.is_zero())?;
+                AspenValue::Float(OrdFloat::from(Float::with_val(DIVISION_PRECISION, l) / r))
+            }
+            BinaryOperator::Modulo => {
+                check_not_zero(r.is_zero())?;
+                AspenValue::Int(l % r)
+            }
+            BinaryOperator::IntDivide => {
+                check_not_zero(r.is_zero())?;
+                AspenValue::Int(l.div_floor(r))
+            }
+            BinaryOperator::Exponent => match r.to_u32() {
+                Some(exp) => AspenValue::Int(l.pow(exp)),
+                None if r < 0 => AspenValue::Float(OrdFloat::from(
+                    Float::with_val(DIVISION_PRECISION, l).pow(r),
+                )),
+                None => return Err(EvaluateError::IntegerOverflow(r)),
+            },
+            _ => unreachable!(),
+        },
+        (AspenValue::Float(l), AspenValue::Float(r)) => {
+            float_arithmetic(Float::from(l), operator, Float::from(r))?
+        }
+        (AspenValue::Float(l), AspenValue::Int(r)) => {
+            let l = Float::from(l);
+            let r = Float::with_val(l.prec(), r);
+            float_arithmetic(l, operator, r)?
+        }
+        (AspenValue::Int(l), AspenValue::Float(r)) => {
+            let r = Float::from(r);
+            let l = Float::with_val(r.prec(), l);
+            float_arithmetic(l, operator, r)?
+        }
+        (AspenValue::Str(l), AspenValue::Str(r)) if operator == BinaryOperator::Plus => {
+            AspenValue::Str(l + &r)
+        }
+        (lhs, rhs) => return Err(unsupported(operator, lhs, rhs)),
+    };
+
+    Ok(result)
+}
+
+/// Evaluates a bitwise operation on two integers, negative integers behaving as in two's complement.
+fn evaluate_bitwise<'a>(
+    l: Integer,
+    operator: BinaryOperator,
+    r: Integer,
+) -> EvaluateResult<AspenValue<'a>> {
+    let result = match operator {
+        BinaryOperator::BitAnd => l & r,
+        BinaryOperator::BitOr => l | r,
+        BinaryOperator::BitXor => l ^ r,
+        _ => {
+            let shift = match r.to_u32() {
+                Some(shift) => shift,
+                None if r < 0 => {
+                    return Err(EvaluateError::Custom(format!(
+                        "Cannot shift by a negative amount: '{}'",
+                        r
+                    )))
+                }
+                None => return Err(EvaluateError::IntegerOverflow(r)),
+            };
+
+            match operator {
+                BinaryOperator::ShiftLeft => l << shift,
+                _ => l >> shift,
+            }
+        }
+    };
+
+    Ok(AspenValue::Int(result))
+}
+
+fn float_arithmetic<'a>(
+    l: Float,
+    operator: BinaryOperator,
+    r: Float,
+) -> EvaluateResult<AspenValue<'a>> {
+    let prec = l.prec().max(r.prec());
+
+    let result = match operator {
+        BinaryOperator::Plus => Float::with_val(prec, l + r),
+        BinaryOperator::Sub => Float::with_val(prec, l - r),
+        BinaryOperator::Times => Float::with_val(prec, l * r),
+        BinaryOperator::Divide => {
+            check_not_zero(r.is_zero())?;
+            Float::with_val(prec, l / r)
+        }
+        BinaryOperator::Modulo => {
+            check_not_zero(r.is_zero())?;
+            Float::with_val(prec, l % r)
+        }
+        BinaryOperator::IntDivide => {
+            check_not_zero(r.is_zero())?;
+            Float::with_val(prec, l / r).floor()
+        }
+        BinaryOperator::Exponent => Float::with_val(prec, l.pow(r)),
+        _ => unreachable!(),
+    };
+
+    Ok(AspenValue::Float(OrdFloat::from(result)))
+}
+
+fn check_not_zero(is_zero: bool) -> EvaluateResult<()> {
+    if is_zero {
+        return Err(EvaluateError::DivisionByZero);
+    }
+
+    Ok(())
+}
+
+/// Compares two values, integers and floats being compared by their numeric value.
+pub fn are_equal<'a>(lhs: &AspenValue<'a>, rhs: &AspenValue<'a>) -> bool {
+    match (lhs, rhs) {
+        (AspenValue::Int(i), AspenValue::Float(f)) | (AspenValue::Float(f), AspenValue::Int(i)) => {
+            f.as_float() == i
+        }
+        (l, r) => l == r,
+    }
+}
+
+/// Orders two numbers or two strings, returns `None` for any other value.
+pub fn compare(lhs: &AspenValue<'_>, rhs: &AspenValue<'_>) -> Option<Ordering> {
+    match (lhs, rhs) {
+        (AspenValue::Int(l), AspenValue::Int(r)) => Some(l.cmp(r)),
+        (AspenValue::Float(l), AspenValue::Float(r)) => l.as_float().partial_cmp(r.as_float()),
+        (AspenValue::Int(l), AspenValue::Float(r)) => l.partial_cmp(r.as_float()),
+        (AspenValue::Float(l), AspenValue::Int(r)) => l.as_float().partial_cmp(r),
+        (AspenValue::Str(l), AspenValue::Str(r)) => Some(l.cmp(r)),
+        _ => None,
+    }
+}
+
+fn unsupported(
+    operator: BinaryOperator,
+    lhs: AspenValue<'_>,
+    rhs: AspenValue<'_>,
+) -> EvaluateError {
+    EvaluateError::UnsupportedOperation {
+        operator,
+        lhs: lhs.into(),
+        rhs: rhs.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{error_of, value_of};
+
+    #[test]
+    fn bitwise_operators_on_integers() {
+        let code = r#"
+let and 12 & 10
+let or 12 | 3
+let xor 12 ^ 10
+let not ~5
+let shifted 1 << 100
+let negative -1 >> 2
+"#;
+        let value = |name| value_of(code, name);
+
+        assert_eq!(value("and"), "8");
+        assert_eq!(value("or"), "15");
+        assert_eq!(value("xor"), "6");
+        assert_eq!(value("not"), "-6");
+        assert_eq!(value("shifted"), "1267650600228229401496703205376");
+        assert_eq!(value("negative"), "-1");
+    }
+
+    #[test]
+    fn integer_division_rounds_down() {
+        assert_eq!(value_of("let a 7 // 2", "a"), "3");
+        assert_eq!(value_of("let a -7 // 2", "a"), "-4");
+        assert_eq!(value_of("let a 7.5 // 2", "a"), "3.000000");
+        assert!(error_of("let a 1 // 0").contains("Cannot divide by zero"));
+    }
+
+    #[test]
+    fn invalid_bitwise_operands() {
+        assert!(error_of("let a 1 << -1").contains("Cannot shift by a negative amount: '-1'"));
+        assert!(error_of("let a ~\"s\"").contains("expected type 'Int'"));
+        assert!(error_of("let a 1.5 & 1").contains("(&) on type 'Float'"));
+    }
+}
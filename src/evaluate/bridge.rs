@@ -0,0 +1,130 @@
+use super::{error::EvaluateError, value::AspenValue, EvaluateResult};
+use serde::{de::DeserializeOwned, Serialize};
+use std::fmt::Display;
+
+pub mod de;
+pub mod ser;
+
+/// Converts any Rust value implementing [`Serialize`] into an [`AspenValue`], e.g. to pass a struct to a script.
+#[allow(dead_code)]
+pub fn to_value<'a, T: ?Sized + Serialize>(value: &T) -> EvaluateResult<AspenValue<'a>> {
+    value.serialize(ser::Serializer::default())
+}
+
+/// Converts an [`AspenValue`] into any Rust value implementing [`serde::Deserialize`], e.g. to read a configuration script into a struct.
+#[allow(dead_code)]
+pub fn from_value<T: DeserializeOwned>(value: AspenValue<'_>) -> EvaluateResult<T> {
+    T::deserialize(value)
+}
+
+impl serde::ser::Error for EvaluateError {
+    fn custom<T: Display>(msg: T) -> Self {
+        EvaluateError::Custom(msg.to_string())
+    }
+}
+
+impl serde::de::Error for EvaluateError {
+    fn custom<T: Display>(msg: T) -> Self {
+        EvaluateError::Custom(msg.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_value, to_value};
+    use crate::{
+        evaluate::{error::EvaluateError, value::AspenValue},
+        test_utils::run,
+    };
+    use serde::{ser::SerializeStruct, Serialize, Serializer};
+    use std::collections::BTreeMap;
+
+    struct Config {
+        name: &'static str,
+        port: u16,
+        tags: Vec<&'static str>,
+        timeout: Option<f64>,
+    }
+
+    impl Serialize for Config {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut state = serializer.serialize_struct("Config", 4)?;
+            state.serialize_field("name", &self.name)?;
+            state.serialize_field("port", &self.port)?;
+            state.serialize_field("tags", &self.tags)?;
+            state.serialize_field("timeout", &self.timeout)?;
+            state.end()
+        }
+    }
+
+    #[test]
+    fn structs_become_objects_in_field_order() {
+        let config = Config {
+            name: "app",
+            port: 8080,
+            tags: vec!["a", "b"],
+            timeout: None,
+        };
+
+        let value = to_value(&config).unwrap();
+        assert_eq!(
+            value.to_string(),
+            "{name! app, port! 8080, tags! [a, b], timeout! nil}"
+        );
+    }
+
+    #[test]
+    fn rust_values_round_trip() {
+        let map = BTreeMap::from([("a".to_owned(), vec![1, 2]), ("b".to_owned(), vec![])]);
+        let back: BTreeMap<String, Vec<i64>> = from_value(to_value(&map).unwrap()).unwrap();
+        assert_eq!(back, map);
+
+        let tuple = (true, "text".to_owned(), 0.5, ());
+        let back: (bool, String, f64, ()) = from_value(to_value(&tuple).unwrap()).unwrap();
+        assert_eq!(back, tuple);
+
+        let options = vec![Some(1u8), None, Some(3)];
+        let back: Vec<Option<u8>> = from_value(to_value(&options).unwrap()).unwrap();
+        assert_eq!(back, options);
+
+        let results: Vec<Result<i32, String>> = vec![Ok(1), Err("no".to_owned())];
+        let value = to_value(&results).unwrap();
+        assert_eq!(value.to_string(), "[{Ok! 1}, {Err! no}]");
+        let back: Vec<Result<i32, String>> = from_value(value).unwrap();
+        assert_eq!(back, results);
+
+        let big = u128::MAX;
+        let back: u128 = from_value(to_value(&big).unwrap()).unwrap();
+        assert_eq!(back, big);
+    }
+
+    #[test]
+    fn scripts_are_read_into_rust_values() {
+        let table = run(r#"
+let config {name! "app", ports! [80, 443], ratio! 0.5, fallback! nil}
+let big 2 ** 100
+"#)
+        .unwrap();
+        let config = table.get_value("config".to_owned()).unwrap();
+        let AspenValue::Object(mut config) = config else {
+            panic!("config should be an object, got '{}'", config);
+        };
+
+        let name: String = from_value(config.shift_remove("name").unwrap()).unwrap();
+        let ports: Vec<u16> = from_value(config.shift_remove("ports").unwrap()).unwrap();
+        let ratio: f64 = from_value(config.shift_remove("ratio").unwrap()).unwrap();
+        let fallback: Option<i64> = from_value(config.shift_remove("fallback").unwrap()).unwrap();
+        assert_eq!(name, "app");
+        assert_eq!(ports, [80, 443]);
+        assert_eq!(ratio, 0.5);
+        assert_eq!(fallback, None);
+
+        let big = table.get_value("big".to_owned()).unwrap();
+        assert!(matches!(
+            from_value::<i64>(big.clone()),
+            Err(EvaluateError::IntegerOverflow(_))
+        ));
+        assert_eq!(from_value::<u128>(big).unwrap(), 1 << 100);
+        assert!(from_value::<u8>(AspenValue::Int(300.into())).is_err());
+    }
+}
@@ -0,0 +1,180 @@
+use super::super::{
+    error::EvaluateError,
+    types::AspenType,
+    value::{AspenObject, AspenValue},
+    EvaluateResult,
+};
+use rug::{Float, Integer};
+use serde::de::{
+    self,
+    value::{MapDeserializer, SeqDeserializer},
+    DeserializeSeed, IntoDeserializer, Visitor,
+};
+
+fn unsupported(value: AspenValue<'_>) -> EvaluateError {
+    EvaluateError::Custom(format!(
+        "A value of type '{}' cannot be converted to a Rust value",
+        AspenType::from(value)
+    ))
+}
+
+fn visit_int<'de, V: Visitor<'de>>(i: Integer, visitor: V) -> EvaluateResult<V::Value> {
+    if let Some(v) = i.to_i64() {
+        visitor.visit_i64(v)
+    } else if let Some(v) = i.to_u64() {
+        visitor.visit_u64(v)
+    } else {
+        Err(EvaluateError::IntegerOverflow(i))
+    }
+}
+
+fn visit_array<'de, 'a, V: Visitor<'de>>(
+    values: Vec<AspenValue<'a>>,
+    visitor: V,
+) -> EvaluateResult<V::Value> {
+    let mut deserializer = SeqDeserializer::new(values.into_iter());
+    let result = visitor.visit_seq(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(result)
+}
+
+fn visit_object<'de, 'a, V: Visitor<'de>>(
+    values: AspenObject<'a>,
+    visitor: V,
+) -> EvaluateResult<V::Value> {
+    let mut deserializer = MapDeserializer::new(values.into_iter());
+    let result = visitor.visit_map(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(result)
+}
+
+/// An [`AspenValue`] is a [`serde::Deserializer`]: objects can be read as structs or maps, arrays as sequences or tuples and `nil` as `()` or `None`.
+///
+/// Integers that do not fit in the requested Rust type produce an [`EvaluateError::IntegerOverflow`].
+impl<'de, 'a> de::Deserializer<'de> for AspenValue<'a> {
+    type Error = EvaluateError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> EvaluateResult<V::Value> {
+        match self {
+            AspenValue::Nil => visitor.visit_unit(),
+            AspenValue::Bool(b) => visitor.visit_bool(b),
+            AspenValue::Str(s) => visitor.visit_string(s),
+            AspenValue::Int(i) => visit_int(i, visitor),
+            AspenValue::Float(f) => visitor.visit_f64(Float::from(f).to_f64()),
+            AspenValue::Array(values) => visit_array(values, visitor),
+            AspenValue::Object(values) => visit_object(values, visitor),
+            x => Err(unsupported(x)),
+        }
+    }
+
+    fn deserialize_i128<V: Visitor<'de>>(self, visitor: V) -> EvaluateResult<V::Value> {
+        match self {
+            AspenValue::Int(i) => match i.to_i128() {
+                Some(v) => visitor.visit_i128(v),
+                None => Err(EvaluateError::IntegerOverflow(i)),
+            },
+            x => x.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_u128<V: Visitor<'de>>(self, visitor: V) -> EvaluateResult<V::Value> {
+        match self {
+            AspenValue::Int(i) => match i.to_u128() {
+                Some(v) => visitor.visit_u128(v),
+                None => Err(EvaluateError::IntegerOverflow(i)),
+            },
+            x => x.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> EvaluateResult<V::Value> {
+        match self {
+            AspenValue::Nil => visitor.visit_none(),
+            x => visitor.visit_some(x),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> EvaluateResult<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> EvaluateResult<V::Value> {
+        match self {
+            AspenValue::Str(variant) => visitor.visit_enum(variant.into_deserializer()),
+            AspenValue::Object(values) if values.len() == 1 => {
+                let (variant, value) = values.into_iter().next().unwrap();
+                visitor.visit_enum(EnumDeserializer { variant, value })
+            }
+            x => Err(EvaluateError::Custom(format!(
+                "An enum is expected to be a string or an object with a single key, found type '{}'",
+                AspenType::from(x)
+            ))),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl<'de, 'a> IntoDeserializer<'de, EvaluateError> for AspenValue<'a> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// Reads an enum variant written as `{variant! value}`.
+struct EnumDeserializer<'a> {
+    variant: String,
+    value: AspenValue<'a>,
+}
+
+impl<'de, 'a> de::EnumAccess<'de> for EnumDeserializer<'a> {
+    type Error = EvaluateError;
+    type Variant = AspenValue<'a>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> EvaluateResult<(V::Value, AspenValue<'a>)> {
+        let variant = seed.deserialize(self.variant.into_deserializer())?;
+        Ok((variant, self.value))
+    }
+}
+
+impl<'de, 'a> de::VariantAccess<'de> for AspenValue<'a> {
+    type Error = EvaluateError;
+
+    fn unit_variant(self) -> EvaluateResult<()> {
+        de::Deserialize::deserialize(self)
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> EvaluateResult<T::Value> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> EvaluateResult<V::Value> {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> EvaluateResult<V::Value> {
+        de::Deserializer::deserialize_map(self, visitor)
+    }
+}
@@ -0,0 +1,538 @@
+use super::super::{
+    error::EvaluateError,
+    value::{AspenObject, AspenValue},
+    EvaluateResult,
+};
+use rug::{float::OrdFloat, Float, Integer};
+use serde::ser::{self, Impossible, Serialize};
+use std::marker::PhantomData;
+
+/// Precision used for floats coming from Rust, enough to hold an `f64` without any loss.
+const F64_PRECISION: u32 = 53;
+
+/// A [`serde::Serializer`] whose output is an [`AspenValue`].
+///
+/// Structs and maps become objects, sequences and tuples become arrays and `None` or `()` become `nil`.
+/// Enum variants are represented like `serde_json` does: a unit variant is a string, any other variant is an object with a single key, the variant name.
+#[derive(Default)]
+pub struct Serializer<'a> {
+    marker: PhantomData<AspenValue<'a>>,
+}
+
+pub struct SerializeArray<'a> {
+    values: Vec<AspenValue<'a>>,
+    variant: Option<&'static str>,
+}
+
+pub struct SerializeObject<'a> {
+    values: AspenObject<'a>,
+    next_key: Option<String>,
+    variant: Option<&'static str>,
+}
+
+fn float<'a>(value: f64) -> AspenValue<'a> {
+    AspenValue::Float(OrdFloat::from(Float::with_val(F64_PRECISION, value)))
+}
+
+/// Wraps the value of an enum variant in an object: `{variant! value}`.
+fn wrap_variant<'a>(variant: Option<&'static str>, value: AspenValue<'a>) -> AspenValue<'a> {
+    match variant {
+        Some(name) => {
+            let mut hashmap = AspenObject::new();
+            hashmap.insert(name.to_owned(), value);
+            AspenValue::Object(hashmap)
+        }
+        None => value,
+    }
+}
+
+impl<'a> ser::Serializer for Serializer<'a> {
+    type Ok = AspenValue<'a>;
+    type Error = EvaluateError;
+
+    type SerializeSeq = SerializeArray<'a>;
+    type SerializeTuple = SerializeArray<'a>;
+    type SerializeTupleStruct = SerializeArray<'a>;
+    type SerializeTupleVariant = SerializeArray<'a>;
+    type SerializeMap = SerializeObject<'a>;
+    type SerializeStruct = SerializeObject<'a>;
+    type SerializeStructVariant = SerializeObject<'a>;
+
+    fn serialize_bool(self, v: bool) -> EvaluateResult<AspenValue<'a>> {
+        Ok(AspenValue::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> EvaluateResult<AspenValue<'a>> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> EvaluateResult<AspenValue<'a>> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> EvaluateResult<AspenValue<'a>> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> EvaluateResult<AspenValue<'a>> {
+        Ok(AspenValue::Int(Integer::from(v)))
+    }
+
+    fn serialize_i128(self, v: i128) -> EvaluateResult<AspenValue<'a>> {
+        Ok(AspenValue::Int(Integer::from(v)))
+    }
+
+    fn serialize_u8(self, v: u8) -> EvaluateResult<AspenValue<'a>> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> EvaluateResult<AspenValue<'a>> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> EvaluateResult<AspenValue<'a>> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> EvaluateResult<AspenValue<'a>> {
+        Ok(AspenValue::Int(Integer::from(v)))
+    }
+
+    fn serialize_u128(self, v: u128) -> EvaluateResult<AspenValue<'a>> {
+        Ok(AspenValue::Int(Integer::from(v)))
+    }
+
+    fn serialize_f32(self, v: f32) -> EvaluateResult<AspenValue<'a>> {
+        Ok(float(f64::from(v)))
+    }
+
+    fn serialize_f64(self, v: f64) -> EvaluateResult<AspenValue<'a>> {
+        Ok(float(v))
+    }
+
+    fn serialize_char(self, v: char) -> EvaluateResult<AspenValue<'a>> {
+        Ok(AspenValue::Str(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> EvaluateResult<AspenValue<'a>> {
+        Ok(AspenValue::Str(v.to_owned()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> EvaluateResult<AspenValue<'a>> {
+        Ok(AspenValue::Array(
+            v.iter()
+                .map(|b| AspenValue::Int(Integer::from(*b)))
+                .collect(),
+        ))
+    }
+
+    fn serialize_none(self) -> EvaluateResult<AspenValue<'a>> {
+        Ok(AspenValue::Nil)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> EvaluateResult<AspenValue<'a>> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> EvaluateResult<AspenValue<'a>> {
+        Ok(AspenValue::Nil)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> EvaluateResult<AspenValue<'a>> {
+        Ok(AspenValue::Nil)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> EvaluateResult<AspenValue<'a>> {
+        Ok(AspenValue::Str(variant.to_owned()))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> EvaluateResult<AspenValue<'a>> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> EvaluateResult<AspenValue<'a>> {
+        Ok(wrap_variant(Some(variant), value.serialize(self)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> EvaluateResult<SerializeArray<'a>> {
+        Ok(SerializeArray {
+            values: Vec::with_capacity(len.unwrap_or(0)),
+            variant: None,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> EvaluateResult<SerializeArray<'a>> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> EvaluateResult<SerializeArray<'a>> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> EvaluateResult<SerializeArray<'a>> {
+        Ok(SerializeArray {
+            values: Vec::with_capacity(len),
+            variant: Some(variant),
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> EvaluateResult<SerializeObject<'a>> {
+        Ok(SerializeObject {
+            values: AspenObject::with_capacity(len.unwrap_or(0)),
+            next_key: None,
+            variant: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> EvaluateResult<SerializeObject<'a>> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> EvaluateResult<SerializeObject<'a>> {
+        Ok(SerializeObject {
+            values: AspenObject::with_capacity(len),
+            next_key: None,
+            variant: Some(variant),
+        })
+    }
+}
+
+impl<'a> SerializeArray<'a> {
+    fn push<T: ?Sized + Serialize>(&mut self, value: &T) -> EvaluateResult<()> {
+        self.values.push(value.serialize(Serializer::default())?);
+        Ok(())
+    }
+
+    fn finish(self) -> EvaluateResult<AspenValue<'a>> {
+        Ok(wrap_variant(self.variant, AspenValue::Array(self.values)))
+    }
+}
+
+impl<'a> ser::SerializeSeq for SerializeArray<'a> {
+    type Ok = AspenValue<'a>;
+    type Error = EvaluateError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> EvaluateResult<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> EvaluateResult<AspenValue<'a>> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeTuple for SerializeArray<'a> {
+    type Ok = AspenValue<'a>;
+    type Error = EvaluateError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> EvaluateResult<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> EvaluateResult<AspenValue<'a>> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeTupleStruct for SerializeArray<'a> {
+    type Ok = AspenValue<'a>;
+    type Error = EvaluateError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> EvaluateResult<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> EvaluateResult<AspenValue<'a>> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeTupleVariant for SerializeArray<'a> {
+    type Ok = AspenValue<'a>;
+    type Error = EvaluateError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> EvaluateResult<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> EvaluateResult<AspenValue<'a>> {
+        self.finish()
+    }
+}
+
+impl<'a> SerializeObject<'a> {
+    fn insert<T: ?Sized + Serialize>(&mut self, key: String, value: &T) -> EvaluateResult<()> {
+        self.values
+            .insert(key, value.serialize(Serializer::default())?);
+        Ok(())
+    }
+
+    fn finish(self) -> EvaluateResult<AspenValue<'a>> {
+        Ok(wrap_variant(self.variant, AspenValue::Object(self.values)))
+    }
+}
+
+impl<'a> ser::SerializeMap for SerializeObject<'a> {
+    type Ok = AspenValue<'a>;
+    type Error = EvaluateError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> EvaluateResult<()> {
+        self.next_key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> EvaluateResult<()> {
+        let key = self.next_key.take().ok_or_else(|| {
+            EvaluateError::Custom("A map value was serialized before its key".to_owned())
+        })?;
+        self.insert(key, value)
+    }
+
+    fn end(self) -> EvaluateResult<AspenValue<'a>> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeStruct for SerializeObject<'a> {
+    type Ok = AspenValue<'a>;
+    type Error = EvaluateError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> EvaluateResult<()> {
+        self.insert(key.to_owned(), value)
+    }
+
+    fn end(self) -> EvaluateResult<AspenValue<'a>> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeStructVariant for SerializeObject<'a> {
+    type Ok = AspenValue<'a>;
+    type Error = EvaluateError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> EvaluateResult<()> {
+        self.insert(key.to_owned(), value)
+    }
+
+    fn end(self) -> EvaluateResult<AspenValue<'a>> {
+        self.finish()
+    }
+}
+
+/// Serializes the keys of a map, object keys can only be strings so numbers and chars are converted.
+struct KeySerializer;
+
+fn invalid_key() -> EvaluateError {
+    EvaluateError::Custom("An object key must be a string, a char or a number".to_owned())
+}
+
+impl ser::Serializer for KeySerializer {
+    type Ok = String;
+    type Error = EvaluateError;
+
+    type SerializeSeq = Impossible<String, EvaluateError>;
+    type SerializeTuple = Impossible<String, EvaluateError>;
+    type SerializeTupleStruct = Impossible<String, EvaluateError>;
+    type SerializeTupleVariant = Impossible<String, EvaluateError>;
+    type SerializeMap = Impossible<String, EvaluateError>;
+    type SerializeStruct = Impossible<String, EvaluateError>;
+    type SerializeStructVariant = Impossible<String, EvaluateError>;
+
+    fn serialize_bool(self, v: bool) -> EvaluateResult<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i8(self, v: i8) -> EvaluateResult<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i16(self, v: i16) -> EvaluateResult<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i32(self, v: i32) -> EvaluateResult<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i64(self, v: i64) -> EvaluateResult<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i128(self, v: i128) -> EvaluateResult<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u8(self, v: u8) -> EvaluateResult<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u16(self, v: u16) -> EvaluateResult<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u32(self, v: u32) -> EvaluateResult<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u64(self, v: u64) -> EvaluateResult<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u128(self, v: u128) -> EvaluateResult<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_f32(self, _v: f32) -> EvaluateResult<String> {
+        Err(invalid_key())
+    }
+
+    fn serialize_f64(self, _v: f64) -> EvaluateResult<String> {
+        Err(invalid_key())
+    }
+
+    fn serialize_char(self, v: char) -> EvaluateResult<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_str(self, v: &str) -> EvaluateResult<String> {
+        Ok(v.to_owned())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> EvaluateResult<String> {
+        Err(invalid_key())
+    }
+
+    fn serialize_none(self) -> EvaluateResult<String> {
+        Err(invalid_key())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> EvaluateResult<String> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> EvaluateResult<String> {
+        Err(invalid_key())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> EvaluateResult<String> {
+        Err(invalid_key())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> EvaluateResult<String> {
+        Ok(variant.to_owned())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> EvaluateResult<String> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> EvaluateResult<String> {
+        Err(invalid_key())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> EvaluateResult<Self::SerializeSeq> {
+        Err(invalid_key())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> EvaluateResult<Self::SerializeTuple> {
+        Err(invalid_key())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> EvaluateResult<Self::SerializeTupleStruct> {
+        Err(invalid_key())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> EvaluateResult<Self::SerializeTupleVariant> {
+        Err(invalid_key())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> EvaluateResult<Self::SerializeMap> {
+        Err(invalid_key())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> EvaluateResult<Self::SerializeStruct> {
+        Err(invalid_key())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> EvaluateResult<Self::SerializeStructVariant> {
+        Err(invalid_key())
+    }
+}
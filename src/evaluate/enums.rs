@@ -0,0 +1,276 @@
+use super::{
+    err::AspenErr, error::EvaluateError, types::AspenType, value::AspenValue, EvaluateResult,
+};
+use crate::parser::enum_decl::{Enum, Variant};
+use std::fmt;
+
+/// The built-in enum of `Ok(value)` and `Err(message)`, an error value is its `Err` variant.
+pub const RESULT: &str = "Result";
+
+/// An enum declared with `$enum`, `Shape.Circle(1)` creates a value of its `Circle` variant.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumType<'a> {
+    pub name: &'a str,
+    pub variants: Vec<Variant<'a>>,
+}
+
+/// A value of an enum variant, along with its payload.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AspenVariant<'a> {
+    pub enum_name: &'a str,
+    pub name: &'a str,
+    /// Names of the values of the payload, `circle.r` is the value named `r`.
+    pub fields: Vec<&'a str>,
+    pub payload: Vec<AspenValue<'a>>,
+    /// The error held by the `Err` variant of `Result`, its message is the payload of the variant.
+    pub error: Option<Box<AspenErr<'a>>>,
+}
+
+impl<'a> EnumType<'a> {
+    /// Returns the built-in `Result` enum.
+    pub fn result() -> Self {
+        Self {
+            name: RESULT,
+            variants: vec![
+                Variant {
+                    name: "Ok",
+                    fields: vec!["value"],
+                },
+                Variant {
+                    name: "Err",
+                    fields: vec!["message"],
+                },
+            ],
+        }
+    }
+
+    fn variant(&self, name: &str) -> EvaluateResult<&Variant<'a>> {
+        self.variants
+            .iter()
+            .find(|variant| variant.name == name)
+            .ok_or_else(|| EvaluateError::UnknownVariant {
+                enum_name: self.name.to_owned(),
+                name: name.to_owned(),
+            })
+    }
+
+    /// Returns the value of `Shape.Empty`, a variant without payload.
+    pub fn unit(&self, name: &str) -> EvaluateResult<AspenValue<'a>> {
+        let variant = self.variant(name)?;
+
+        if !variant.fields.is_empty() {
+            return Err(EvaluateError::Custom(format!(
+                "Variant '{}.{}' has a payload, create it with '{}.{}({})'",
+                self.name,
+                name,
+                self.name,
+                name,
+                variant.fields.join(", ")
+            )));
+        }
+
+        self.construct(name, vec![])
+    }
+
+    /// Creates a value of the variant from the values of its payload: `Shape.Circle(1)`.
+    pub fn construct(
+        &self,
+        name: &str,
+        payload: Vec<AspenValue<'a>>,
+    ) -> EvaluateResult<AspenValue<'a>> {
+        let variant = self.variant(name)?;
+
+        if self.name == RESULT && variant.name == "Err" {
+            return Ok(AspenErr::from_args(payload)?.into());
+        }
+
+        let expected_num = variant.fields.len();
+        match payload.len() {
+            found if found < expected_num => {
+                return Err(EvaluateError::NotEnoughArgs {
+                    expected_num,
+                    found,
+                })
+            }
+            found if found > expected_num => {
+                return Err(EvaluateError::TooMuchArgs {
+                    expected_num,
+                    found,
+                })
+            }
+            _ => (),
+        }
+
+        Ok(AspenValue::Variant(AspenVariant {
+            enum_name: self.name,
+            name: variant.name,
+            fields: variant.fields.to_owned(),
+            payload,
+            error: None,
+        }))
+    }
+}
+
+impl<'a> AspenVariant<'a> {
+    /// Returns the value of `Ok(value)`.
+    pub fn ok(value: AspenValue<'a>) -> Self {
+        Self {
+            enum_name: RESULT,
+            name: "Ok",
+            fields: vec!["value"],
+            payload: vec![value],
+            error: None,
+        }
+    }
+
+    /// Returns the value of `Err(message)` holding `err`.
+    pub fn err(err: AspenErr<'a>) -> Self {
+        Self {
+            enum_name: RESULT,
+            name: "Err",
+            fields: vec!["message"],
+            payload: vec![AspenValue::Str(err.message.to_owned())],
+            error: Some(Box::new(err)),
+        }
+    }
+
+    /// Returns the value wrapped in `Ok(value)` or the error held by `Err(message)`.
+    ///
+    /// The variants of other enums are not results, they are a type error.
+    pub fn into_result(self) -> EvaluateResult<Result<AspenValue<'a>, AspenErr<'a>>> {
+        match (self.enum_name, self.name, self.error) {
+            (RESULT, "Err", Some(err)) => Ok(Err(*err)),
+            (RESULT, "Ok", None) => Ok(Ok(self
+                .payload
+                .into_iter()
+                .next()
+                .unwrap_or(AspenValue::Nil))),
+            (enum_name, ..) => Err(EvaluateError::InvalidType {
+                expected: AspenType::Enum(RESULT.to_owned()),
+                found: AspenType::Enum(enum_name.to_owned()),
+            }),
+        }
+    }
+
+    /// Returns the value of the payload named `name`, an error also has a kind, a payload and a cause.
+    pub fn property(&self, name: &str) -> Option<AspenValue<'a>> {
+        if let Some(err) = &self.error {
+            return err.property(name);
+        }
+
+        let index = self.fields.iter().position(|field| *field == name)?;
+        self.payload.get(index).cloned()
+    }
+}
+
+impl<'a> From<Enum<'a>> for EnumType<'a> {
+    fn from(enum_decl: Enum<'a>) -> Self {
+        let Enum { name, variants } = enum_decl;
+        Self { name, variants }
+    }
+}
+
+impl<'a> fmt::Display for AspenVariant<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(err) = &self.error {
+            return write!(f, "Err({})", err);
+        }
+
+        // the variants of 'Result' are written without it, like 'Err(...)'
+        if self.enum_name != RESULT {
+            write!(f, "{}.", self.enum_name)?;
+        }
+        write!(f, "{}", self.name)?;
+
+        if self.payload.is_empty() {
+            return Ok(());
+        }
+
+        write!(f, "(")?;
+        for (i, value) in self.payload.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", value)?;
+        }
+        write!(f, ")")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{error_of, value_of};
+
+    #[test]
+    fn err_patterns_bind_the_message() {
+        let code = r#"
+let e Err("x")
+let described match e {
+    Err(m) => "err {m}",
+    _ => "ok"
+}
+"#;
+
+        assert_eq!(value_of(code, "described"), "err x");
+    }
+
+    #[test]
+    fn both_err_spellings_give_the_same_value() {
+        let code = r#"
+let a Err("x", {kind! "NotFound"})
+let b Result.Err("x", {kind! "NotFound"})
+let same a == b
+"#;
+
+        assert_eq!(value_of(code, "same"), "true");
+        assert_eq!(value_of(code, "a"), value_of(code, "b"));
+    }
+
+    #[test]
+    fn variants_carry_their_payload() {
+        let code = r#"
+$enum Shape { Circle(r), Square(side), Empty }
+let c Shape.Circle(2)
+let area match c {
+    Shape.Circle(r) => r * r * 3,
+    Shape.Square(s) => s * s,
+    _ => 0
+}
+let r c.r
+let ok Ok(1)
+let unwrapped ok?
+"#;
+
+        assert_eq!(value_of(code, "c"), "Shape.Circle(2)");
+        assert_eq!(value_of(code, "area"), "12");
+        assert_eq!(value_of(code, "r"), "2");
+        assert_eq!(value_of(code, "unwrapped"), "1");
+    }
+
+    #[test]
+    fn errors_are_variants_of_result() {
+        let code = r#"
+let e Result.Err("x", {kind! "NotFound"})
+let described match e {
+    Result.Err(m) => "err {m}",
+    Result.Ok(v) => "ok {v}"
+}
+let kind e.kind
+let recovered e ?? 2
+"#;
+
+        assert_eq!(value_of(code, "described"), "err x");
+        assert_eq!(value_of(code, "kind"), "NotFound");
+        assert_eq!(value_of(code, "recovered"), "2");
+        assert_eq!(error_of(&format!("{}e?\n", code)), "NotFound: x");
+    }
+
+    #[test]
+    fn other_variants_are_not_results() {
+        let code = "$enum Shape { Empty }\nlet s Shape.Empty\n";
+        let expected = "expected type 'Result' found type 'Shape'";
+
+        assert!(error_of(&format!("{}s?\n", code)).contains(expected));
+        assert!(error_of(&format!("{}let x s ?? 1\n", code)).contains(expected));
+    }
+}
@@ -0,0 +1,205 @@
+use super::{
+    enums::AspenVariant,
+    error::EvaluateError,
+    value::{AspenObject, AspenValue},
+    EvaluateResult,
+};
+use std::{fmt, ops::Range};
+
+/// The kind of an error created without one.
+pub const DEFAULT_KIND: &str = "Error";
+
+/// An error value, created with `Err(message, {kind!, payload!, cause!})` or returned by a failing native function.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AspenErr<'a> {
+    /// Used to tell errors apart, e.g in a `match`.
+    pub kind: String,
+    pub message: String,
+    /// Values describing what went wrong.
+    pub payload: Option<AspenObject<'a>>,
+    /// Position of the first `?` the error was propagated through.
+    pub span: Option<Range<usize>>,
+    /// The error this one was created from.
+    pub cause: Option<Box<AspenErr<'a>>>,
+}
+
+impl<'a> AspenErr<'a> {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            kind: DEFAULT_KIND.to_owned(),
+            message: message.into(),
+            payload: None,
+            span: None,
+            cause: None,
+        }
+    }
+
+    /// Creates an error from the arguments of `Err`: a message followed by an optional object of options.
+    pub fn from_args(args: Vec<AspenValue<'a>>) -> EvaluateResult<Self> {
+        if args.len() > 2 {
+            return Err(EvaluateError::TooMuchArgs {
+                expected_num: 2,
+                found: args.len(),
+            });
+        }
+
+        let mut args = args.into_iter();
+
+        let mut err = match args.next() {
+            Some(AspenValue::Str(message)) => Self::new(message),
+            Some(value) => Self::new(value.to_string()),
+            None => Self::new(""),
+        };
+
+        let options = match args.next() {
+            Some(AspenValue::Object(options)) => options,
+            Some(_) => {
+                return Err(EvaluateError::Custom(
+                    "The options of 'Err' must be an object, e.g {kind! \"NotFound\"}".to_owned(),
+                ))
+            }
+            None => return Ok(err),
+        };
+
+        for (key, value) in options {
+            match (key.as_str(), value) {
+                ("kind", AspenValue::Str(kind)) => err.kind = kind,
+                ("payload", AspenValue::Object(payload)) => err.payload = Some(payload),
+                (
+                    "cause",
+                    AspenValue::Variant(AspenVariant {
+                        error: Some(cause), ..
+                    }),
+                ) => err.cause = Some(cause),
+                ("cause", AspenValue::Nil) => (),
+                ("kind" | "payload" | "cause", value) => {
+                    return Err(EvaluateError::Custom(format!(
+                        "Invalid '{}' option of 'Err': '{}'",
+                        key, value
+                    )))
+                }
+                _ => {
+                    return Err(EvaluateError::Custom(format!(
+                        "Unknown option '{}' of 'Err', expected 'kind', 'payload' or 'cause'",
+                        key
+                    )))
+                }
+            }
+        }
+
+        Ok(err)
+    }
+
+    /// Returns the value of `err.name`.
+    pub fn property(&self, name: &str) -> Option<AspenValue<'a>> {
+        let value = match name {
+            "kind" => AspenValue::Str(self.kind.to_owned()),
+            "message" => AspenValue::Str(self.message.to_owned()),
+            "payload" => self
+                .payload
+                .to_owned()
+                .map_or(AspenValue::Nil, AspenValue::Object),
+            "cause" => self
+                .cause
+                .to_owned()
+                .map_or(AspenValue::Nil, |cause| AspenValue::from(*cause)),
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    /// Returns the properties of the error as an object, used to match it against an object pattern.
+    pub fn properties(&self) -> AspenObject<'a> {
+        ["kind", "message", "payload", "cause"]
+            .into_iter()
+            .filter_map(|name| Some((name.to_owned(), self.property(name)?)))
+            .collect()
+    }
+
+    /// Returns the error followed by its causes.
+    pub fn chain(&self) -> impl Iterator<Item = &AspenErr<'a>> {
+        std::iter::successors(Some(self), |err| err.cause.as_deref())
+    }
+
+    /// Describes the error without its kind: its message followed by its payload.
+    pub fn description(&self) -> String {
+        match &self.payload {
+            Some(payload) => format!(
+                "{} {}",
+                self.message,
+                AspenValue::Object(payload.to_owned())
+            ),
+            None => self.message.to_owned(),
+        }
+    }
+}
+
+/// A native error caught by a `try` or a `??`.
+impl<'a> From<EvaluateError> for AspenErr<'a> {
+    fn from(error: EvaluateError) -> Self {
+        Self {
+            kind: error.kind().to_owned(),
+            ..Self::new(error.to_string())
+        }
+    }
+}
+
+impl<'a> fmt::Display for AspenErr<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.kind, self.description())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{error_of, value_of};
+
+    #[test]
+    fn errors_have_a_kind_a_payload_and_a_cause() {
+        let code = r#"
+let missing Err("file not found", {kind! "NotFound", payload! {path! "/tmp/x"}})
+let wrapped Err("cannot load config", {kind! "Config", cause! missing})
+let plain Err("boom")
+let kind plain.kind
+let path missing.payload.path
+let cause wrapped.cause.kind
+let text "{missing}"
+"#;
+        let value = |name| value_of(code, name);
+
+        assert_eq!(value("kind"), "Error");
+        assert_eq!(value("path"), "/tmp/x");
+        assert_eq!(value("cause"), "NotFound");
+        assert_eq!(
+            value("text"),
+            "Err(NotFound: file not found {path! /tmp/x})"
+        );
+    }
+
+    #[test]
+    fn errors_are_matched_on_their_properties() {
+        let code = r#"
+let missing Err("file not found", {kind! "NotFound"})
+let wrapped Err("cannot load config", {kind! "Config", cause! missing})
+let text match wrapped {
+    {kind! "NotFound"} => "not found",
+    {kind! "Config", cause! {kind! k}} => "config because of {k}",
+    _ => "other"
+}
+"#;
+
+        assert_eq!(value_of(code, "text"), "config because of NotFound");
+        assert_eq!(
+            error_of(&format!("{}wrapped?\n", code)),
+            "Config: cannot load config"
+        );
+    }
+
+    #[test]
+    fn invalid_error_options() {
+        assert!(error_of(r#"let e Err("a", 1)"#).contains("must be an object"));
+        assert!(error_of(r#"let e Err("a", {kind! 1})"#).contains("Invalid 'kind' option"));
+        assert!(error_of(r#"let e Err("a", {code! 1})"#).contains("Unknown option 'code'"));
+    }
+}
@@ -1,23 +1,70 @@
 use super::types::AspenType;
-use std::{error::Error, fmt};
+use crate::parser::operator::BinaryOperator;
+use rug::Integer;
+use std::{error::Error, fmt, ops::Range};
 
 #[derive(Debug)]
 pub enum EvaluateError {
-    ProgramEndErrorPropagated,
+    /// An error value was propagated with `?`, the value is kept by the context until it is caught.
+    ErrorPropagated,
 
     UnknownModule(String),
     CannotUseDestructuring,
 
     UndefinedIdentifier(String),
     IdentifierAlreadyUsed(String),
+    AssignToConstant {
+        name: String,
+        assignment: Range<usize>,
+        declaration: Range<usize>,
+    },
+    /// A method modifying its value was called on a constant.
+    FrozenValue {
+        name: String,
+        method: String,
+    },
 
     Custom(String),
+    /// The state of the interpreter is inconsistent, it is a bug of the interpreter rather than of the script.
+    Internal(String),
 
     InvalidType {
         expected: AspenType,
         found: AspenType,
     },
 
+    IntegerOverflow(Integer),
+    IndexOutOfBounds {
+        index: Integer,
+        len: usize,
+    },
+    DivisionByZero,
+    UnsupportedOperation {
+        operator: BinaryOperator,
+        lhs: AspenType,
+        rhs: AspenType,
+    },
+
+    UnknownProperty(String),
+    /// A record was created with a field its type does not declare.
+    UnknownField {
+        record: String,
+        field: String,
+    },
+    UnknownVariant {
+        enum_name: String,
+        name: String,
+    },
+    /// A record was created without one of the fields of its type.
+    MissingField {
+        record: String,
+        field: String,
+    },
+    UnknownMethod {
+        ty: AspenType,
+        name: String,
+    },
+
     IdentifierIsNotValidFn(String),
     OnlyFuncsCanBeCalled(String),
     NotEnoughArgs {
@@ -32,21 +79,63 @@ pub enum EvaluateError {
 
 impl<'a> Error for EvaluateError {}
 
+impl EvaluateError {
+    /// The kind of the error value it becomes when it is caught.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            EvaluateError::ErrorPropagated | EvaluateError::Custom(_) => "Error",
+            EvaluateError::Internal(_) => "InternalError",
+            EvaluateError::UnknownModule(_) => "ImportError",
+            EvaluateError::UndefinedIdentifier(_) | EvaluateError::IdentifierAlreadyUsed(_) => {
+                "NameError"
+            }
+            EvaluateError::CannotUseDestructuring
+            | EvaluateError::InvalidType { .. }
+            | EvaluateError::UnsupportedOperation { .. }
+            | EvaluateError::IdentifierIsNotValidFn(_)
+            | EvaluateError::OnlyFuncsCanBeCalled(_) => "TypeError",
+            EvaluateError::AssignToConstant { .. } | EvaluateError::FrozenValue { .. } => {
+                "ConstantError"
+            }
+            EvaluateError::IntegerOverflow(_) => "OverflowError",
+            EvaluateError::IndexOutOfBounds { .. } => "IndexError",
+            EvaluateError::DivisionByZero => "ZeroDivisionError",
+            EvaluateError::UnknownProperty(_)
+            | EvaluateError::UnknownMethod { .. }
+            | EvaluateError::UnknownField { .. }
+            | EvaluateError::UnknownVariant { .. }
+            | EvaluateError::MissingField { .. } => "PropertyError",
+            EvaluateError::NotEnoughArgs { .. } | EvaluateError::TooMuchArgs { .. } => "ArityError",
+        }
+    }
+}
+
 impl fmt::Display for EvaluateError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            EvaluateError::ProgramEndErrorPropagated => write!(f, ""),
+            EvaluateError::ErrorPropagated => write!(f, "An error was propagated with '?'"),
             EvaluateError::UnknownModule(name) => write!(f, "Unknown module '{}'", name),
             EvaluateError::CannotUseDestructuring => {
                 write!(f, "Destructuring can only be used on arrays and objects")
             }
             EvaluateError::Custom(s) => write!(f, "{}", s),
+            EvaluateError::Internal(s) => write!(f, "Internal error: {}", s),
             EvaluateError::IdentifierAlreadyUsed(name) => {
                 write!(f, "Identifier already in use: '{}'", name)
             }
             EvaluateError::UndefinedIdentifier(name) => {
                 write!(f, "Unknown variable or function: '{}'", name)
             }
+            EvaluateError::AssignToConstant { name, .. } => {
+                write!(f, "Cannot assign a value to constant '{}'", name)
+            }
+            EvaluateError::FrozenValue { name, method } => {
+                write!(
+                    f,
+                    "Cannot modify constant '{}' with method '{}', its value is frozen",
+                    name, method
+                )
+            }
             EvaluateError::InvalidType { expected, found } => {
                 write!(
                     f,
@@ -54,6 +143,42 @@ impl fmt::Display for EvaluateError {
                     expected, found
                 )
             }
+            EvaluateError::IntegerOverflow(i) => {
+                write!(f, "Integer '{}' is too big to be converted", i)
+            }
+            EvaluateError::IndexOutOfBounds { index, len } => {
+                write!(
+                    f,
+                    "Index '{}' is out of bounds, the length is {}",
+                    index, len
+                )
+            }
+            EvaluateError::DivisionByZero => write!(f, "Cannot divide by zero"),
+            EvaluateError::UnsupportedOperation { operator, lhs, rhs } => {
+                write!(
+                    f,
+                    "Cannot {} type '{}' {} type '{}'",
+                    operator.get_verb(),
+                    lhs,
+                    operator.get_proposition(),
+                    rhs
+                )
+            }
+            EvaluateError::UnknownProperty(name) => {
+                write!(f, "Object does not have a '{}' property", name)
+            }
+            EvaluateError::UnknownField { record, field } => {
+                write!(f, "Type '{}' does not have a '{}' field", record, field)
+            }
+            EvaluateError::UnknownVariant { enum_name, name } => {
+                write!(f, "Enum '{}' does not have a '{}' variant", enum_name, name)
+            }
+            EvaluateError::MissingField { record, field } => {
+                write!(f, "Missing field '{}' when creating a '{}'", field, record)
+            }
+            EvaluateError::UnknownMethod { ty, name } => {
+                write!(f, "Type '{}' does not have a '{}' method", ty, name)
+            }
             EvaluateError::IdentifierIsNotValidFn(name) => {
                 write!(f, "Func '{}' cannot be called as it does not exist!", name)
             }
@@ -1,5 +1,5 @@
 use crate::{
-    evaluate::{error::EvaluateError, AspenTable},
+    evaluate::{error::EvaluateError, generator::Generator, iterator::AspenIterator, AspenTable},
     parser::{func::Argument, utils::Block},
 };
 
@@ -10,19 +10,59 @@ pub struct AspenFn<'a> {
     pub args: Vec<Argument<'a>>,
     pub body: Box<Block<'a>>,
     pub name: &'a str,
+    /// True when the body contains a `yield`, calling the function then returns an iterator.
+    pub is_generator: bool,
 }
 
+/// The name given to the value a function is called on, in `obj.method()`.
+pub const SELF: &str = "self";
+
 impl<'a> AspenFn<'a> {
     pub fn call(
         &self,
         base_ctx: &AspenTable<'a>,
         args: Vec<AspenValue<'a>>,
+    ) -> EvaluateResult<AspenValue<'a>> {
+        self.call_on(base_ctx, None, args)
+    }
+
+    /// Calls the function as a method of `receiver`, which is bound to `self` in its body.
+    pub fn call_method(
+        &self,
+        base_ctx: &AspenTable<'a>,
+        mut receiver: AspenValue<'a>,
+        args: Vec<AspenValue<'a>>,
+    ) -> EvaluateResult<AspenValue<'a>> {
+        self.call_on(base_ctx, Some(&mut receiver), args)
+    }
+
+    /// Calls the function as a method of `receiver`, then stores back in `receiver` the value of `self`
+    /// once the function returned, e.g the state updated by the `next` method of an iterator.
+    pub fn call_method_mut(
+        &self,
+        base_ctx: &AspenTable<'a>,
+        receiver: &mut AspenValue<'a>,
+        args: Vec<AspenValue<'a>>,
+    ) -> EvaluateResult<AspenValue<'a>> {
+        self.call_on(base_ctx, Some(receiver), args)
+    }
+
+    fn call_on(
+        &self,
+        base_ctx: &AspenTable<'a>,
+        receiver: Option<&mut AspenValue<'a>>,
+        args: Vec<AspenValue<'a>>,
     ) -> EvaluateResult<AspenValue<'a>> {
         // there can only be one spread argument, it's ensured by the parser
-        let minimum_num = self.args.iter().filter(|a| a.is_spread == false).count();
+        let maximum_num = self.args.iter().filter(|a| !a.is_spread).count();
+        let minimum_num = self
+            .args
+            .iter()
+            .filter(|a| !a.is_spread && a.base_value.is_none())
+            .count();
         let found_num = args.len();
 
-        let has_spread_arg = minimum_num != self.args.len();
+        let has_spread_arg = maximum_num != self.args.len();
 
         if found_num < minimum_num {
             return Err(EvaluateError::NotEnoughArgs {
@@ -31,43 +71,170 @@ impl<'a> AspenFn<'a> {
             });
         }
 
-        if !has_spread_arg && found_num > minimum_num {
+        if !has_spread_arg && found_num > maximum_num {
             return Err(EvaluateError::TooMuchArgs {
-                expected_num: minimum_num,
+                expected_num: maximum_num,
                 found: found_num,
             });
         }
 
-        let mut ctx = self.init_ctx(base_ctx, args, has_spread_arg)?;
-        let result = ctx.evaluate_block(self.body.statements())?;
+        let mut ctx = self.init_ctx(base_ctx, receiver.as_deref().cloned(), args)?;
 
-        Ok(result)
+        if self.is_generator {
+            return Ok(AspenValue::Iterator(AspenIterator::new(Generator::new(
+                ctx, &self.body,
+            ))));
+        }
+
+        let result = match ctx.evaluate_block(self.body.statements()) {
+            // '?' returns the error from the function to its caller
+            Err(EvaluateError::ErrorPropagated) => ctx
+                .catch_error(EvaluateError::ErrorPropagated)
+                .map(AspenValue::from),
+            result => result,
+        };
+
+        if let (Some(receiver), Ok(value)) = (receiver, ctx.get_ref_value(SELF)) {
+            *receiver = value.to_owned();
+        }
+
+        result
     }
 
+    /// Declares the receiver and the arguments in a new context, a missing argument takes its default value.
     fn init_ctx(
         &self,
         base_ctx: &AspenTable<'a>,
+        receiver: Option<AspenValue<'a>>,
         args: Vec<AspenValue<'a>>,
-        has_spread_arg: bool,
     ) -> EvaluateResult<AspenTable<'a>> {
         let mut fn_ctx = base_ctx.create_sub_ctx();
+        let mut args = args.into_iter();
+
+        if let Some(receiver) = receiver {
+            fn_ctx.insert_value(SELF, receiver)?;
+        }
 
-        if has_spread_arg {
-            for (i, arg) in self.args.iter().enumerate() {
-                if !arg.is_spread {
-                    fn_ctx.insert_value(arg.identifier, args[i].clone())?;
-                } else {
-                    let spread_args = args.iter().skip(i).cloned().collect::<Vec<_>>();
-                    fn_ctx.insert_value(arg.identifier, AspenValue::Array(spread_args))?;
-                    break;
+        for arg in &self.args {
+            let value = if arg.is_spread {
+                AspenValue::Array(args.by_ref().collect())
+            } else {
+                match (args.next(), &arg.base_value) {
+                    (Some(value), _) => value,
+                    (None, Some(default)) => fn_ctx.evaluate_expr(*default.clone())?,
+                    (None, None) => unreachable!("the number of arguments is checked before"),
                 }
-            }
-        } else {
-            for (arg, value) in self.args.iter().zip(args.into_iter()) {
-                fn_ctx.insert_value(arg.identifier, value)?;
-            }
+            };
+
+            fn_ctx.bind_variables(arg.variables.clone(), value)?;
         }
 
         Ok(fn_ctx)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{error_of, value_of};
+
+    #[test]
+    fn question_mark_returns_the_error_from_the_function() {
+        let code = r#"
+@read path {
+    Err("missing {path}", {kind! "NotFound"})?
+    >> "content"
+}
+@load {
+    let config read("a")
+    let again read("b")?
+    >> "unreachable"
+}
+let direct read("x")
+let through load()
+let values []
+@gen {
+    yield 1
+    Err("stop")?
+    yield 2
+}
+for x in gen() {
+    values.push(x)
+}
+"#;
+
+        assert_eq!(value_of(code, "direct"), "Err(NotFound: missing x)");
+        assert_eq!(value_of(code, "through"), "Err(NotFound: missing b)");
+        assert_eq!(value_of(code, "values"), "[1, Err(Error: stop)]");
+    }
+
+    #[test]
+    fn question_mark_at_the_top_level_ends_the_script() {
+        let code = "let ok 1?\nErr(\"fatal\")?\nlet after 1\n";
+
+        assert_eq!(error_of(code), "Error: fatal");
+    }
+
+    #[test]
+    fn methods_are_called_with_self_through_the_proto_chain() {
+        let code = r#"
+@describe {
+    >> "{self.name} says {self.sound}"
+}
+@rename name {
+    self.name = name
+    >> self
+}
+let animal {sound! "...", describe! describe, rename! rename}
+let dog {name! "Rex", sound! "woof", proto! animal}
+let puppy {name! "Bit", proto! dog}
+let cat {name! "Tom", proto! animal}
+let dog_says dog.describe()
+let puppy_says puppy.describe()
+let cat_says cat.describe()
+let inherited cat.sound
+let renamed dog.rename("Max").name
+"#;
+
+        assert_eq!(value_of(code, "dog_says"), "Rex says woof");
+        assert_eq!(value_of(code, "puppy_says"), "Bit says woof");
+        assert_eq!(value_of(code, "cat_says"), "Tom says ...");
+        assert_eq!(value_of(code, "inherited"), "...");
+        assert_eq!(value_of(code, "renamed"), "Max");
+    }
+
+    #[test]
+    fn self_is_only_bound_in_methods() {
+        let code = "@plain {\n    >> self\n}\nplain()\n";
+
+        assert!(error_of(code).contains("Unknown variable or function: 'self'"));
+        assert!(error_of("let o {proto! {}}\no.fly()\n").contains("'fly'"));
+    }
+
+    #[test]
+    fn methods_change_the_value_they_are_called_on() {
+        let code = r#"
+@inc {
+    self.n = self.n + 1
+}
+let counter {n! 0, inc! inc}
+counter.inc()
+counter.inc()
+let proto {inc! inc}
+let child {n! 5, proto! proto}
+child.inc()
+let outer {inner! {n! 1, inc! inc}}
+outer.inner.inc()
+let count counter.n
+let child_count child.n
+let inner_count outer.inner.n
+"#;
+
+        assert_eq!(value_of(code, "count"), "2");
+        assert_eq!(value_of(code, "child_count"), "6");
+        assert_eq!(value_of(code, "inner_count"), "2");
+        assert!(
+            error_of("@inc {\n    self.n = 1\n}\nconst C {n! 0, inc! inc}\nC.inc()\n")
+                .contains("'C'")
+        );
+    }
+}
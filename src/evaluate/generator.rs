@@ -0,0 +1,306 @@
+use super::{
+    error::EvaluateError, iterator::AspenIterator, value::AspenValue, AspenTable, EvaluateResult,
+    Flow,
+};
+use crate::parser::{
+    for_loop::For,
+    loop_control::{Break, Continue},
+    match_expr::Match,
+    return_stmt::Return,
+    try_catch::Try,
+    utils::Block,
+    var::Variables,
+    while_loop::While,
+    yield_stmt::Yield,
+    Expr, Statement,
+};
+use std::vec;
+
+/// The state of a function containing `yield`, evaluated up to the next `yield` each time a value is asked.
+pub struct Generator<'a> {
+    ctx: AspenTable<'a>,
+    /// Blocks and loops being evaluated, innermost last.
+    frames: Vec<Frame<'a>>,
+}
+
+enum Frame<'a> {
+    Block {
+        statements: vec::IntoIter<Box<Statement<'a>>>,
+        /// False for the body of the function, whose variables live as long as the generator.
+        scoped: bool,
+    },
+    For {
+        label: Option<&'a str>,
+        args: Variables<'a>,
+        iterator: AspenIterator<'a>,
+        body: Box<Block<'a>>,
+    },
+    While {
+        label: Option<&'a str>,
+        condition: Box<Expr<'a>>,
+        body: Box<Block<'a>>,
+    },
+    /// A `try` whose body is evaluated in the frames above it, an error raised there is handled by `handler`.
+    Try {
+        binding: Option<&'a str>,
+        handler: Block<'a>,
+    },
+}
+
+impl<'a> Generator<'a> {
+    pub fn new(ctx: AspenTable<'a>, body: &Block<'a>) -> Self {
+        Self {
+            ctx,
+            frames: vec![Frame::Block {
+                statements: body.statements().into_iter(),
+                scoped: false,
+            }],
+        }
+    }
+
+    /// Evaluates the function until its next `yield`, returns `None` once it has ended.
+    fn resume(&mut self) -> EvaluateResult<Option<AspenValue<'a>>> {
+        loop {
+            match self.step() {
+                Err(error) => self.handle_error(error)?,
+                result => return result,
+            }
+        }
+    }
+
+    /// Evaluates the frames until a `yield`, the end of the function or an error.
+    fn step(&mut self) -> EvaluateResult<Option<AspenValue<'a>>> {
+        loop {
+            let frame = match self.frames.last_mut() {
+                Some(frame) => frame,
+                None => return Ok(None),
+            };
+
+            let next_frame = match frame {
+                Frame::Block { statements, scoped } => match statements.next() {
+                    Some(stmt) => match *stmt {
+                        Statement::Yield(Yield(value)) => {
+                            return self.ctx.evaluate_expr(*value).map(Some)
+                        }
+                        Statement::Return(Return(value)) => {
+                            // the returned value ends the iteration, it is not one of its values
+                            self.ctx.evaluate_expr(*value)?;
+                            self.frames.clear();
+                            return Ok(None);
+                        }
+                        Statement::For(For {
+                            label,
+                            args,
+                            indexed,
+                            body,
+                        }) => {
+                            let iterable = self.ctx.evaluate_expr(*indexed)?;
+                            Frame::For {
+                                label,
+                                args,
+                                iterator: self.ctx.iterate(iterable)?,
+                                body,
+                            }
+                        }
+                        Statement::While(While {
+                            label,
+                            condition,
+                            body,
+                        }) => Frame::While {
+                            label,
+                            condition,
+                            body,
+                        },
+                        Statement::Break(Break { label, value }) => {
+                            // the value of a loop is only used when it is an expression
+                            if let Some(value) = value {
+                                self.ctx.evaluate_expr(*value)?;
+                            }
+                            self.leave_loops(label, true);
+                            continue;
+                        }
+                        Statement::Continue(Continue { label }) => {
+                            self.leave_loops(label, false);
+                            continue;
+                        }
+                        Statement::If(if_stmt) => match self.ctx.select_branch(if_stmt)? {
+                            Some(block) => self.enter_block(&block),
+                            None => continue,
+                        },
+                        Statement::Expr { value, is_returned } => match *value {
+                            Expr::Try(try_expr) => {
+                                let Try {
+                                    body,
+                                    binding,
+                                    handler,
+                                } = *try_expr;
+                                self.frames.push(Frame::Try { binding, handler });
+                                self.enter_block(&body)
+                            }
+                            Expr::Match(Match { value, arms }) => {
+                                let value = self.ctx.evaluate_expr(*value)?;
+                                let body = self.ctx.enter_arm(&value, arms)?;
+
+                                // the scope of the arm holds the names bound by its pattern
+                                Frame::Block {
+                                    statements: body.statements().into_iter(),
+                                    scoped: true,
+                                }
+                            }
+                            value => {
+                                let stmt = Statement::Expr {
+                                    value: Box::new(value),
+                                    is_returned,
+                                };
+
+                                match self.ctx.execute_statement(stmt)? {
+                                    Flow::Return(_) => {
+                                        self.frames.clear();
+                                        return Ok(None);
+                                    }
+                                    _ => continue,
+                                }
+                            }
+                        },
+                        stmt => match self.ctx.execute_statement(stmt)? {
+                            Flow::Return(_) => {
+                                self.frames.clear();
+                                return Ok(None);
+                            }
+                            _ => continue,
+                        },
+                    },
+                    None => {
+                        if *scoped {
+                            self.ctx.pop_scope();
+                        }
+                        self.frames.pop();
+                        continue;
+                    }
+                },
+                Frame::For {
+                    args,
+                    iterator,
+                    body,
+                    ..
+                } => match iterator.next_value()? {
+                    Some(value) => {
+                        let (args, body) = (args.to_owned(), body.to_owned());
+                        let block = self.enter_block(&body);
+                        self.ctx.bind_variables(args, value)?;
+                        block
+                    }
+                    None => {
+                        self.frames.pop();
+                        continue;
+                    }
+                },
+                // the body of the try ended without any error
+                Frame::Try { .. } => {
+                    self.frames.pop();
+                    continue;
+                }
+                Frame::While {
+                    condition, body, ..
+                } => {
+                    let (condition, body) = (*condition.to_owned(), body.to_owned());
+
+                    if self.ctx.evaluate_condition(condition)? {
+                        self.enter_block(&body)
+                    } else {
+                        self.frames.pop();
+                        continue;
+                    }
+                }
+            };
+
+            self.frames.push(next_frame);
+        }
+    }
+
+    /// Leaves the frames up to the innermost `try` and goes on with its handler, the error is returned if there is none.
+    fn handle_error(&mut self, error: EvaluateError) -> EvaluateResult<()> {
+        let index = match self
+            .frames
+            .iter()
+            .rposition(|frame| matches!(frame, Frame::Try { .. }))
+        {
+            Some(index) => index,
+            None => return Err(error),
+        };
+
+        for frame in self.frames.drain(index + 1..).rev() {
+            if let Frame::Block { scoped: true, .. } = frame {
+                self.ctx.pop_scope();
+            }
+        }
+
+        let (binding, handler) = match self.frames.pop() {
+            Some(Frame::Try { binding, handler }) => (binding, handler),
+            _ => unreachable!("the frames are left up to the try"),
+        };
+        let err = self.ctx.catch_error(error)?;
+
+        // the caught error is only visible in the handler
+        let frame = self.enter_block(&handler);
+        if let Some(name) = binding {
+            self.ctx.insert_value(name, err.into())?;
+        }
+        self.frames.push(frame);
+
+        Ok(())
+    }
+
+    /// Leaves the blocks up to the innermost loop, or up to the loop labelled `label`.
+    ///
+    /// The loop itself is left when `is_break`, otherwise it goes on with its next iteration.
+    fn leave_loops(&mut self, label: Option<&'a str>, is_break: bool) {
+        while let Some(frame) = self.frames.pop() {
+            match &frame {
+                Frame::Block { scoped: true, .. } => self.ctx.pop_scope(),
+                Frame::For {
+                    label: loop_label, ..
+                }
+                | Frame::While {
+                    label: loop_label, ..
+                } if label.is_none() || label == *loop_label => {
+                    if !is_break {
+                        self.frames.push(frame);
+                    }
+                    return;
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Opens the scope of a block of a loop or of a condition.
+    fn enter_block(&mut self, block: &Block<'a>) -> Frame<'a> {
+        self.ctx.push_scope();
+
+        Frame::Block {
+            statements: block.statements().into_iter(),
+            scoped: true,
+        }
+    }
+}
+
+impl<'a> Iterator for Generator<'a> {
+    type Item = EvaluateResult<AspenValue<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.resume() {
+            Ok(value) => value.map(Ok),
+            Err(e) => {
+                // an error ends the generator, an error propagated with '?' is its last value
+                self.frames.clear();
+                match e {
+                    EvaluateError::ErrorPropagated => {
+                        Some(self.ctx.catch_error(e).map(AspenValue::from))
+                    }
+                    e => Some(Err(e)),
+                }
+            }
+        }
+    }
+}
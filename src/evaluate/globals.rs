@@ -1,6 +1,12 @@
 use std::io::Write;
 
-use super::{error::EvaluateError, value::AspenValue, EvaluateResult, ValueWrapper};
+use super::{
+    enums::{AspenVariant, EnumType, RESULT},
+    error::EvaluateError,
+    types::AspenType,
+    value::AspenValue,
+    AspenTable, EvaluateResult, ValueWrapper,
+};
 use hashbrown::HashMap;
 
 // in here are all the global functions defined
@@ -29,6 +35,29 @@ pub fn set_up_globals<'a>(hashmap: &mut HashMap<&'a str, ValueWrapper<'a>>) {
         }),
     );
 
+    hashmap.insert(
+        "Ok",
+        ValueWrapper::CurrentContext(AspenValue::RustBindFn {
+            name: "Ok",
+            code: ok,
+        }),
+    );
+    hashmap.insert(
+        RESULT,
+        ValueWrapper::CurrentContext(AspenValue::EnumType(EnumType::result())),
+    );
+
+    hashmap.insert(
+        "type_of",
+        ValueWrapper::CurrentContext(AspenValue::RustBindFn {
+            name: "type_of",
+            code: type_of,
+        }),
+    );
+
+    // returned by the next() method of an exhausted iterator
+    hashmap.insert("Done", ValueWrapper::CurrentContext(AspenValue::Done));
+
     hashmap.insert(
         "Array",
         ValueWrapper::CurrentContext(AspenValue::RustBindFn {
@@ -38,9 +67,12 @@ pub fn set_up_globals<'a>(hashmap: &mut HashMap<&'a str, ValueWrapper<'a>>) {
     );
 }
 
-pub fn print<'a>(args: Vec<AspenValue<'a>>) -> EvaluateResult<AspenValue<'a>> {
+pub fn print<'a>(
+    ctx: &AspenTable<'a>,
+    args: Vec<AspenValue<'a>>,
+) -> EvaluateResult<AspenValue<'a>> {
     for (i, arg) in args.iter().enumerate() {
-        print!("{arg}");
+        print!("{}", ctx.stringify(arg)?);
 
         if i != args.len() - 1 {
             print!(", ")
@@ -52,43 +84,70 @@ pub fn print<'a>(args: Vec<AspenValue<'a>>) -> EvaluateResult<AspenValue<'a>> {
     Ok(AspenValue::Nil)
 }
 
-pub fn input<'a>(args: Vec<AspenValue<'a>>) -> EvaluateResult<AspenValue<'a>> {
-    let mut user_input = String::new();
+crate::native_fn! {
+    pub fn input<'a>(prompt: Option<String>) -> EvaluateResult<String> {
+        let mut user_input = String::new();
 
-    if let Some(prompt) = args.get(0) {
-        match prompt {
-            AspenValue::Str(prompt_str) => print!("{}", prompt_str),
-            _ => return Err(EvaluateError::Custom("Invalid prompt".to_string())),
+        if let Some(prompt) = prompt {
+            print!("{}", prompt);
+            let _ = std::io::stdout().flush();
         }
-        let _ = std::io::stdout().flush();
-    }
 
-    std::io::stdin()
-        .read_line(&mut user_input)
-        .map_err(|err| EvaluateError::Custom(format!("Error reading input: {}", err)))?;
+        std::io::stdin()
+            .read_line(&mut user_input)
+            .map_err(|err| EvaluateError::Custom(format!("Error reading input: {}", err)))?;
 
-    user_input.pop(); // Remove newline character
+        user_input.pop(); // Remove newline character
 
-    Ok(AspenValue::Str(user_input))
+        Ok(user_input)
+    }
 }
 
-// Function named 'Err'
-pub fn error<'a>(args: Vec<AspenValue<'a>>) -> EvaluateResult<AspenValue<'a>> {
-    let mut result = String::new();
-
-    for (i, arg) in args.iter().enumerate() {
-        result.push_str("{arg}");
+// Function named 'Err', the same as 'Result.Err'
+pub fn error<'a>(
+    _ctx: &AspenTable<'a>,
+    args: Vec<AspenValue<'a>>,
+) -> EvaluateResult<AspenValue<'a>> {
+    EnumType::result().construct("Err", args)
+}
 
-        if i != args.len() - 1 {
-            result.push_str(", ")
-        }
+crate::native_fn! {
+    /// Function named 'Ok', `Ok()` holds nil.
+    pub fn ok<'a>(value: Option<AspenValue<'a>>) -> EvaluateResult<AspenValue<'a>> {
+        let value = value.unwrap_or(AspenValue::Nil);
+        Ok(AspenValue::Variant(AspenVariant::ok(value)))
     }
+}
 
-    result.push_str("\n");
-
-    Ok(AspenValue::Error(result))
+crate::native_fn! {
+    /// Returns the name of the type of a value, e.g "Int" or the name of a record type.
+    pub fn type_of<'a>(value: AspenValue<'a>) -> EvaluateResult<String> {
+        Ok(AspenType::from(value).to_string())
+    }
 }
 
-pub fn array<'a>(args: Vec<AspenValue<'a>>) -> EvaluateResult<AspenValue<'a>> {
+pub fn array<'a>(
+    _ctx: &AspenTable<'a>,
+    args: Vec<AspenValue<'a>>,
+) -> EvaluateResult<AspenValue<'a>> {
     Ok(AspenValue::Array(args))
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{error_of, value_of};
+
+    #[test]
+    fn type_of_takes_a_single_value() {
+        assert_eq!(value_of("let t type_of(1.5)\n", "t"), "Float");
+        assert!(error_of("type_of()\n").contains("Expected at least 1, found 0"));
+        assert!(error_of("type_of(1, 2)\n").contains("Expected at most 1, found 2"));
+    }
+
+    #[test]
+    fn ok_holds_at_most_one_value() {
+        assert_eq!(value_of("let o Ok()\n", "o"), "Ok(nil)");
+        assert_eq!(value_of("let o Ok(2)?\n", "o"), "2");
+        assert!(error_of("Ok(1, 2)\n").contains("Expected at most 1, found 2"));
+    }
+}
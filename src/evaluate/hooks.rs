@@ -0,0 +1,205 @@
+use super::{
+    binary::are_equal, error::EvaluateError, func::AspenFn, types::AspenType, utils::find_property,
+    value::AspenValue, AspenTable, EvaluateResult,
+};
+use crate::parser::operator::BinaryOperator;
+
+// an object or a record overloads an operator with a function property named after it,
+// the function is called with the left value bound to `self`
+
+/// Gives the text of the value for `print` and interpolated strings.
+pub const STR: &str = "__str";
+/// Called by `value[index]`.
+pub const INDEX: &str = "__index";
+/// Called by `value .. other`.
+pub const CONCAT: &str = "__concat";
+/// Called by `==` and `!=`.
+const EQ: &str = "__eq";
+/// Called by `<`, `>`, `<=` and `>=`.
+const LT: &str = "__lt";
+
+/// Returns the hook named `name` of an object, along its prototype chain, or of a record.
+pub fn find_hook<'v, 'a>(value: &'v AspenValue<'a>, name: &str) -> Option<&'v AspenFn<'a>> {
+    let hook = match value {
+        AspenValue::Object(obj) => find_property(obj, name),
+        AspenValue::Record(record) => record.fields.get(name),
+        _ => None,
+    };
+
+    match hook {
+        Some(AspenValue::Func(func)) => Some(func),
+        _ => None,
+    }
+}
+
+impl<'a> AspenTable<'a> {
+    /// Evaluates `lhs operator rhs` with the hook of `lhs`, returns `None` if it does not overload the operator.
+    ///
+    /// `__eq` also gives `!=`, and `__lt` gives `>`, `<=` and `>=` by negating it and by comparing with `__eq`,
+    /// or with the equality of the values when there is no `__eq`. `lhs` is always the one bound to `self`.
+    pub fn call_binary_hook(
+        &self,
+        lhs: &AspenValue<'a>,
+        operator: &BinaryOperator,
+        rhs: &AspenValue<'a>,
+    ) -> EvaluateResult<Option<AspenValue<'a>>> {
+        let name = match operator {
+            BinaryOperator::Plus => "__add",
+            BinaryOperator::Sub => "__sub",
+            BinaryOperator::Times => "__mul",
+            BinaryOperator::Divide => "__div",
+            BinaryOperator::Modulo => "__mod",
+            BinaryOperator::Exponent => "__pow",
+            BinaryOperator::IntDivide => "__floordiv",
+            BinaryOperator::BitAnd => "__and",
+            BinaryOperator::BitOr => "__or",
+            BinaryOperator::BitXor => "__xor",
+            BinaryOperator::ShiftLeft => "__shl",
+            BinaryOperator::ShiftRight => "__shr",
+            BinaryOperator::Equal | BinaryOperator::NotEqual => EQ,
+            BinaryOperator::LessThan
+            | BinaryOperator::GreaterThan
+            | BinaryOperator::LessThanOrEqual
+            | BinaryOperator::GreaterThanOrEqual => LT,
+            BinaryOperator::And | BinaryOperator::Or | BinaryOperator::Coalesce => return Ok(None),
+        };
+
+        let hook = match find_hook(lhs, name) {
+            Some(hook) => hook,
+            None => return Ok(None),
+        };
+
+        if name != EQ && name != LT {
+            return hook
+                .call_method(self, lhs.to_owned(), vec![rhs.to_owned()])
+                .map(Some);
+        }
+
+        let is_true = self.compare_with_hook(hook, lhs, rhs)?;
+        let result = match operator {
+            BinaryOperator::NotEqual | BinaryOperator::GreaterThanOrEqual => !is_true,
+            BinaryOperator::LessThanOrEqual => is_true || self.hooked_equal(lhs, rhs)?,
+            BinaryOperator::GreaterThan => !is_true && !self.hooked_equal(lhs, rhs)?,
+            _ => is_true,
+        };
+
+        Ok(Some(AspenValue::Bool(result)))
+    }
+
+    /// Calls the comparison hook `hook` of `lhs` with `rhs`, it must return a boolean.
+    fn compare_with_hook(
+        &self,
+        hook: &AspenFn<'a>,
+        lhs: &AspenValue<'a>,
+        rhs: &AspenValue<'a>,
+    ) -> EvaluateResult<bool> {
+        match hook.call_method(self, lhs.to_owned(), vec![rhs.to_owned()])? {
+            AspenValue::Bool(b) => Ok(b),
+            x => Err(EvaluateError::InvalidType {
+                expected: AspenType::Bool,
+                found: x.into(),
+            }),
+        }
+    }
+
+    /// Tells if `lhs` equals `rhs` with the `__eq` hook of `lhs`, or by comparing the values if it has none.
+    fn hooked_equal(&self, lhs: &AspenValue<'a>, rhs: &AspenValue<'a>) -> EvaluateResult<bool> {
+        match find_hook(lhs, EQ) {
+            Some(hook) => self.compare_with_hook(hook, lhs, rhs),
+            None => Ok(are_equal(lhs, rhs)),
+        }
+    }
+
+    /// Calls the hook named `name` of `value` with `args`, returns `None` if it has no such hook.
+    pub fn call_hook(
+        &self,
+        value: &AspenValue<'a>,
+        name: &str,
+        args: Vec<AspenValue<'a>>,
+    ) -> EvaluateResult<Option<AspenValue<'a>>> {
+        match find_hook(value, name) {
+            Some(hook) => hook.call_method(self, value.to_owned(), args).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the text of a value, given by its `__str` hook if it has one.
+    pub fn stringify(&self, value: &AspenValue<'a>) -> EvaluateResult<String> {
+        match self.call_hook(value, STR, vec![])? {
+            Some(AspenValue::Str(text)) => Ok(text),
+            Some(x) => Err(EvaluateError::InvalidType {
+                expected: AspenType::String,
+                found: x.into(),
+            }),
+            None => Ok(value.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{error_of, value_of};
+
+    #[test]
+    fn comparisons_call_the_hooks_of_the_left_value() {
+        let code = r#"
+@lt_num rhs {
+    >> self.v < rhs
+}
+@eq_num rhs {
+    >> self.v == rhs
+}
+let x {v! 3, __lt! lt_num, __eq! eq_num}
+let lt x < 4
+let gt x > 2
+let not_gt x > 3
+let ge x >= 3
+let le x <= 3
+let not_le x <= 2
+let eq x == 3
+let ne x != 3
+"#;
+        let value = |name| value_of(code, name);
+
+        assert_eq!(value("lt"), "true");
+        assert_eq!(value("gt"), "true");
+        assert_eq!(value("not_gt"), "false");
+        assert_eq!(value("ge"), "true");
+        assert_eq!(value("le"), "true");
+        assert_eq!(value("not_le"), "false");
+        assert_eq!(value("eq"), "true");
+        assert_eq!(value("ne"), "false");
+    }
+
+    #[test]
+    fn comparison_hooks_must_return_booleans() {
+        let code = r#"
+@eq rhs {
+    >> 5
+}
+let a {__eq! eq}
+let same a == a
+"#;
+
+        assert!(error_of(code).contains("expected type 'Bool'"));
+    }
+
+    #[test]
+    fn arithmetic_and_string_hooks() {
+        let code = r#"
+@vec_add rhs {
+    let x self.x + rhs.x
+    let y self.y + rhs.y
+    >> Vec({x! x, y! y})
+}
+@vec_str {
+    >> "<{self.x}, {self.y}>"
+}
+$type Vec { x!, y!, __add! vec_add, __str! vec_str }
+let sum Vec({x! 1, y! 2}) + Vec({x! 3, y! 4})
+let text "{sum}"
+"#;
+
+        assert_eq!(value_of(code, "text"), "<4, 6>");
+    }
+}
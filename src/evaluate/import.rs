@@ -1,12 +1,20 @@
 use super::value::AspenValue;
 
+pub mod array;
 pub mod io;
+pub mod json;
 pub mod math;
+pub mod object;
+pub mod string;
 
 pub fn import_module<'a>(name: &'a str) -> Option<AspenValue<'a>> {
     match name {
+        "array" => Some(array::module()),
         "io" => Some(io::module()),
+        "json" => Some(json::module()),
         "math" => Some(math::module()),
+        "object" => Some(object::module()),
+        "string" => Some(string::module()),
         _ => None,
     }
 }
@@ -0,0 +1,378 @@
+use super::super::{
+    binary::{are_equal, compare},
+    error::EvaluateError,
+    native::check_arity,
+    types::AspenType,
+    value::AspenValue,
+    AspenTable, EvaluateResult,
+};
+use rug::Integer;
+use std::cmp::Ordering;
+
+pub fn module<'a>() -> AspenValue<'a> {
+    crate::native_module! {
+        "len" => len,
+        "map" => map,
+        "filter" => filter,
+        "reduce" => reduce,
+        "find" => find,
+        "any" => any,
+        "all" => all,
+        "sort" => sort,
+        "reverse" => reverse,
+        "zip" => zip,
+        "flatten" => flatten,
+        "unique" => unique,
+        "push" => push,
+        "pop" => pop,
+        "index_of" => index_of,
+    }
+}
+
+/// Calls a predicate given to a native function, it must return a boolean.
+fn call_predicate<'a>(
+    ctx: &AspenTable<'a>,
+    f: &AspenValue<'a>,
+    value: AspenValue<'a>,
+) -> EvaluateResult<bool> {
+    match ctx.call_value(f.clone(), vec![value])? {
+        AspenValue::Bool(b) => Ok(b),
+        x => Err(EvaluateError::InvalidType {
+            expected: AspenType::Bool,
+            found: x.into(),
+        }),
+    }
+}
+
+crate::native_fn! {
+    pub fn len<'a>(values: Vec<AspenValue<'a>>) -> EvaluateResult<Integer> {
+        Ok(Integer::from(values.len()))
+    }
+}
+
+crate::native_fn! {
+    pub fn map<'a>(ctx: &AspenTable<'a>, values: Vec<AspenValue<'a>>, f: AspenValue<'a>) -> EvaluateResult<Vec<AspenValue<'a>>> {
+        values
+            .into_iter()
+            .map(|value| ctx.call_value(f.clone(), vec![value]))
+            .collect()
+    }
+}
+
+crate::native_fn! {
+    pub fn filter<'a>(ctx: &AspenTable<'a>, values: Vec<AspenValue<'a>>, f: AspenValue<'a>) -> EvaluateResult<Vec<AspenValue<'a>>> {
+        let mut result = Vec::new();
+
+        for value in values {
+            if call_predicate(ctx, &f, value.clone())? {
+                result.push(value);
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+crate::native_fn! {
+    /// Folds the array with `f(accumulator, value)`, starting from `initial` or from the first value.
+    pub fn reduce<'a>(ctx: &AspenTable<'a>, values: Vec<AspenValue<'a>>, f: AspenValue<'a>, initial: Option<AspenValue<'a>>) -> EvaluateResult<AspenValue<'a>> {
+        let mut values = values.into_iter();
+
+        let mut accumulator = match initial.or_else(|| values.next()) {
+            Some(value) => value,
+            None => {
+                return Err(EvaluateError::Custom(
+                    "Cannot reduce an empty array without an initial value".to_owned(),
+                ))
+            }
+        };
+
+        for value in values {
+            accumulator = ctx.call_value(f.clone(), vec![accumulator, value])?;
+        }
+
+        Ok(accumulator)
+    }
+}
+
+crate::native_fn! {
+    /// Returns the first value matching the predicate, or nil.
+    pub fn find<'a>(ctx: &AspenTable<'a>, values: Vec<AspenValue<'a>>, f: AspenValue<'a>) -> EvaluateResult<Option<AspenValue<'a>>> {
+        for value in values {
+            if call_predicate(ctx, &f, value.clone())? {
+                return Ok(Some(value));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+crate::native_fn! {
+    pub fn any<'a>(ctx: &AspenTable<'a>, values: Vec<AspenValue<'a>>, f: AspenValue<'a>) -> EvaluateResult<bool> {
+        for value in values {
+            if call_predicate(ctx, &f, value)? {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+}
+
+crate::native_fn! {
+    pub fn all<'a>(ctx: &AspenTable<'a>, values: Vec<AspenValue<'a>>, f: AspenValue<'a>) -> EvaluateResult<bool> {
+        for value in values {
+            if !call_predicate(ctx, &f, value)? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+crate::native_fn! {
+    /// Sorts numbers or strings, or any values with a comparator returning a negative number, zero or a positive number.
+    ///
+    /// The sort is stable, the given array is not modified.
+    pub fn sort<'a>(ctx: &AspenTable<'a>, values: Vec<AspenValue<'a>>, comparator: Option<AspenValue<'a>>) -> EvaluateResult<Vec<AspenValue<'a>>> {
+        let mut values = values;
+        let mut error = None;
+
+        let cmp = |a: &AspenValue<'a>, b: &AspenValue<'a>| -> EvaluateResult<Ordering> {
+            match &comparator {
+                Some(f) => match ctx.call_value(f.clone(), vec![a.clone(), b.clone()])? {
+                    AspenValue::Int(i) => Ok(i.cmp0()),
+                    AspenValue::Float(f) => Ok(f.as_float().cmp0().unwrap_or(Ordering::Equal)),
+                    x => Err(EvaluateError::InvalidType {
+                        expected: AspenType::Number,
+                        found: x.into(),
+                    }),
+                },
+                None => compare(a, b).ok_or_else(|| {
+                    EvaluateError::Custom(format!(
+                        "Cannot compare type '{}' and type '{}' without a comparator",
+                        AspenType::from(a.to_owned()),
+                        AspenType::from(b.to_owned())
+                    ))
+                }),
+            }
+        };
+
+        values.sort_by(|a, b| {
+            if error.is_some() {
+                return Ordering::Equal;
+            }
+
+            cmp(a, b).unwrap_or_else(|e| {
+                error = Some(e);
+                Ordering::Equal
+            })
+        });
+
+        match error {
+            Some(e) => Err(e),
+            None => Ok(values),
+        }
+    }
+}
+
+crate::native_fn! {
+    pub fn reverse<'a>(values: Vec<AspenValue<'a>>) -> EvaluateResult<Vec<AspenValue<'a>>> {
+        let mut values = values;
+        values.reverse();
+        Ok(values)
+    }
+}
+
+crate::native_fn! {
+    /// Pairs the values of two arrays, stops at the end of the shortest one.
+    pub fn zip<'a>(values: Vec<AspenValue<'a>>, others: Vec<AspenValue<'a>>) -> EvaluateResult<Vec<Vec<AspenValue<'a>>>> {
+        Ok(values
+            .into_iter()
+            .zip(others)
+            .map(|(a, b)| vec![a, b])
+            .collect())
+    }
+}
+
+crate::native_fn! {
+    /// Flattens one level of nested arrays.
+    pub fn flatten<'a>(values: Vec<AspenValue<'a>>) -> EvaluateResult<Vec<AspenValue<'a>>> {
+        let mut result = Vec::with_capacity(values.len());
+
+        for value in values {
+            match value {
+                AspenValue::Array(nested) => result.extend(nested),
+                x => result.push(x),
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+crate::native_fn! {
+    /// Removes the duplicated values, keeping the first occurrence of each one.
+    pub fn unique<'a>(values: Vec<AspenValue<'a>>) -> EvaluateResult<Vec<AspenValue<'a>>> {
+        let mut result: Vec<AspenValue<'a>> = Vec::with_capacity(values.len());
+
+        for value in values {
+            if !result.iter().any(|v| are_equal(v, &value)) {
+                result.push(value);
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+/// Returns a copy of the array with the values added at its end, use `arr.push(value)` to modify an array.
+pub fn push<'a>(
+    _ctx: &AspenTable<'a>,
+    args: Vec<AspenValue<'a>>,
+) -> EvaluateResult<AspenValue<'a>> {
+    check_arity(1, usize::MAX, args.len())?;
+    let mut args = args.into_iter();
+
+    match args.next().unwrap() {
+        AspenValue::Array(mut values) => {
+            values.extend(args);
+            Ok(AspenValue::Array(values))
+        }
+        x => Err(EvaluateError::InvalidType {
+            expected: AspenType::Array,
+            found: x.into(),
+        }),
+    }
+}
+
+crate::native_fn! {
+    /// Returns the last value of the array or nil, use `arr.pop()` to modify an array.
+    pub fn pop<'a>(values: Vec<AspenValue<'a>>) -> EvaluateResult<Option<AspenValue<'a>>> {
+        let mut values = values;
+        Ok(values.pop())
+    }
+}
+
+crate::native_fn! {
+    /// Returns the index of the first value equal to `value`, or nil.
+    pub fn index_of<'a>(values: Vec<AspenValue<'a>>, value: AspenValue<'a>) -> EvaluateResult<Option<Integer>> {
+        Ok(values
+            .iter()
+            .position(|v| are_equal(v, &value))
+            .map(Integer::from))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{error_of, value_of};
+
+    #[test]
+    fn module_functions_are_called_through_the_module() {
+        let code = r#"
+let array $import("array")
+@double x {
+    >> x * 2
+}
+@is_even x {
+    >> x % 2 == 0
+}
+@add acc, x {
+    >> acc + x
+}
+@descending a, b {
+    >> b - a
+}
+let a [3, 1, 2, 2]
+let len array.len(a)
+let mapped array.map(a, double)
+let filtered array.filter(a, is_even)
+let reduced array.reduce(a, add)
+let reduced_from array.reduce(a, add, 10)
+let found array.find(a, is_even)
+let any array.any(a, is_even)
+let all array.all(a, is_even)
+let sorted array.sort(a)
+let sorted_by array.sort(a, descending)
+let reversed array.reverse(a)
+let zipped array.zip(a, ["x", "y"])
+let flattened array.flatten([[1, 2], 3, [4]])
+let unique array.unique(a)
+let pushed array.push(a, 4, 5)
+let popped array.pop(a)
+let index array.index_of(a, 2)
+"#;
+        let value = |name| value_of(code, name);
+
+        assert_eq!(value("len"), "4");
+        assert_eq!(value("mapped"), "[6, 2, 4, 4]");
+        assert_eq!(value("filtered"), "[2, 2]");
+        assert_eq!(value("reduced"), "8");
+        assert_eq!(value("reduced_from"), "18");
+        assert_eq!(value("found"), "2");
+        assert_eq!(value("any"), "true");
+        assert_eq!(value("all"), "false");
+        assert_eq!(value("sorted"), "[1, 2, 2, 3]");
+        assert_eq!(value("sorted_by"), "[3, 2, 2, 1]");
+        assert_eq!(value("reversed"), "[2, 2, 1, 3]");
+        assert_eq!(value("zipped"), "[[3, x], [1, y]]");
+        assert_eq!(value("flattened"), "[1, 2, 3, 4]");
+        assert_eq!(value("unique"), "[3, 1, 2]");
+        assert_eq!(value("pushed"), "[3, 1, 2, 2, 4, 5]");
+        assert_eq!(value("popped"), "2");
+        assert_eq!(value("index"), "2");
+        // the module functions return new arrays
+        assert_eq!(value("a"), "[3, 1, 2, 2]");
+    }
+
+    #[test]
+    fn methods_modify_the_array_they_are_called_on() {
+        let code = r#"
+let a [1, 2]
+a.push(3)
+let last a.pop()
+let len a.len()
+"#;
+
+        assert_eq!(value_of(code, "last"), "3");
+        assert_eq!(value_of(code, "len"), "2");
+    }
+
+    #[test]
+    fn sorts_are_stable_and_accept_strings() {
+        let code = r#"
+@by_len a, b {
+    >> a.len() - b.len()
+}
+let words ["ccc", "a", "bb", "b"]
+let sorted words.sort()
+let by_length words.sort(by_len)
+"#;
+
+        assert_eq!(value_of(code, "sorted"), "[a, b, bb, ccc]");
+        assert_eq!(value_of(code, "by_length"), "[a, b, bb, ccc]");
+        assert_eq!(value_of(code, "words"), "[ccc, a, bb, b]");
+    }
+
+    #[test]
+    fn invalid_callbacks_and_values() {
+        let callbacks = r#"
+@not_bool x {
+    >> x
+}
+@not_number a, b {
+    >> "a"
+}
+"#;
+        let code = |body| format!("{}{}", callbacks, body);
+
+        assert!(error_of(&code("let f [1].filter(not_bool)")).contains("expected type 'Bool'"));
+        assert!(error_of(&code("let s [2, 1].sort(not_number)")).contains("expected type 'Number'"));
+        assert!(error_of(&code("let s [1, \"a\"].sort()")).contains("without a comparator"));
+        assert!(error_of(&code("let r [].reduce(not_number)")).contains("empty array"));
+        assert_eq!(value_of("let r [].reduce(print, 0)", "r"), "0");
+    }
+}
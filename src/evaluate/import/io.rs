@@ -1,121 +1,57 @@
-use std::{fs, io::Write};
+use std::{
+    fs,
+    io::{self, BufRead, Write},
+};
 
-use super::super::{error::EvaluateError, value::AspenValue, EvaluateResult};
-use hashbrown::HashMap;
+use super::super::{
+    error::EvaluateError, iterator::AspenIterator, value::AspenValue, EvaluateResult,
+};
 
 pub fn module<'a>() -> AspenValue<'a> {
-    let mut hashmap = HashMap::new();
-
-    hashmap.insert(
-        "read",
-        AspenValue::RustBindFn {
-            name: "read",
-            code: read_file,
-        },
-    );
-
-    hashmap.insert(
-        "write",
-        AspenValue::RustBindFn {
-            name: "write",
-            code: write_file,
-        },
-    );
-    hashmap.insert(
-        "append",
-        AspenValue::RustBindFn {
-            name: "append",
-            code: append_file,
-        },
-    );
-    AspenValue::Object(hashmap)
+    crate::native_module! {
+        "read" => read_file,
+        "write" => write_file,
+        "append" => append_file,
+        "lines" => lines,
+    }
 }
 
-pub fn read_file<'a>(args: Vec<AspenValue<'a>>) -> EvaluateResult<AspenValue<'a>> {
-    if args.len() != 1 {
-        return Err(EvaluateError::Custom(
-            "'read' function expects 1 argument: a file name".to_string(),
-        ));
+crate::native_fn! {
+    pub fn read_file<'a>(file_name: String) -> EvaluateResult<io::Result<String>> {
+        Ok(fs::read_to_string(file_name))
     }
-
-    let file_name = match args[0].to_owned() {
-        AspenValue::Str(n) => n,
-        _ => {
-            return Err(EvaluateError::Custom(
-                "'read' argument is not of type 'String'".to_string(),
-            ))
-        }
-    };
-
-    let result = match fs::read_to_string(file_name) {
-        Ok(s) => s.into(),
-        Err(s) => AspenValue::Error(s.to_string()),
-    };
-
-    Ok(result)
 }
 
-pub fn write_file<'a>(args: Vec<AspenValue<'a>>) -> EvaluateResult<AspenValue<'a>> {
-    if args.len() != 2 {
-        return Err(EvaluateError::Custom(
-            "'write' function expects 2 arguments: a file name and a content".to_string(),
-        ));
+crate::native_fn! {
+    pub fn write_file<'a>(file_name: String, data: String) -> EvaluateResult<io::Result<()>> {
+        Ok(fs::write(file_name, data))
     }
+}
 
-    let file_name = match args[0].to_owned() {
-        AspenValue::Str(n) => n,
-        _ => {
-            return Err(EvaluateError::Custom(
-                "'write' first argument is not of type 'String'".to_string(),
-            ))
-        }
-    };
-
-    let data = match args[1].to_owned() {
-        AspenValue::Str(d) => d,
-        _ => {
-            return Err(EvaluateError::Custom(
-                "'write' second argument is not of type 'String'".to_string(),
-            ))
-        }
-    };
+crate::native_fn! {
+    pub fn append_file<'a>(file_name: String, data: String) -> EvaluateResult<io::Result<()>> {
+        let result = fs::OpenOptions::new()
+            .append(true)
+            .open(file_name)
+            .and_then(|mut file| file.write_all(data.as_bytes()));
 
-    match fs::write(&file_name, data) {
-        Ok(_) => Ok(AspenValue::Nil),
-        Err(err) => Ok(AspenValue::Error(err.to_string())),
+        Ok(result)
     }
 }
 
-pub fn append_file<'a>(args: Vec<AspenValue<'a>>) -> EvaluateResult<AspenValue<'a>> {
-    if args.len() != 2 {
-        return Err(EvaluateError::Custom(
-            "'append' function expects 2 arguments: a file name and a content".to_string(),
-        ));
-    }
-
-    let file_name = match args[0].to_owned() {
-        AspenValue::Str(n) => n,
-        _ => {
-            return Err(EvaluateError::Custom(
-                "'append' first argument is not of type 'String'".to_string(),
-            ))
-        }
-    };
+crate::native_fn! {
+    /// Returns an iterator over the lines of a file, read one at a time.
+    pub fn lines<'a>(file_name: String) -> EvaluateResult<io::Result<AspenIterator<'a>>> {
+        let file = match fs::File::open(file_name) {
+            Ok(file) => file,
+            Err(err) => return Ok(Err(err)),
+        };
 
-    let data = match args[1].to_owned() {
-        AspenValue::Str(d) => d,
-        _ => {
-            return Err(EvaluateError::Custom(
-                "'append' second argument is not of type 'String'".to_string(),
-            ))
-        }
-    };
+        let lines = io::BufReader::new(file).lines().map(|line| {
+            line.map(AspenValue::Str)
+                .map_err(|err| EvaluateError::Custom(format!("Error reading a line: {}", err)))
+        });
 
-    match fs::OpenOptions::new().append(true).open(&file_name) {
-        Ok(mut file) => match file.write_all(data.as_bytes()) {
-            Ok(_) => Ok(AspenValue::Nil),
-            Err(err) => Ok(AspenValue::Error(err.to_string())),
-        },
-        Err(err) => Ok(AspenValue::Error(err.to_string())),
+        Ok(Ok(AspenIterator::new(lines)))
     }
 }
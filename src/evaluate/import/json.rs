@@ -0,0 +1,402 @@
+use super::super::{
+    types::AspenType,
+    value::{AspenObject, AspenValue},
+    EvaluateResult,
+};
+use logos::{Lexer, Logos};
+use rug::{float::OrdFloat, Float, Integer};
+use std::fmt::Write;
+
+/// Precision used for decoded floats, enough to hold any JSON number that fits in an `f64`.
+const F64_PRECISION: u32 = 53;
+/// Number of arrays and objects that can be nested in a decoded document.
+const MAX_DEPTH: usize = 128;
+
+pub fn module<'a>() -> AspenValue<'a> {
+    crate::native_module! {
+        "parse" => parse,
+        "stringify" => stringify,
+    }
+}
+
+crate::native_fn! {
+    /// Decodes a JSON document, returns an error value if the document is invalid.
+    pub fn parse<'a>(text: String) -> EvaluateResult<Result<AspenValue<'a>, String>> {
+        Ok(parse_json(&text))
+    }
+}
+
+crate::native_fn! {
+    /// Encodes a value in JSON, on a single line or indented with the given number of spaces.
+    pub fn stringify<'a>(value: AspenValue<'a>, indent: Option<Integer>) -> EvaluateResult<Result<String, String>> {
+        let indent = match indent {
+            Some(i) => match i.to_usize() {
+                Some(i) => Some(i),
+                None => return Ok(Err(format!("Invalid indentation '{}'", i))),
+            },
+            None => None,
+        };
+
+        let mut output = String::new();
+        Ok(write_json(&mut output, &value, indent, 0).map(|_| output))
+    }
+}
+
+#[derive(Logos, Debug, PartialEq, Clone)]
+#[logos(skip r"[ \t\r\n]+")]
+enum JsonToken<'s> {
+    #[token("{")]
+    OpenBrace,
+    #[token("}")]
+    CloseBrace,
+    #[token("[")]
+    OpenBracket,
+    #[token("]")]
+    CloseBracket,
+    #[token(",")]
+    Comma,
+    #[token(":")]
+    Colon,
+
+    #[token("null")]
+    Null,
+    #[regex("true|false", |lex| lex.slice() == "true")]
+    Bool(bool),
+
+    #[regex(r"-?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][-+]?[0-9]+)?", |lex| lex.slice())]
+    Number(&'s str),
+
+    #[regex(r#""([^"\\\x00-\x1F]|\\(["\\/bfnrt]|u[a-fA-F0-9]{4}))*""#, |lex| let raw=lex.slice();&raw[1..raw.len()-1])]
+    String(&'s str),
+}
+
+type JsonLexer<'s> = Lexer<'s, JsonToken<'s>>;
+
+fn parse_json<'a>(text: &str) -> Result<AspenValue<'a>, String> {
+    let mut lexer = JsonToken::lexer(text);
+    let token = next_token(&mut lexer)?;
+    let value = parse_value(&mut lexer, token, 0)?;
+
+    match lexer.next() {
+        None => Ok(value),
+        Some(_) => Err(unexpected(&lexer)),
+    }
+}
+
+fn unexpected(lexer: &JsonLexer<'_>) -> String {
+    format!(
+        "Invalid JSON: unexpected '{}' at offset {}",
+        lexer.slice(),
+        lexer.span().start
+    )
+}
+
+fn next_token<'s>(lexer: &mut JsonLexer<'s>) -> Result<JsonToken<'s>, String> {
+    match lexer.next() {
+        Some(Ok(token)) => Ok(token),
+        Some(Err(_)) => Err(unexpected(lexer)),
+        None => Err("Invalid JSON: unexpected end of input".to_owned()),
+    }
+}
+
+/// Decodes the value starting with `token`, nested inside `depth` arrays and objects.
+fn parse_value<'a>(
+    lexer: &mut JsonLexer<'_>,
+    token: JsonToken<'_>,
+    depth: usize,
+) -> Result<AspenValue<'a>, String> {
+    if depth == MAX_DEPTH && matches!(token, JsonToken::OpenBracket | JsonToken::OpenBrace) {
+        return Err(format!(
+            "Invalid JSON: more than {} nested arrays and objects at offset {}",
+            MAX_DEPTH,
+            lexer.span().start
+        ));
+    }
+
+    let value = match token {
+        JsonToken::Null => AspenValue::Nil,
+        JsonToken::Bool(b) => AspenValue::Bool(b),
+        JsonToken::String(s) => AspenValue::Str(unescape(s)?),
+        JsonToken::Number(n) if n.contains(['.', 'e', 'E']) => {
+            let float = Float::parse(n).map_err(|e| e.to_string())?;
+            AspenValue::Float(OrdFloat::from(Float::with_val(F64_PRECISION, float)))
+        }
+        // integers are kept exact, whatever their size
+        JsonToken::Number(n) => {
+            AspenValue::Int(Integer::from(Integer::parse(n).map_err(|e| e.to_string())?))
+        }
+        JsonToken::OpenBracket => {
+            let mut values = Vec::new();
+            let mut token = next_token(lexer)?;
+
+            if token != JsonToken::CloseBracket {
+                loop {
+                    values.push(parse_value(lexer, token, depth + 1)?);
+
+                    match next_token(lexer)? {
+                        JsonToken::Comma => token = next_token(lexer)?,
+                        JsonToken::CloseBracket => break,
+                        _ => return Err(unexpected(lexer)),
+                    }
+                }
+            }
+
+            AspenValue::Array(values)
+        }
+        JsonToken::OpenBrace => {
+            let mut values = AspenObject::new();
+            let mut token = next_token(lexer)?;
+
+            if token != JsonToken::CloseBrace {
+                loop {
+                    let key = match token {
+                        JsonToken::String(s) => unescape(s)?,
+                        _ => return Err(unexpected(lexer)),
+                    };
+
+                    if next_token(lexer)? != JsonToken::Colon {
+                        return Err(unexpected(lexer));
+                    }
+
+                    let value_token = next_token(lexer)?;
+                    values.insert(key, parse_value(lexer, value_token, depth + 1)?);
+
+                    match next_token(lexer)? {
+                        JsonToken::Comma => token = next_token(lexer)?,
+                        JsonToken::CloseBrace => break,
+                        _ => return Err(unexpected(lexer)),
+                    }
+                }
+            }
+
+            AspenValue::Object(values)
+        }
+        _ => return Err(unexpected(lexer)),
+    };
+
+    Ok(value)
+}
+
+/// Decodes the escape sequences of a JSON string, the lexer ensures they are well-formed.
+fn unescape(raw: &str) -> Result<String, String> {
+    let mut result = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('b') => result.push('\u{8}'),
+            Some('f') => result.push('\u{c}'),
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('t') => result.push('\t'),
+            Some('u') => {
+                let mut code = read_hex(&mut chars);
+
+                // characters outside of the BMP are written as a surrogate pair
+                if (0xD800..0xDC00).contains(&code) && chars.as_str().starts_with("\\u") {
+                    chars.nth(1);
+                    let low = read_hex(&mut chars);
+                    if !(0xDC00..=0xDFFF).contains(&low) {
+                        return Err(format!("Invalid JSON: invalid low surrogate '{:X}'", low));
+                    }
+                    code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                }
+
+                match char::from_u32(code) {
+                    Some(c) => result.push(c),
+                    None => {
+                        return Err(format!("Invalid JSON: invalid unicode escape '{:X}'", code))
+                    }
+                }
+            }
+            Some(c) => result.push(c),
+            None => unreachable!("an escape is always followed by a char"),
+        }
+    }
+
+    Ok(result)
+}
+
+fn read_hex(chars: &mut std::str::Chars<'_>) -> u32 {
+    chars
+        .take(4)
+        .fold(0, |code, c| code * 16 + c.to_digit(16).unwrap_or(0))
+}
+
+fn write_escaped(output: &mut String, s: &str) {
+    output.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            '\u{8}' => output.push_str("\\b"),
+            '\u{c}' => output.push_str("\\f"),
+            c if c < ' ' => {
+                let _ = write!(output, "\\u{:04x}", c as u32);
+            }
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+}
+
+fn write_newline(output: &mut String, indent: Option<usize>, depth: usize) {
+    if let Some(indent) = indent {
+        output.push('\n');
+        output.push_str(&" ".repeat(indent * depth));
+    }
+}
+
+fn write_json(
+    output: &mut String,
+    value: &AspenValue<'_>,
+    indent: Option<usize>,
+    depth: usize,
+) -> Result<(), String> {
+    match value {
+        AspenValue::Nil => output.push_str("null"),
+        AspenValue::Bool(b) => output.push_str(if *b { "true" } else { "false" }),
+        AspenValue::Int(i) => output.push_str(&i.to_string()),
+        AspenValue::Float(f) => {
+            let f = Float::from(f.to_owned()).to_f64();
+            if !f.is_finite() {
+                return Err(format!("Cannot convert '{}' to JSON", f));
+            }
+            let _ = write!(output, "{:?}", f);
+        }
+        AspenValue::Str(s) => write_escaped(output, s),
+        AspenValue::Array(values) => {
+            output.push('[');
+            for (i, v) in values.iter().enumerate() {
+                if i > 0 {
+                    output.push(',');
+                }
+                write_newline(output, indent, depth + 1);
+                write_json(output, v, indent, depth + 1)?;
+            }
+            if !values.is_empty() {
+                write_newline(output, indent, depth);
+            }
+            output.push(']');
+        }
+        AspenValue::Object(values) => {
+            output.push('{');
+            for (i, (key, v)) in values.iter().enumerate() {
+                if i > 0 {
+                    output.push(',');
+                }
+                write_newline(output, indent, depth + 1);
+                write_escaped(output, key);
+                output.push(':');
+                if indent.is_some() {
+                    output.push(' ');
+                }
+                write_json(output, v, indent, depth + 1)?;
+            }
+            if !values.is_empty() {
+                write_newline(output, indent, depth);
+            }
+            output.push('}');
+        }
+        x => {
+            return Err(format!(
+                "Cannot convert a value of type '{}' to JSON",
+                AspenType::from(x.to_owned())
+            ))
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_json;
+    use crate::test_utils::{error_of, value_of};
+
+    #[test]
+    fn keeps_big_integers_exact() {
+        let code = r#"
+let json $import("json")
+let value json.parse("[123456789012345678901234567890, 2.5, null, true]")
+let big value[0] + 1
+"#;
+
+        assert_eq!(value_of(code, "big"), "123456789012345678901234567891");
+    }
+
+    #[test]
+    fn stringify_round_trips_through_parse() {
+        let code = r#"
+let json $import("json")
+let text r'{"name": "aspen", "tags": ["a", "b"], "n": -12}'
+let value json.parse(text)
+let compact json.stringify(value)
+let indented json.stringify(value, 2)
+"#;
+
+        assert_eq!(
+            value_of(code, "compact"),
+            r#"{"name":"aspen","tags":["a","b"],"n":-12}"#
+        );
+        assert_eq!(
+            value_of(code, "indented"),
+            "{\n  \"name\": \"aspen\",\n  \"tags\": [\n    \"a\",\n    \"b\"\n  ],\n  \"n\": -12\n}"
+        );
+    }
+
+    #[test]
+    fn invalid_documents_are_error_values() {
+        let code = r#"
+let json $import("json")
+let text r'[1, 2'
+let result json.parse(text) ?? "fallback"
+"#;
+
+        assert_eq!(value_of(code, "result"), "fallback");
+
+        let code = r#"
+let json $import("json")
+let result json.parse("tru")?
+"#;
+        assert!(
+            error_of(code).contains("Invalid JSON"),
+            "{}",
+            error_of(code)
+        );
+    }
+
+    #[test]
+    fn decodes_surrogate_pairs() {
+        assert_eq!(
+            parse_json(r#""\ud83d\ude00""#).unwrap().to_string(),
+            "\u{1F600}"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_low_surrogates() {
+        let err = parse_json(r#""\ud83d\u0041""#).unwrap_err();
+
+        assert!(err.contains("invalid low surrogate"), "{}", err);
+    }
+
+    #[test]
+    fn rejects_documents_nested_too_deeply() {
+        let nested = "[".repeat(100_000);
+        let err = parse_json(&nested).unwrap_err();
+
+        assert!(err.contains("nested"), "{}", err);
+
+        let allowed = format!("{}{}", "[".repeat(128), "]".repeat(128));
+        assert!(parse_json(&allowed).is_ok());
+    }
+}
@@ -1,110 +1,121 @@
-use super::super::{error::EvaluateError, utils::extract_range, value::AspenValue, EvaluateResult};
-use hashbrown::HashMap;
-use rug::{float::OrdFloat, Float, Integer};
+use super::super::{
+    error::EvaluateError, range::AspenRange, types::AspenType, value::AspenValue, EvaluateResult,
+};
+use rug::{Float, Integer};
+
+/// Number of values a range can hold to be shuffled, all of them are stored in the shuffled array.
+const MAX_SHUFFLE_LEN: usize = 1_000_000;
 
 pub fn module<'a>() -> AspenValue<'a> {
-    let mut hashmap = HashMap::new();
-
-    hashmap.insert(
-        "random",
-        AspenValue::RustBindFn {
-            name: "random",
-            code: random,
-        },
-    );
-
-    hashmap.insert(
-        "random_int",
-        AspenValue::RustBindFn {
-            name: "random_int",
-            code: random_int,
-        },
-    );
-
-    hashmap.insert(
-        "shuffle",
-        AspenValue::RustBindFn {
-            name: "shuffle",
-            code: shuffle,
-        },
-    );
-
-    AspenValue::Object(hashmap)
+    crate::native_module! {
+        "random" => random,
+        "random_int" => random_int,
+        "shuffle" => shuffle,
+    }
 }
 
 use rand::prelude::*;
 
-pub fn random<'a>(args: Vec<AspenValue<'a>>) -> EvaluateResult<AspenValue<'a>> {
-    if args.len() != 0 {
-        return Err(EvaluateError::Custom(
-            "random function expects no argument".to_string(),
-        ));
+crate::native_fn! {
+    pub fn random<'a>() -> EvaluateResult<Float> {
+        let mut rng = rand::thread_rng();
+        let f: f64 = rng.gen();
+        Ok(Float::with_val(18, f))
     }
-
-    let mut rng = rand::thread_rng();
-    let f: f64 = rng.gen();
-    let random_number = Float::with_val(18, f);
-    Ok(AspenValue::Float(OrdFloat::from(random_number)))
 }
 
-pub fn random_int<'a>(args: Vec<AspenValue<'a>>) -> EvaluateResult<AspenValue<'a>> {
-    if args.len() != 1 {
-        return Err(EvaluateError::Custom(
-            "shuffle function expects 1 argument: a range of ints".to_string(),
-        ));
-    }
+/// Returns a random integer in `0..bound`, with `bound` as large as needed.
+fn random_below(rng: &mut impl Rng, bound: &Integer) -> Integer {
+    let bits = bound.significant_bits();
 
-    let (start, end, step) = extract_range(args[0].to_owned())?;
-    let base_range = start..=end;
-    let expected_range = match step {
-        Some(step) => Some((start..=end).step_by(step)),
-        None => None,
-    };
+    // drawing until the number is below the bound keeps the distribution uniform
+    loop {
+        let mut n = Integer::new();
+        let mut remaining = bits;
 
-    let mut rng = rand::thread_rng();
-    let mut num: usize = rng.gen_range(base_range.clone());
+        while remaining > 0 {
+            let chunk = remaining.min(64);
+            n <<= chunk;
+            n += rng.gen::<u64>() >> (64 - chunk);
+            remaining -= chunk;
+        }
 
-    while expected_range.is_some() && !expected_range.clone().unwrap().any(|x| x == num) {
-        num = rng.gen_range(base_range.clone());
+        if n < *bound {
+            return n;
+        }
     }
-
-    Ok(AspenValue::Int(Integer::from(num)))
 }
 
-pub fn shuffle<'a>(args: Vec<AspenValue<'a>>) -> EvaluateResult<AspenValue<'a>> {
-    if args.len() != 1 {
-        return Err(EvaluateError::Custom(
-            "shuffle function expects 1 argument: a range or an array".to_string(),
-        ));
-    }
-
-    let arg = args[0].to_owned();
-    let mut rng = rand::thread_rng();
+crate::native_fn! {
+    /// Returns a random value of a range of integers, e.g `random_int(1:6)`.
+    pub fn random_int<'a>(range: AspenRange) -> EvaluateResult<Integer> {
+        if !matches!(range, AspenRange::Int { .. }) {
+            return Err(EvaluateError::Custom(
+                "A random integer can only be picked from a range of integers".to_owned(),
+            ));
+        }
 
-    match arg {
-        AspenValue::Array(mut values) => {
-            values.shuffle(&mut rng);
-            Ok(AspenValue::Array(values))
+        let len = range.len();
+        if len == 0 {
+            return Err(EvaluateError::Custom(
+                "Cannot pick a random integer from an empty range".to_owned(),
+            ));
         }
-        AspenValue::Range { .. } => {
-            let (start, end, step) = extract_range(arg)?;
-            let mut nums: Vec<_> = match step {
-                Some(step) => (start..=end).step_by(step).collect(),
-                None => (start..=end).collect(),
-            };
-
-            nums.shuffle(&mut rng);
-
-            Ok(AspenValue::Array(
-                nums.into_iter()
-                    .map(|x| AspenValue::Int(Integer::from(x)))
-                    .collect(),
-            ))
+
+        let index = random_below(&mut rand::thread_rng(), &len);
+
+        match range.get(&index) {
+            Some(AspenValue::Int(i)) => Ok(i),
+            _ => unreachable!("the index is below the length of a range of integers"),
         }
-        _ => {
-            return Err(EvaluateError::Custom(
-                "shuffle function expects an argument of type 'Range' or 'Array'".to_string(),
-            ))
+    }
+}
+
+crate::native_fn! {
+    pub fn shuffle<'a>(values: AspenValue<'a>) -> EvaluateResult<Vec<AspenValue<'a>>> {
+        let mut rng = rand::thread_rng();
+
+        match values {
+            AspenValue::Array(mut values) => {
+                values.shuffle(&mut rng);
+                Ok(values)
+            }
+            AspenValue::Range(range) => {
+                if range.len() > MAX_SHUFFLE_LEN {
+                    return Err(EvaluateError::Custom(format!(
+                        "Cannot shuffle a range of more than {} values",
+                        MAX_SHUFFLE_LEN
+                    )));
+                }
+
+                let mut values: Vec<_> = range.iter().collect();
+                values.shuffle(&mut rng);
+                Ok(values)
+            }
+            x => Err(EvaluateError::InvalidType {
+                expected: AspenType::Array,
+                found: x.into(),
+            }),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{error_of, value_of};
+
+    #[test]
+    fn shuffle_keeps_every_value_of_small_ranges() {
+        let code = r#"
+let math $import("math")
+let array $import("array")
+let shuffled array.sort(math.shuffle(1:5))
+"#;
+
+        assert_eq!(value_of(code, "shuffled"), "[1, 2, 3, 4, 5]");
+        assert!(
+            error_of("let math $import(\"math\")\nmath.shuffle(1:100000000000)\n")
+                .contains("Cannot shuffle a range of more than 1000000 values")
+        );
+    }
+}
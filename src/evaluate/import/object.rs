@@ -0,0 +1,171 @@
+use super::super::{
+    error::EvaluateError,
+    types::AspenType,
+    value::{AspenObject, AspenValue},
+    EvaluateResult,
+};
+use rug::Integer;
+
+pub fn module<'a>() -> AspenValue<'a> {
+    crate::native_module! {
+        "len" => len,
+        "keys" => keys,
+        "values" => values,
+        "entries" => entries,
+        "has" => has,
+        "remove" => remove,
+        "merge" => merge,
+        "from_entries" => from_entries,
+    }
+}
+
+crate::native_fn! {
+    pub fn len<'a>(object: AspenObject<'a>) -> EvaluateResult<Integer> {
+        Ok(Integer::from(object.len()))
+    }
+}
+
+crate::native_fn! {
+    pub fn keys<'a>(object: AspenObject<'a>) -> EvaluateResult<Vec<String>> {
+        Ok(object.into_keys().collect())
+    }
+}
+
+crate::native_fn! {
+    pub fn values<'a>(object: AspenObject<'a>) -> EvaluateResult<Vec<AspenValue<'a>>> {
+        Ok(object.into_values().collect())
+    }
+}
+
+crate::native_fn! {
+    /// Returns the `[key, value]` pairs of an object.
+    pub fn entries<'a>(object: AspenObject<'a>) -> EvaluateResult<Vec<Vec<AspenValue<'a>>>> {
+        Ok(object
+            .into_iter()
+            .map(|(key, value)| vec![AspenValue::Str(key), value])
+            .collect())
+    }
+}
+
+crate::native_fn! {
+    pub fn has<'a>(object: AspenObject<'a>, key: String) -> EvaluateResult<bool> {
+        Ok(object.contains_key(&key))
+    }
+}
+
+crate::native_fn! {
+    /// Returns a copy of the object without the `key` property, use `obj.remove(key)` to modify an object.
+    pub fn remove<'a>(object: AspenObject<'a>, key: String) -> EvaluateResult<AspenObject<'a>> {
+        let mut object = object;
+        object.shift_remove(&key);
+        Ok(object)
+    }
+}
+
+crate::native_fn! {
+    /// Returns a new object with the properties of both objects, those of `other` taking precedence.
+    ///
+    /// Properties already in `object` keep their position, new ones are added at the end.
+    pub fn merge<'a>(object: AspenObject<'a>, other: AspenObject<'a>) -> EvaluateResult<AspenObject<'a>> {
+        let mut object = object;
+        object.extend(other);
+        Ok(object)
+    }
+}
+
+crate::native_fn! {
+    /// Builds an object from `[key, value]` pairs, a key appearing twice keeps its last value.
+    pub fn from_entries<'a>(entries: Vec<Vec<AspenValue<'a>>>) -> EvaluateResult<AspenObject<'a>> {
+        let mut object = AspenObject::with_capacity(entries.len());
+
+        for entry in entries {
+            let len = entry.len();
+            let mut entry = entry.into_iter();
+
+            match (entry.next(), entry.next()) {
+                (Some(AspenValue::Str(key)), Some(value)) if len == 2 => {
+                    object.insert(key, value);
+                }
+                (Some(key), Some(_)) if len == 2 => {
+                    return Err(EvaluateError::InvalidType {
+                        expected: AspenType::String,
+                        found: key.into(),
+                    })
+                }
+                _ => {
+                    return Err(EvaluateError::Custom(format!(
+                        "An entry must be a [key, value] pair, found an array of length '{}'",
+                        len
+                    )))
+                }
+            }
+        }
+
+        Ok(object)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{error_of, value_of};
+
+    #[test]
+    fn module_functions_are_called_through_the_module() {
+        let code = r#"
+let object $import("object")
+let o {b! 1, a! 2}
+let len object.len(o)
+let keys object.keys(o)
+let values object.values(o)
+let entries object.entries(o)
+let has object.has(o, "a")
+let removed object.remove(o, "b")
+let merged object.merge(o, {a! 3, c! 4})
+let built object.from_entries([["x", 1], ["y", 2]])
+"#;
+        let value = |name| value_of(code, name);
+
+        assert_eq!(value("len"), "2");
+        assert_eq!(value("keys"), "[b, a]");
+        assert_eq!(value("values"), "[1, 2]");
+        assert_eq!(value("entries"), "[[b, 1], [a, 2]]");
+        assert_eq!(value("has"), "true");
+        assert_eq!(value("removed"), "{a! 2}");
+        assert_eq!(value("merged"), "{b! 1, a! 3, c! 4}");
+        assert_eq!(value("built"), "{x! 1, y! 2}");
+        assert_eq!(value("o"), "{b! 1, a! 2}");
+    }
+
+    #[test]
+    fn function_properties_take_precedence_over_builtin_methods() {
+        let code = r#"
+@size {
+    >> 42
+}
+let q {items! [1, 2, 3], len! size}
+let custom q.len()
+let p {len! 5}
+let builtin p.len()
+"#;
+
+        assert_eq!(value_of(code, "custom"), "42");
+        assert_eq!(value_of(code, "builtin"), "1");
+    }
+
+    #[test]
+    fn properties_are_read_with_dynamic_keys_in_insertion_order() {
+        let code = r#"
+let o {z! 1, a! 2, m! 3}
+let key "a"
+let by_key o[key]
+let by_literal o["m"]
+let keys o.keys()
+"#;
+
+        assert_eq!(value_of(code, "by_key"), "2");
+        assert_eq!(value_of(code, "by_literal"), "3");
+        assert_eq!(value_of(code, "keys"), "[z, a, m]");
+        assert!(error_of("let o {a! 1}\nlet v o[\"b\"]").contains("b"));
+        assert!(error_of("let o {a! 1}\nlet v o[0]").contains("expected type 'String'"));
+    }
+}
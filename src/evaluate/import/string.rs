@@ -0,0 +1,199 @@
+use super::super::{error::EvaluateError, value::AspenValue, EvaluateResult};
+use rug::Integer;
+
+pub fn module<'a>() -> AspenValue<'a> {
+    crate::native_module! {
+        "len" => len,
+        "split" => split,
+        "join" => join,
+        "trim" => trim,
+        "replace" => replace,
+        "find" => find,
+        "starts_with" => starts_with,
+        "ends_with" => ends_with,
+        "upper" => upper,
+        "lower" => lower,
+        "repeat" => repeat,
+        "chars" => chars,
+        "bytes" => bytes,
+    }
+}
+
+crate::native_fn! {
+    /// Counts the unicode chars of a string, not its bytes.
+    pub fn len<'a>(s: String) -> EvaluateResult<Integer> {
+        Ok(Integer::from(s.chars().count()))
+    }
+}
+
+crate::native_fn! {
+    /// Splits a string on a separator, or on whitespaces if none is given.
+    pub fn split<'a>(s: String, separator: Option<String>) -> EvaluateResult<Vec<String>> {
+        let parts = match separator {
+            Some(sep) if sep.is_empty() => {
+                return Err(EvaluateError::Custom("Cannot split a string on an empty separator".to_owned()))
+            }
+            Some(sep) => s.split(sep.as_str()).map(str::to_owned).collect(),
+            None => s.split_whitespace().map(str::to_owned).collect(),
+        };
+
+        Ok(parts)
+    }
+}
+
+crate::native_fn! {
+    /// Joins an array of values into a string, values that are not strings are displayed.
+    pub fn join<'a>(values: Vec<AspenValue<'a>>, separator: Option<String>) -> EvaluateResult<String> {
+        let parts: Vec<_> = values.iter().map(AspenValue::to_string).collect();
+
+        Ok(parts.join(separator.as_deref().unwrap_or("")))
+    }
+}
+
+crate::native_fn! {
+    pub fn trim<'a>(s: String) -> EvaluateResult<String> {
+        Ok(s.trim().to_owned())
+    }
+}
+
+crate::native_fn! {
+    pub fn replace<'a>(s: String, from: String, to: String) -> EvaluateResult<String> {
+        Ok(s.replace(&from, &to))
+    }
+}
+
+crate::native_fn! {
+    /// Returns the char index of the first occurrence of `pattern`, or nil if it is not found.
+    pub fn find<'a>(s: String, pattern: String) -> EvaluateResult<Option<Integer>> {
+        Ok(s
+            .find(&pattern)
+            .map(|byte_index| Integer::from(s[..byte_index].chars().count())))
+    }
+}
+
+crate::native_fn! {
+    pub fn starts_with<'a>(s: String, prefix: String) -> EvaluateResult<bool> {
+        Ok(s.starts_with(&prefix))
+    }
+}
+
+crate::native_fn! {
+    pub fn ends_with<'a>(s: String, suffix: String) -> EvaluateResult<bool> {
+        Ok(s.ends_with(&suffix))
+    }
+}
+
+crate::native_fn! {
+    pub fn upper<'a>(s: String) -> EvaluateResult<String> {
+        Ok(s.to_uppercase())
+    }
+}
+
+crate::native_fn! {
+    pub fn lower<'a>(s: String) -> EvaluateResult<String> {
+        Ok(s.to_lowercase())
+    }
+}
+
+crate::native_fn! {
+    pub fn repeat<'a>(s: String, times: Integer) -> EvaluateResult<String> {
+        match times.to_usize() {
+            Some(times) => Ok(s.repeat(times)),
+            None => Err(EvaluateError::Custom(format!(
+                "A string cannot be repeated '{}' times",
+                times
+            ))),
+        }
+    }
+}
+
+crate::native_fn! {
+    pub fn chars<'a>(s: String) -> EvaluateResult<Vec<String>> {
+        Ok(s.chars().map(String::from).collect())
+    }
+}
+
+crate::native_fn! {
+    /// Returns the UTF-8 bytes of a string.
+    pub fn bytes<'a>(s: String) -> EvaluateResult<Vec<Integer>> {
+        Ok(s.bytes().map(Integer::from).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{error_of, value_of};
+
+    #[test]
+    fn module_functions_are_called_through_the_module() {
+        let code = r#"
+let string $import("string")
+let s " Héllo wörld "
+let len string.len(s)
+let split string.split(s)
+let joined string.join(split, "-")
+let trimmed string.trim(s)
+let replaced string.replace(trimmed, "wörld", "you")
+let found string.find(trimmed, "w")
+let starts string.starts_with(trimmed, "Hé")
+let ends string.ends_with(trimmed, "x")
+let upper string.upper(trimmed)
+let lower string.lower(trimmed)
+let repeated string.repeat("ab", 3)
+let chars string.chars("hé")
+let bytes string.bytes("hé")
+"#;
+        let value = |name| value_of(code, name);
+
+        assert_eq!(value("len"), "13");
+        assert_eq!(value("split"), "[Héllo, wörld]");
+        assert_eq!(value("joined"), "Héllo-wörld");
+        assert_eq!(value("trimmed"), "Héllo wörld");
+        assert_eq!(value("replaced"), "Héllo you");
+        assert_eq!(value("found"), "6");
+        assert_eq!(value("starts"), "true");
+        assert_eq!(value("ends"), "false");
+        assert_eq!(value("upper"), "HÉLLO WÖRLD");
+        assert_eq!(value("lower"), "héllo wörld");
+        assert_eq!(value("repeated"), "ababab");
+        assert_eq!(value("chars"), "[h, é]");
+        assert_eq!(value("bytes"), "[104, 195, 169]");
+    }
+
+    #[test]
+    fn methods_match_module_functions() {
+        let code = r#"
+let s "a,b"
+let len s.len()
+let parts s.split(",")
+"#;
+
+        assert_eq!(value_of(code, "len"), "3");
+        assert_eq!(value_of(code, "parts"), "[a, b]");
+    }
+
+    #[test]
+    fn strings_are_indexed_and_sliced_by_chars() {
+        let code = r#"
+let s "héllo"
+let second s[1]
+let middle s[1:3]
+let start s[0:<2]
+let reversed s[4:0:-1]
+"#;
+
+        assert_eq!(value_of(code, "second"), "é");
+        assert_eq!(value_of(code, "middle"), "éll");
+        assert_eq!(value_of(code, "start"), "hé");
+        assert_eq!(value_of(code, "reversed"), "olléh");
+        assert!(error_of("let c \"hé\"[2]").contains("out of bounds"));
+    }
+
+    #[test]
+    fn invalid_string_arguments() {
+        assert_eq!(value_of(r#"let i "abc".find("x")"#, "i"), "nil");
+        assert!(error_of(r#"let p "abc".split("")"#).contains("empty separator"));
+        assert!(error_of(r#"let r "ab".repeat(-1)"#).contains("cannot be repeated '-1' times"));
+        assert!(error_of(r#"let u "ab".upper(1)"#).contains("Expected at most 0, found 1"));
+    }
+}
@@ -0,0 +1,177 @@
+use super::{error::EvaluateError, value::AspenValue, EvaluateResult};
+use std::{cell::RefCell, fmt, rc::Rc};
+
+/// A lazy sequence of values: a generator, the lines of a file...
+///
+/// All the copies of an iterator share its state, advancing one of them advances them all.
+#[derive(Clone)]
+pub struct AspenIterator<'a>(Rc<RefCell<dyn Iterator<Item = EvaluateResult<AspenValue<'a>>> + 'a>>);
+
+impl<'a> AspenIterator<'a> {
+    pub fn new(iterator: impl Iterator<Item = EvaluateResult<AspenValue<'a>>> + 'a) -> Self {
+        Self(Rc::new(RefCell::new(iterator)))
+    }
+
+    /// Returns the next value, or `None` once the iterator is exhausted.
+    pub fn next_value(&self) -> EvaluateResult<Option<AspenValue<'a>>> {
+        // a generator asking for its own next value
+        let mut iterator = self.0.try_borrow_mut().map_err(|_| {
+            EvaluateError::Custom("An iterator cannot be advanced while it is running".to_owned())
+        })?;
+
+        iterator.next().transpose()
+    }
+}
+
+impl<'a> Iterator for AspenIterator<'a> {
+    type Item = EvaluateResult<AspenValue<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_value().transpose()
+    }
+}
+
+impl fmt::Debug for AspenIterator<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AspenIterator")
+    }
+}
+
+/// Two iterators are equal if they share the same state.
+impl PartialEq for AspenIterator<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::addr_eq(Rc::as_ptr(&self.0), Rc::as_ptr(&other.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{error_of, value_of};
+
+    #[test]
+    fn objects_with_a_next_method_keep_their_state() {
+        let code = r#"
+@step {
+    if self.i >= self.max {
+        >> Done
+    }
+    self.i = self.i + 1
+    >> self.i
+}
+let counter {i! 0, max! 3, next! step}
+let seen []
+for x in counter {
+    seen.push(x)
+}
+"#;
+
+        assert_eq!(value_of(code, "seen"), "[1, 2, 3]");
+        // the loop iterates over a copy of the object
+        assert_eq!(
+            value_of(code, "counter"),
+            "{i! 0, max! 3, next! Func<step>}"
+        );
+    }
+
+    #[test]
+    fn next_can_be_inherited_from_the_proto() {
+        let code = r#"
+@step {
+    if self.i == 2 {
+        >> Done
+    }
+    self.i = self.i + 1
+    >> self.i * 10
+}
+let counting {next! step}
+let seen []
+for x in {i! 0, proto! counting} {
+    seen.push(x)
+}
+"#;
+
+        assert_eq!(value_of(code, "seen"), "[10, 20]");
+    }
+
+    #[test]
+    fn generators_yield_lazily() {
+        let code = r#"
+@naturals {
+    let i 0
+    while true {
+        yield i
+        i = i + 1
+    }
+}
+let nat naturals()
+let first nat.take(3)
+let next nat.next()
+"#;
+
+        assert_eq!(value_of(code, "first"), "[0, 1, 2]");
+        assert_eq!(value_of(code, "next"), "3");
+    }
+
+    #[test]
+    fn generators_yield_inside_try_and_match() {
+        let code = r#"
+@safe_values {
+    try {
+        yield 1
+        yield [1][5]
+        yield 2
+    } catch e {
+        yield e.kind
+    }
+    yield 3
+}
+@labels {
+    for i in 0:2 {
+        match i {
+            0 => {
+                yield "zero"
+            },
+            n if n > 1 => {
+                yield "big {n}"
+                yield "again {n}"
+            },
+            _ => {
+                yield "other"
+            }
+        }
+    }
+}
+@nested {
+    try {
+        match 1 {
+            n => {
+                yield n
+                let bad 1 % 0
+                yield "unreachable"
+            }
+        }
+    } catch {
+        yield "caught"
+    }
+}
+let safe safe_values().take(5)
+let labelled labels().take(5)
+let caught nested().take(5)
+"#;
+
+        assert_eq!(value_of(code, "safe"), "[1, IndexError, 3]");
+        assert_eq!(value_of(code, "labelled"), "[zero, other, big 2, again 2]");
+        assert_eq!(value_of(code, "caught"), "[1, caught]");
+    }
+
+    #[test]
+    fn objects_without_next_cannot_be_iterated() {
+        let code = r#"
+for x in {a! 1} {
+    print(x)
+}
+"#;
+
+        assert!(error_of(code).contains("'next' method"));
+    }
+}
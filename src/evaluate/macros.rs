@@ -0,0 +1,157 @@
+/// Declares a function callable from Aspen with typed arguments!
+///
+/// The number of arguments is checked against the declaration and each argument is extracted with
+/// [`FromAspenValue`](crate::evaluate::native::FromAspenValue), an `Option<T>` argument can be left out.
+/// The body returns an `EvaluateResult<T>` where `T` implements [`IntoAspenValue`](crate::evaluate::native::IntoAspenValue).
+///
+/// ```rust
+/// crate::native_fn! {
+///     pub fn read_file<'a>(file_name: String) -> EvaluateResult<std::io::Result<String>> {
+///         Ok(fs::read_to_string(file_name))
+///     }
+/// }
+/// ```
+///
+/// A function calling back Aspen functions receives the context of its caller as first argument:
+///
+/// ```rust
+/// crate::native_fn! {
+///     pub fn map<'a>(ctx: &AspenTable<'a>, values: Vec<AspenValue<'a>>, f: AspenValue<'a>) -> EvaluateResult<Vec<AspenValue<'a>>> {
+///         values.into_iter().map(|v| ctx.call_value(f.clone(), vec![v])).collect()
+///     }
+/// }
+/// ```
+///
+/// The number of arguments the function accepts is returned by `name::arity()`, e.g `map::arity()` is `(2, 2)`.
+///
+/// A function can also accept any number of arguments, they are then all collected in a single one:
+///
+/// ```rust
+/// crate::native_fn! {
+///     pub fn array<'a>(...values: Vec<AspenValue<'a>>) -> EvaluateResult<Vec<AspenValue<'a>>> {
+///         Ok(values)
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! native_fn {
+    ($(#[$meta:meta])* $vis:vis fn $name:ident<$lt:lifetime>($ctx:ident: &AspenTable<$ctx_lt:lifetime> $(, $arg:ident: $ty:ty)* $(,)?) -> $ret:ty $body:block) => {
+        $(#[$meta])*
+        $vis fn $name<$lt>(
+            $ctx: &$crate::evaluate::AspenTable<$lt>,
+            args: Vec<$crate::evaluate::value::AspenValue<$lt>>,
+        ) -> $crate::evaluate::EvaluateResult<$crate::evaluate::value::AspenValue<$lt>> {
+            #[allow(unused_imports)]
+            use $crate::evaluate::native::{FromAspenValue, IntoAspenValue};
+
+            fn inner<$lt>($ctx: &$crate::evaluate::AspenTable<$lt>, $($arg: $ty),*) -> $ret $body
+
+            $crate::native_fn!(@extract $lt, args, $($arg: $ty),*);
+
+            inner($ctx, $($arg),*).map(IntoAspenValue::into_aspen_value)
+        }
+
+        $crate::native_fn!(@arity $vis $name<$lt>($($arg: $ty),*));
+    };
+    ($(#[$meta:meta])* $vis:vis fn $name:ident<$lt:lifetime>($($arg:ident: $ty:ty),* $(,)?) -> $ret:ty $body:block) => {
+        $(#[$meta])*
+        $vis fn $name<$lt>(
+            _ctx: &$crate::evaluate::AspenTable<$lt>,
+            args: Vec<$crate::evaluate::value::AspenValue<$lt>>,
+        ) -> $crate::evaluate::EvaluateResult<$crate::evaluate::value::AspenValue<$lt>> {
+            #[allow(unused_imports)]
+            use $crate::evaluate::native::{FromAspenValue, IntoAspenValue};
+
+            #[allow(clippy::extra_unused_lifetimes)]
+            fn inner<$lt>($($arg: $ty),*) -> $ret $body
+
+            $crate::native_fn!(@extract $lt, args, $($arg: $ty),*);
+
+            inner($($arg),*).map(IntoAspenValue::into_aspen_value)
+        }
+
+        $crate::native_fn!(@arity $vis $name<$lt>($($arg: $ty),*));
+    };
+
+    ($(#[$meta:meta])* $vis:vis fn $name:ident<$lt:lifetime>(...$rest:ident: $ty:ty) -> $ret:ty $body:block) => {
+        $(#[$meta])*
+        $vis fn $name<$lt>(
+            _ctx: &$crate::evaluate::AspenTable<$lt>,
+            args: Vec<$crate::evaluate::value::AspenValue<$lt>>,
+        ) -> $crate::evaluate::EvaluateResult<$crate::evaluate::value::AspenValue<$lt>> {
+            use $crate::evaluate::native::{FromAspenValue, IntoAspenValue};
+
+            #[allow(clippy::extra_unused_lifetimes)]
+            fn inner<$lt>($rest: $ty) -> $ret $body
+
+            let $rest = <$ty as FromAspenValue<$lt>>::from_aspen_value(
+                $crate::evaluate::value::AspenValue::Array(args),
+            )?;
+
+            inner($rest).map(IntoAspenValue::into_aspen_value)
+        }
+
+        #[doc(hidden)]
+        #[allow(dead_code)]
+        $vis mod $name {
+            /// Minimum and maximum number of arguments of the function.
+            pub fn arity() -> (usize, usize) {
+                (0, usize::MAX)
+            }
+        }
+    };
+    (@arity $vis:vis $name:ident<$lt:lifetime>($($arg:ident: $ty:ty),*)) => {
+        #[doc(hidden)]
+        #[allow(dead_code)]
+        $vis mod $name {
+            #[allow(unused_imports)]
+            use super::*;
+
+            /// Minimum and maximum number of arguments of the function.
+            #[allow(clippy::extra_unused_lifetimes)]
+            pub fn arity<$lt>() -> (usize, usize) {
+                $crate::evaluate::native::arity(&[$(<$ty as $crate::evaluate::native::FromAspenValue<$lt>>::OPTIONAL),*])
+            }
+        }
+    };
+    (@extract $lt:lifetime, $args:ident, $($arg:ident: $ty:ty),*) => {
+        let (min, max) =
+            $crate::evaluate::native::arity(&[$(<$ty as FromAspenValue<$lt>>::OPTIONAL),*]);
+        $crate::evaluate::native::check_arity(min, max, $args.len())?;
+
+        #[allow(unused_mut, unused_variables)]
+        let mut $args = $args.into_iter();
+        $(
+            let $arg = match $args.next() {
+                Some(value) => <$ty as FromAspenValue<$lt>>::from_aspen_value(value)?,
+                // the arity check ensures only optional arguments can be missing
+                None => <$ty as FromAspenValue<$lt>>::from_missing().unwrap(),
+            };
+        )*
+    };
+}
+
+/// Builds the object of a module from its native functions!
+///
+/// ```rust
+/// crate::native_module! {
+///     "read" => read_file,
+///     "write" => write_file,
+/// }
+/// ```
+#[macro_export]
+macro_rules! native_module {
+    ($($name:literal => $code:path),* $(,)?) => {{
+        let mut hashmap = $crate::evaluate::value::AspenObject::new();
+        $(
+            hashmap.insert(
+                $name.to_owned(),
+                $crate::evaluate::value::AspenValue::RustBindFn {
+                    name: $name,
+                    code: $code,
+                },
+            );
+        )*
+        $crate::evaluate::value::AspenValue::Object(hashmap)
+    }};
+}
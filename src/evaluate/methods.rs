@@ -0,0 +1,344 @@
+use super::{
+    binary::evaluate_binary,
+    error::EvaluateError,
+    import::{array, object, string},
+    iterator::AspenIterator,
+    native::{check_arity, NativeFn},
+    range::AspenRange,
+    types::AspenType,
+    value::AspenValue,
+    AspenTable, EvaluateResult,
+};
+use crate::parser::operator::BinaryOperator;
+use rug::{Float, Integer};
+
+/// Signature of the methods modifying the value they are called on, e.g `arr.push(1)`.
+pub type MutMethod<'a> =
+    fn(receiver: &mut AspenValue<'a>, args: Vec<AspenValue<'a>>) -> EvaluateResult<AspenValue<'a>>;
+
+/// A builtin method, called with `value.method(args)`.
+#[derive(Clone, Copy)]
+pub enum Method<'a> {
+    /// A native function receiving the value it is called on as its first argument, along its arity.
+    Pure(NativeFn<'a>, (usize, usize)),
+    /// A method modifying the value it is called on, the new value is stored back in the variable.
+    Mut(MutMethod<'a>),
+}
+
+/// Builds a [`Method::Pure`] from a function declared with [`crate::native_fn`].
+macro_rules! pure {
+    ($($code:ident)::+) => {
+        Method::Pure($($code)::+, $($code)::+::arity())
+    };
+}
+
+/// Finds the builtin method `name` of the type of `receiver`.
+pub fn find_method<'a>(receiver: &AspenValue<'a>, name: &str) -> Option<Method<'a>> {
+    let method = match (receiver, name) {
+        (_, "to_str") => pure!(to_str),
+
+        (AspenValue::Int(_) | AspenValue::Float(_), "power") => pure!(power),
+        (AspenValue::Int(_) | AspenValue::Float(_), "abs") => pure!(abs),
+        (AspenValue::Float(_), "round") => pure!(round),
+        (AspenValue::Float(_), "floor") => pure!(floor),
+        (AspenValue::Float(_), "ceil") => pure!(ceil),
+
+        (AspenValue::Str(_), "len") => pure!(string::len),
+        (AspenValue::Str(_), "split") => pure!(string::split),
+        (AspenValue::Str(_), "trim") => pure!(string::trim),
+        (AspenValue::Str(_), "replace") => pure!(string::replace),
+        (AspenValue::Str(_), "find") => pure!(string::find),
+        (AspenValue::Str(_), "starts_with") => pure!(string::starts_with),
+        (AspenValue::Str(_), "ends_with") => pure!(string::ends_with),
+        (AspenValue::Str(_), "upper") => pure!(string::upper),
+        (AspenValue::Str(_), "lower") => pure!(string::lower),
+        (AspenValue::Str(_), "repeat") => pure!(string::repeat),
+        (AspenValue::Str(_), "chars") => pure!(string::chars),
+        (AspenValue::Str(_), "bytes") => pure!(string::bytes),
+
+        (AspenValue::Array(_), "len") => pure!(array::len),
+        (AspenValue::Array(_), "join") => pure!(string::join),
+        (AspenValue::Array(_), "map") => pure!(array::map),
+        (AspenValue::Array(_), "filter") => pure!(array::filter),
+        (AspenValue::Array(_), "reduce") => pure!(array::reduce),
+        (AspenValue::Array(_), "find") => pure!(array::find),
+        (AspenValue::Array(_), "any") => pure!(array::any),
+        (AspenValue::Array(_), "all") => pure!(array::all),
+        (AspenValue::Array(_), "sort") => pure!(array::sort),
+        (AspenValue::Array(_), "reverse") => pure!(array::reverse),
+        (AspenValue::Array(_), "zip") => pure!(array::zip),
+        (AspenValue::Array(_), "flatten") => pure!(array::flatten),
+        (AspenValue::Array(_), "unique") => pure!(array::unique),
+        (AspenValue::Array(_), "index_of") => pure!(array::index_of),
+        (AspenValue::Array(_), "push") => Method::Mut(push),
+        (AspenValue::Array(_), "pop") => Method::Mut(pop),
+
+        (AspenValue::Object(_), "len") => pure!(object::len),
+        (AspenValue::Object(_), "keys") => pure!(object::keys),
+        (AspenValue::Object(_), "values") => pure!(object::values),
+        (AspenValue::Object(_), "entries") => pure!(object::entries),
+        (AspenValue::Object(_), "has") => pure!(object::has),
+        (AspenValue::Object(_), "merge") => pure!(object::merge),
+        (AspenValue::Object(_), "remove") => Method::Mut(remove),
+
+        (AspenValue::Range(_), "len") => pure!(range_len),
+        (AspenValue::Range(_), "contains") => pure!(range_contains),
+
+        (AspenValue::Iterator(_), "next") => pure!(next),
+        (AspenValue::Iterator(_), "take") => pure!(take),
+
+        _ => return None,
+    };
+
+    Some(method)
+}
+
+impl<'a> Method<'a> {
+    /// Calls the method on a value that is not stored in a variable, a [`Method::Mut`] only modifies a temporary value.
+    pub fn call(
+        self,
+        ctx: &AspenTable<'a>,
+        mut receiver: AspenValue<'a>,
+        args: Vec<AspenValue<'a>>,
+    ) -> EvaluateResult<AspenValue<'a>> {
+        match self {
+            Method::Mut(code) => code(&mut receiver, args),
+            Method::Pure(code, (min, max)) => {
+                // the receiver is not an argument written by the user
+                check_arity(min.saturating_sub(1), max.saturating_sub(1), args.len())?;
+
+                let mut all_args = Vec::with_capacity(args.len() + 1);
+                all_args.push(receiver);
+                all_args.extend(args);
+
+                code(ctx, all_args)
+            }
+        }
+    }
+}
+
+crate::native_fn! {
+    fn to_str<'a>(value: AspenValue<'a>) -> EvaluateResult<String> {
+        Ok(value.to_string())
+    }
+}
+
+crate::native_fn! {
+    fn power<'a>(value: AspenValue<'a>, exponent: AspenValue<'a>) -> EvaluateResult<AspenValue<'a>> {
+        evaluate_binary(value, BinaryOperator::Exponent, exponent)
+    }
+}
+
+crate::native_fn! {
+    fn abs<'a>(value: AspenValue<'a>) -> EvaluateResult<AspenValue<'a>> {
+        match value {
+            AspenValue::Int(i) => Ok(AspenValue::Int(i.abs())),
+            AspenValue::Float(f) => Ok(Float::from(f).abs().into()),
+            _ => unreachable!("only called on numbers"),
+        }
+    }
+}
+
+fn float_to_int(f: Float) -> EvaluateResult<Integer> {
+    f.to_integer().ok_or_else(|| {
+        EvaluateError::Custom(format!("Float '{}' cannot be converted to an integer", f))
+    })
+}
+
+crate::native_fn! {
+    fn round<'a>(value: Float) -> EvaluateResult<Integer> {
+        float_to_int(value.round())
+    }
+}
+
+crate::native_fn! {
+    fn floor<'a>(value: Float) -> EvaluateResult<Integer> {
+        float_to_int(value.floor())
+    }
+}
+
+crate::native_fn! {
+    fn ceil<'a>(value: Float) -> EvaluateResult<Integer> {
+        float_to_int(value.ceil())
+    }
+}
+
+fn push<'a>(
+    receiver: &mut AspenValue<'a>,
+    args: Vec<AspenValue<'a>>,
+) -> EvaluateResult<AspenValue<'a>> {
+    match receiver {
+        AspenValue::Array(values) => {
+            values.extend(args);
+            Ok(AspenValue::Nil)
+        }
+        _ => unreachable!("only called on arrays"),
+    }
+}
+
+fn pop<'a>(
+    receiver: &mut AspenValue<'a>,
+    args: Vec<AspenValue<'a>>,
+) -> EvaluateResult<AspenValue<'a>> {
+    super::native::check_arity(0, 0, args.len())?;
+
+    match receiver {
+        AspenValue::Array(values) => Ok(values.pop().unwrap_or(AspenValue::Nil)),
+        _ => unreachable!("only called on arrays"),
+    }
+}
+
+fn remove<'a>(
+    receiver: &mut AspenValue<'a>,
+    args: Vec<AspenValue<'a>>,
+) -> EvaluateResult<AspenValue<'a>> {
+    super::native::check_arity(1, 1, args.len())?;
+
+    let key = match args.into_iter().next().unwrap() {
+        AspenValue::Str(key) => key,
+        x => {
+            return Err(EvaluateError::InvalidType {
+                expected: AspenType::String,
+                found: x.into(),
+            })
+        }
+    };
+
+    match receiver {
+        AspenValue::Object(values) => Ok(values.shift_remove(&key).unwrap_or(AspenValue::Nil)),
+        _ => unreachable!("only called on objects"),
+    }
+}
+
+crate::native_fn! {
+    fn range_len<'a>(range: AspenRange) -> EvaluateResult<Integer> {
+        Ok(range.len())
+    }
+}
+
+crate::native_fn! {
+    fn range_contains<'a>(range: AspenRange, value: AspenValue<'a>) -> EvaluateResult<bool> {
+        Ok(range.contains(&value))
+    }
+}
+
+crate::native_fn! {
+    /// Returns the next value of an iterator, or `Done` once it is exhausted.
+    fn next<'a>(iterator: AspenIterator<'a>) -> EvaluateResult<AspenValue<'a>> {
+        Ok(iterator.next_value()?.unwrap_or(AspenValue::Done))
+    }
+}
+
+crate::native_fn! {
+    /// Returns an array of the `n` next values of an iterator, or less if it ends before.
+    fn take<'a>(iterator: AspenIterator<'a>, n: Integer) -> EvaluateResult<Vec<AspenValue<'a>>> {
+        let n = n.to_usize().ok_or_else(|| {
+            EvaluateError::Custom(format!("Cannot take '{}' values from an iterator", n))
+        })?;
+
+        let mut values = Vec::with_capacity(n.min(1024));
+        while values.len() < n {
+            match iterator.next_value()? {
+                Some(value) => values.push(value),
+                None => break,
+            }
+        }
+
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{error_of, value_of};
+
+    #[test]
+    fn methods_of_numbers() {
+        let code = r#"
+let i -3, f 2.6
+let abs i.abs()
+let power i.power(2)
+let rounded f.round()
+let floored f.floor()
+let ceiled f.ceil()
+let text i.to_str()
+"#;
+        let value = |name| value_of(code, name);
+
+        assert_eq!(value("abs"), "3");
+        assert_eq!(value("power"), "9");
+        assert_eq!(value("rounded"), "3");
+        assert_eq!(value("floored"), "2");
+        assert_eq!(value("ceiled"), "3");
+        assert_eq!(value("text"), "-3");
+    }
+
+    #[test]
+    fn mutating_methods_store_the_value_back() {
+        let code = r#"
+let values [1, 2]
+values.push(3, 4)
+let last values.pop()
+let o {a! 1, b! 2}
+let removed o.remove("a")
+"#;
+
+        assert_eq!(value_of(code, "values"), "[1, 2, 3]");
+        assert_eq!(value_of(code, "last"), "4");
+        assert_eq!(value_of(code, "removed"), "1");
+        assert_eq!(value_of(code, "o"), "{b! 2}");
+    }
+
+    #[test]
+    fn mutating_methods_change_properties_in_place() {
+        let code = r#"
+let o {a! [1], inner! {b! {c! 1, d! 2}}}
+o.a.push(2)
+o.inner.b.remove("c")
+"#;
+
+        assert_eq!(value_of(code, "o"), "{a! [1, 2], inner! {b! {d! 2}}}");
+        assert!(error_of("const O {a! [1]}\nO.a.push(2)\n").contains("'O'"));
+    }
+
+    #[test]
+    fn methods_can_be_chained() {
+        let code = r#"
+let words "  b a c ".trim().split(" ").sort().join("-").upper()
+let range_len (1:10).len()
+"#;
+
+        assert_eq!(value_of(code, "words"), "A-B-C");
+        assert_eq!(value_of(code, "range_len"), "10");
+    }
+
+    #[test]
+    fn method_errors_count_the_arguments_written() {
+        assert_eq!(
+            error_of("let a [1].pop(1)"),
+            "Invalid number of arguments when invoking function. Expected at most 0, found 1"
+        );
+        assert!(error_of("let n 1\nlet a n.power()").contains("Expected at least 1, found 0"));
+        assert!(error_of("let n 1\nlet a n.unknown()").contains("unknown"));
+        assert!(error_of("let a [1].map()").contains("Expected at least 1, found 0"));
+    }
+
+    #[test]
+    fn callback_errors_are_not_counted_as_method_errors() {
+        let code = r#"
+@g {
+    >> 1
+}
+let a [1].map(g)
+"#;
+        assert!(error_of(code).contains("Expected at most 0, found 1"));
+
+        let code = r#"
+@add acc, x, y {
+    >> acc + x + y
+}
+let a [1, 2].reduce(add)
+"#;
+        assert!(error_of(code).contains("Expected at least 3, found 2"));
+    }
+}
@@ -0,0 +1,351 @@
+use super::{
+    err::AspenErr,
+    error::EvaluateError,
+    iterator::AspenIterator,
+    range::AspenRange,
+    types::AspenType,
+    value::{AspenObject, AspenValue},
+    AspenTable, EvaluateResult,
+};
+use rug::{float::OrdFloat, Float, Integer};
+use std::fmt::Display;
+
+/// Signature of every function written in Rust and callable from Aspen.
+///
+/// `ctx` is the context of the caller, it is used to call the functions received as arguments.
+pub type NativeFn<'a> =
+    fn(ctx: &AspenTable<'a>, args: Vec<AspenValue<'a>>) -> EvaluateResult<AspenValue<'a>>;
+
+/// Extracts a Rust value from an argument given to a native function.
+///
+/// See [`crate::native_fn`] to declare a native function with typed arguments.
+pub trait FromAspenValue<'a>: Sized {
+    /// Whether the argument can be left out when calling the function.
+    const OPTIONAL: bool = false;
+
+    fn from_aspen_value(value: AspenValue<'a>) -> EvaluateResult<Self>;
+
+    /// Value used when the argument is left out, only called if [`FromAspenValue::OPTIONAL`] is true.
+    fn from_missing() -> Option<Self> {
+        None
+    }
+}
+
+/// Turns the result of a native function back into an [`AspenValue`].
+pub trait IntoAspenValue<'a> {
+    fn into_aspen_value(self) -> AspenValue<'a>;
+}
+
+/// Computes the minimum and maximum number of arguments a native function accepts.
+///
+/// Arguments after the last non optional one can all be left out.
+pub fn arity(optional: &[bool]) -> (usize, usize) {
+    let min = optional
+        .iter()
+        .rposition(|is_optional| !is_optional)
+        .map(|i| i + 1)
+        .unwrap_or(0);
+
+    (min, optional.len())
+}
+
+/// Returns an error if `found` arguments cannot be given to a function expecting between `min` and `max` arguments.
+pub fn check_arity(min: usize, max: usize, found: usize) -> EvaluateResult<()> {
+    if found < min {
+        return Err(EvaluateError::NotEnoughArgs {
+            expected_num: min,
+            found,
+        });
+    }
+
+    if found > max {
+        return Err(EvaluateError::TooMuchArgs {
+            expected_num: max,
+            found,
+        });
+    }
+
+    Ok(())
+}
+
+fn invalid_type<T>(expected: AspenType, found: AspenValue<'_>) -> EvaluateResult<T> {
+    Err(EvaluateError::InvalidType {
+        expected,
+        found: found.into(),
+    })
+}
+
+impl<'a> FromAspenValue<'a> for AspenValue<'a> {
+    fn from_aspen_value(value: AspenValue<'a>) -> EvaluateResult<Self> {
+        Ok(value)
+    }
+}
+
+impl<'a> FromAspenValue<'a> for String {
+    fn from_aspen_value(value: AspenValue<'a>) -> EvaluateResult<Self> {
+        match value {
+            AspenValue::Str(s) => Ok(s),
+            x => invalid_type(AspenType::String, x),
+        }
+    }
+}
+
+impl<'a> FromAspenValue<'a> for bool {
+    fn from_aspen_value(value: AspenValue<'a>) -> EvaluateResult<Self> {
+        match value {
+            AspenValue::Bool(b) => Ok(b),
+            x => invalid_type(AspenType::Bool, x),
+        }
+    }
+}
+
+impl<'a> FromAspenValue<'a> for Integer {
+    fn from_aspen_value(value: AspenValue<'a>) -> EvaluateResult<Self> {
+        match value {
+            AspenValue::Int(i) => Ok(i),
+            x => invalid_type(AspenType::Int, x),
+        }
+    }
+}
+
+impl<'a> FromAspenValue<'a> for OrdFloat {
+    fn from_aspen_value(value: AspenValue<'a>) -> EvaluateResult<Self> {
+        match value {
+            AspenValue::Float(f) => Ok(f),
+            x => invalid_type(AspenType::Float, x),
+        }
+    }
+}
+
+impl<'a> FromAspenValue<'a> for Float {
+    fn from_aspen_value(value: AspenValue<'a>) -> EvaluateResult<Self> {
+        OrdFloat::from_aspen_value(value).map(Float::from)
+    }
+}
+
+impl<'a, T: FromAspenValue<'a>> FromAspenValue<'a> for Vec<T> {
+    fn from_aspen_value(value: AspenValue<'a>) -> EvaluateResult<Self> {
+        match value {
+            AspenValue::Array(values) => values.into_iter().map(T::from_aspen_value).collect(),
+            x => invalid_type(AspenType::Array, x),
+        }
+    }
+}
+
+impl<'a> FromAspenValue<'a> for AspenObject<'a> {
+    fn from_aspen_value(value: AspenValue<'a>) -> EvaluateResult<Self> {
+        match value {
+            AspenValue::Object(values) => Ok(values),
+            x => invalid_type(AspenType::Object, x),
+        }
+    }
+}
+
+impl<'a> FromAspenValue<'a> for AspenRange {
+    fn from_aspen_value(value: AspenValue<'a>) -> EvaluateResult<Self> {
+        match value {
+            AspenValue::Range(range) => Ok(range),
+            x => invalid_type(AspenType::Range, x),
+        }
+    }
+}
+
+impl<'a> FromAspenValue<'a> for AspenIterator<'a> {
+    fn from_aspen_value(value: AspenValue<'a>) -> EvaluateResult<Self> {
+        match value {
+            AspenValue::Iterator(iterator) => Ok(iterator),
+            x => invalid_type(AspenType::Iterator, x),
+        }
+    }
+}
+
+/// An optional argument, `nil` is considered as a missing argument.
+impl<'a, T: FromAspenValue<'a>> FromAspenValue<'a> for Option<T> {
+    const OPTIONAL: bool = true;
+
+    fn from_aspen_value(value: AspenValue<'a>) -> EvaluateResult<Self> {
+        match value {
+            AspenValue::Nil => Ok(None),
+            x => T::from_aspen_value(x).map(Some),
+        }
+    }
+
+    fn from_missing() -> Option<Self> {
+        Some(None)
+    }
+}
+
+impl<'a> IntoAspenValue<'a> for AspenValue<'a> {
+    fn into_aspen_value(self) -> AspenValue<'a> {
+        self
+    }
+}
+
+impl<'a> IntoAspenValue<'a> for () {
+    fn into_aspen_value(self) -> AspenValue<'a> {
+        AspenValue::Nil
+    }
+}
+
+impl<'a> IntoAspenValue<'a> for String {
+    fn into_aspen_value(self) -> AspenValue<'a> {
+        AspenValue::Str(self)
+    }
+}
+
+impl<'a> IntoAspenValue<'a> for bool {
+    fn into_aspen_value(self) -> AspenValue<'a> {
+        AspenValue::Bool(self)
+    }
+}
+
+impl<'a> IntoAspenValue<'a> for Integer {
+    fn into_aspen_value(self) -> AspenValue<'a> {
+        AspenValue::Int(self)
+    }
+}
+
+impl<'a> IntoAspenValue<'a> for OrdFloat {
+    fn into_aspen_value(self) -> AspenValue<'a> {
+        AspenValue::Float(self)
+    }
+}
+
+impl<'a> IntoAspenValue<'a> for Float {
+    fn into_aspen_value(self) -> AspenValue<'a> {
+        AspenValue::Float(OrdFloat::from(self))
+    }
+}
+
+impl<'a, T: IntoAspenValue<'a>> IntoAspenValue<'a> for Vec<T> {
+    fn into_aspen_value(self) -> AspenValue<'a> {
+        AspenValue::Array(self.into_iter().map(T::into_aspen_value).collect())
+    }
+}
+
+impl<'a> IntoAspenValue<'a> for AspenObject<'a> {
+    fn into_aspen_value(self) -> AspenValue<'a> {
+        AspenValue::Object(self)
+    }
+}
+
+impl<'a> IntoAspenValue<'a> for AspenIterator<'a> {
+    fn into_aspen_value(self) -> AspenValue<'a> {
+        AspenValue::Iterator(self)
+    }
+}
+
+impl<'a, T: IntoAspenValue<'a>> IntoAspenValue<'a> for Option<T> {
+    fn into_aspen_value(self) -> AspenValue<'a> {
+        match self {
+            Some(value) => value.into_aspen_value(),
+            None => AspenValue::Nil,
+        }
+    }
+}
+
+/// A failure that the script can handle: an `Err` becomes an Aspen error value that can be propagated with `?`.
+impl<'a, T: IntoAspenValue<'a>, E: Display> IntoAspenValue<'a> for Result<T, E> {
+    fn into_aspen_value(self) -> AspenValue<'a> {
+        match self {
+            Ok(value) => value.into_aspen_value(),
+            Err(err) => AspenErr::new(err.to_string()).into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{arity, check_arity, FromAspenValue, IntoAspenValue};
+    use crate::evaluate::{value::AspenValue, AspenTable, EvaluateError, EvaluateResult};
+    use rug::Integer;
+
+    crate::native_fn! {
+        fn repeat<'a>(text: String, times: Option<Integer>) -> EvaluateResult<String> {
+            let times = times.map_or(Some(2), |times| times.to_usize()).unwrap_or(0);
+            Ok(text.repeat(times))
+        }
+    }
+
+    fn call(args: Vec<AspenValue<'_>>) -> EvaluateResult<AspenValue<'_>> {
+        repeat(&AspenTable::global(), args)
+    }
+
+    #[test]
+    fn trailing_optional_arguments_can_be_left_out() {
+        assert_eq!(arity(&[false, true]), (1, 2));
+        assert_eq!(arity(&[true, false, true]), (2, 3));
+        assert_eq!(arity(&[]), (0, 0));
+        assert_eq!(repeat::arity(), (1, 2));
+
+        assert!(check_arity(1, 2, 2).is_ok());
+        assert!(matches!(
+            check_arity(1, 2, 0),
+            Err(EvaluateError::NotEnoughArgs {
+                expected_num: 1,
+                found: 0
+            })
+        ));
+        assert!(matches!(
+            check_arity(1, 2, 3),
+            Err(EvaluateError::TooMuchArgs {
+                expected_num: 2,
+                found: 3
+            })
+        ));
+    }
+
+    #[test]
+    fn native_functions_extract_their_arguments() {
+        let text = || AspenValue::Str("ab".to_owned());
+
+        assert_eq!(
+            call(vec![text()]).unwrap(),
+            AspenValue::Str("abab".to_owned())
+        );
+        assert_eq!(
+            call(vec![text(), AspenValue::Nil]).unwrap(),
+            AspenValue::Str("abab".to_owned())
+        );
+        assert_eq!(
+            call(vec![text(), AspenValue::Int(Integer::from(3))]).unwrap(),
+            AspenValue::Str("ababab".to_owned())
+        );
+        assert!(matches!(
+            call(vec![]),
+            Err(EvaluateError::NotEnoughArgs { .. })
+        ));
+        assert_eq!(
+            call(vec![AspenValue::Bool(true)]).unwrap_err().to_string(),
+            "Invalid type: expected type 'String' found type 'Bool'"
+        );
+    }
+
+    #[test]
+    fn arrays_are_extracted_element_by_element() {
+        let values = AspenValue::Array(vec![AspenValue::Bool(true), AspenValue::Bool(false)]);
+        assert_eq!(
+            Vec::<bool>::from_aspen_value(values).unwrap(),
+            [true, false]
+        );
+
+        let mixed = AspenValue::Array(vec![AspenValue::Bool(true), AspenValue::Nil]);
+        assert!(Vec::<bool>::from_aspen_value(mixed).is_err());
+    }
+
+    #[test]
+    fn failures_become_error_values() {
+        let failed: Result<bool, String> = Err("no such file".to_owned());
+
+        match failed.into_aspen_value() {
+            AspenValue::Variant(variant) => {
+                assert_eq!(
+                    variant.property("message"),
+                    Some("no such file".to_owned().into())
+                )
+            }
+            value => panic!("expected an error value, got {}", value),
+        }
+        assert_eq!(None::<bool>.into_aspen_value(), AspenValue::Nil);
+    }
+}
@@ -0,0 +1,131 @@
+use super::{
+    binary::are_equal,
+    enums::AspenVariant,
+    range::AspenRange,
+    types::AspenType,
+    value::{AspenObject, AspenValue},
+    EvaluateResult,
+};
+use crate::parser::match_expr::Pattern;
+
+/// Returns true if `value` matches `pattern`, the values bound by the pattern are added to `bindings`.
+pub fn match_pattern<'a>(
+    pattern: &Pattern<'a>,
+    value: &AspenValue<'a>,
+    bindings: &mut Vec<(&'a str, AspenValue<'a>)>,
+) -> EvaluateResult<bool> {
+    let is_match = match (pattern, value) {
+        (Pattern::Wildcard, _) => true,
+        (Pattern::Binding(name), value) => {
+            bindings.push((name, value.to_owned()));
+            true
+        }
+        (Pattern::Literal(literal), value) => are_equal(&literal.to_owned().into(), value),
+        (
+            Pattern::Range {
+                start,
+                end,
+                inclusive,
+            },
+            value,
+        ) => AspenRange::new(
+            start.to_owned().into(),
+            end.to_owned().into(),
+            None,
+            *inclusive,
+        )?
+        .contains(value),
+        (Pattern::Array { elements, rest }, AspenValue::Array(values)) => {
+            let has_valid_len = match rest {
+                Some(_) => values.len() >= elements.len(),
+                None => values.len() == elements.len(),
+            };
+
+            if !has_valid_len {
+                return Ok(false);
+            }
+
+            for (element, value) in elements.iter().zip(values) {
+                if !match_pattern(element, value, bindings)? {
+                    return Ok(false);
+                }
+            }
+
+            if let Some(rest) = rest {
+                let rest_values = values[elements.len()..].to_vec();
+                bindings.push((rest, AspenValue::Array(rest_values)));
+            }
+
+            true
+        }
+        (Pattern::Object(properties), AspenValue::Object(object)) => {
+            match_properties(properties, object, bindings)?
+        }
+        (Pattern::Object(properties), AspenValue::Record(record)) => {
+            match_properties(properties, &record.fields, bindings)?
+        }
+        // an error is matched by its kind, message, payload and cause
+        (
+            Pattern::Object(properties),
+            AspenValue::Variant(AspenVariant {
+                error: Some(err), ..
+            }),
+        ) => match_properties(properties, &err.properties(), bindings)?,
+        (
+            Pattern::Variant {
+                enum_name,
+                name,
+                payload,
+            },
+            AspenValue::Variant(variant),
+        ) => {
+            enum_name.is_none_or(|enum_name| enum_name == variant.enum_name)
+                && *name == variant.name
+                && match payload {
+                    Some(patterns) => match_payload(patterns, &variant.payload, bindings)?,
+                    None => true,
+                }
+        }
+        (Pattern::Type(name), value) => match AspenType::from_name(name) {
+            Some(expected) => expected.includes(&value.to_owned().into()),
+            // the name of a record type declared with '$type'
+            None => matches!(value, AspenValue::Record(record) if record.name == *name),
+        },
+        _ => false,
+    };
+
+    Ok(is_match)
+}
+
+fn match_payload<'a>(
+    patterns: &[Pattern<'a>],
+    payload: &[AspenValue<'a>],
+    bindings: &mut Vec<(&'a str, AspenValue<'a>)>,
+) -> EvaluateResult<bool> {
+    if patterns.len() != payload.len() {
+        return Ok(false);
+    }
+
+    for (pattern, value) in patterns.iter().zip(payload) {
+        if !match_pattern(pattern, value, bindings)? {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+fn match_properties<'a>(
+    properties: &[(&'a str, Pattern<'a>)],
+    object: &AspenObject<'a>,
+    bindings: &mut Vec<(&'a str, AspenValue<'a>)>,
+) -> EvaluateResult<bool> {
+    for (key, pattern) in properties {
+        match object.get(*key) {
+            Some(value) if match_pattern(pattern, value, bindings)? => (),
+            _ => return Ok(false),
+        }
+    }
+
+    Ok(true)
+}
@@ -0,0 +1,462 @@
+use super::{error::EvaluateError, types::AspenType, value::AspenValue, EvaluateResult};
+use rug::{float::OrdFloat, ops::DivRounding, Float, Integer};
+use std::{fmt, marker::PhantomData};
+
+/// A lazy range: `start:end`, `start:end:step`, or `start:<end:step` to exclude the end.
+///
+/// Its values are computed one at a time when it is iterated,
+/// its length and whether it contains a value are computed without iterating it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AspenRange {
+    Int {
+        start: Integer,
+        end: Integer,
+        step: Integer,
+        inclusive: bool,
+    },
+    Float {
+        start: Float,
+        end: Float,
+        step: Float,
+        inclusive: bool,
+    },
+    /// A range of chars, e.g `'a':'z'`, stepping through their code points.
+    Char {
+        start: char,
+        end: char,
+        step: Integer,
+        inclusive: bool,
+    },
+}
+
+impl AspenRange {
+    /// Creates a range from its evaluated parts.
+    ///
+    /// Without a step, the range counts up, or down when `end` is less than `start`.
+    /// A step going away from the end gives an empty range.
+    pub fn new<'a>(
+        start: AspenValue<'a>,
+        end: AspenValue<'a>,
+        step: Option<AspenValue<'a>>,
+        inclusive: bool,
+    ) -> EvaluateResult<Self> {
+        let range = match (start, end, step) {
+            (
+                AspenValue::Int(start),
+                AspenValue::Int(end),
+                step @ (None | Some(AspenValue::Int(_))),
+            ) => {
+                let step = match step {
+                    Some(AspenValue::Int(step)) => step,
+                    _ => Integer::from(if end < start { -1 } else { 1 }),
+                };
+
+                AspenRange::Int {
+                    start,
+                    end,
+                    step,
+                    inclusive,
+                }
+            }
+            (
+                start @ (AspenValue::Int(_) | AspenValue::Float(_)),
+                end @ (AspenValue::Int(_) | AspenValue::Float(_)),
+                step @ (None | Some(AspenValue::Int(_) | AspenValue::Float(_))),
+            ) => {
+                // like arithmetic operations, the values have the precision of the most precise float
+                let precision = [Some(&start), Some(&end), step.as_ref()]
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|value| match value {
+                        AspenValue::Float(f) => Some(f.as_float().prec()),
+                        _ => None,
+                    })
+                    .max()
+                    .unwrap(/* at least one part is a float */);
+
+                let start = to_float(start, precision);
+                let end = to_float(end, precision);
+                let step = match step {
+                    Some(step) => to_float(step, precision),
+                    None => Float::with_val(precision, if end < start { -1 } else { 1 }),
+                };
+
+                if !start.is_finite() || !end.is_finite() || !step.is_finite() {
+                    return Err(EvaluateError::Custom(
+                        "A range cannot be created from an infinite or NaN number".to_owned(),
+                    ));
+                }
+
+                AspenRange::Float {
+                    start,
+                    end,
+                    step,
+                    inclusive,
+                }
+            }
+            (
+                AspenValue::Str(start),
+                AspenValue::Str(end),
+                step @ (None | Some(AspenValue::Int(_))),
+            ) => {
+                let (start, end) = match (single_char(&start), single_char(&end)) {
+                    (Some(start), Some(end)) => (start, end),
+                    _ => {
+                        return Err(EvaluateError::Custom(format!(
+                            "A range can only be generated from two numbers or two characters, \"{}\" and \"{}\" must be one character long",
+                            start, end
+                        )))
+                    }
+                };
+
+                // code points between the two chars that are not chars
+                let (low, high) = (start.min(end) as u32, start.max(end) as u32);
+                if low < 0xD800 && high > 0xDFFF {
+                    return Err(EvaluateError::Custom(format!(
+                        "A range of characters cannot go from '{}' to '{}', it contains surrogate code points",
+                        start, end
+                    )));
+                }
+
+                let step = match step {
+                    Some(AspenValue::Int(step)) => step,
+                    _ => Integer::from(if end < start { -1 } else { 1 }),
+                };
+
+                AspenRange::Char {
+                    start,
+                    end,
+                    step,
+                    inclusive,
+                }
+            }
+            (start, end, Some(step)) if is_bound(&start) && is_bound(&end) => {
+                return Err(EvaluateError::InvalidType {
+                    expected: AspenType::Number,
+                    found: step.into(),
+                })
+            }
+            (a, b, _) => {
+                return Err(EvaluateError::Custom(format!(
+                    "A range cannot be created from type '{}' and type '{}'",
+                    AspenType::from(a),
+                    AspenType::from(b)
+                )))
+            }
+        };
+
+        if range.step_is_zero() {
+            return Err(EvaluateError::Custom(
+                "Step of a range cannot be zero".to_owned(),
+            ));
+        }
+
+        Ok(range)
+    }
+
+    fn step_is_zero(&self) -> bool {
+        match self {
+            AspenRange::Int { step, .. } | AspenRange::Char { step, .. } => step.is_zero(),
+            AspenRange::Float { step, .. } => step.is_zero(),
+        }
+    }
+
+    /// Number of values of the range.
+    pub fn len(&self) -> Integer {
+        match self {
+            AspenRange::Int {
+                start,
+                end,
+                step,
+                inclusive,
+            } => int_len(start, end, step, *inclusive),
+            AspenRange::Char {
+                start,
+                end,
+                step,
+                inclusive,
+            } => int_len(
+                &Integer::from(*start as u32),
+                &Integer::from(*end as u32),
+                step,
+                *inclusive,
+            ),
+            AspenRange::Float {
+                start,
+                end,
+                step,
+                inclusive,
+            } => {
+                // number of steps needed to reach the end
+                let steps = Float::with_val(start.prec(), end - start) / step;
+
+                let tolerance = float_tolerance(&steps);
+
+                let len = if *inclusive {
+                    (steps + tolerance).floor() + 1u32
+                } else {
+                    (steps - tolerance).ceil()
+                };
+
+                len.to_integer()
+                    .map(|len| len.max(Integer::new()))
+                    .unwrap_or_default()
+            }
+        }
+    }
+
+    /// Returns the value at `index`, or `None` if the range does not have that many values.
+    pub fn get<'a>(&self, index: &Integer) -> Option<AspenValue<'a>> {
+        if *index < 0 || *index >= self.len() {
+            return None;
+        }
+
+        let value = match self {
+            AspenRange::Int { start, step, .. } => {
+                AspenValue::Int(start + Integer::from(index * step))
+            }
+            AspenRange::Float { start, step, .. } => {
+                let offset = Float::with_val(step.prec(), step * index);
+                AspenValue::Float(OrdFloat::from(Float::with_val(
+                    start.prec(),
+                    start + offset,
+                )))
+            }
+            AspenRange::Char { start, step, .. } => {
+                let code_point = Integer::from(*start as u32) + Integer::from(index * step);
+                // the range never contains surrogates, checked when creating it
+                let c = code_point.to_u32().and_then(char::from_u32).unwrap();
+                AspenValue::Str(c.to_string())
+            }
+        };
+
+        Some(value)
+    }
+
+    /// Returns true if `value` is one of the values of the range.
+    pub fn contains(&self, value: &AspenValue<'_>) -> bool {
+        let len = self.len();
+
+        match (self, value) {
+            (AspenRange::Int { start, step, .. }, AspenValue::Int(i)) => {
+                int_index(start, step, i).is_some_and(|index| index < len)
+            }
+            (AspenRange::Int { start, step, .. }, AspenValue::Float(f)) => f
+                .as_float()
+                .to_integer()
+                .filter(|i| f.as_float() == i)
+                .and_then(|i| int_index(start, step, &i))
+                .is_some_and(|index| index < len),
+            (AspenRange::Float { start, step, .. }, AspenValue::Int(_) | AspenValue::Float(_)) => {
+                let value = to_float(value.to_owned(), start.prec());
+                let steps = Float::with_val(start.prec(), value - start) / step;
+                let index = Float::with_val(steps.prec(), steps.round_ref());
+
+                let tolerance = float_tolerance(&steps);
+
+                (steps - &index).abs() <= tolerance
+                    && index
+                        .to_integer()
+                        .is_some_and(|index| index >= 0 && index < len)
+            }
+            (AspenRange::Char { start, step, .. }, AspenValue::Str(s)) => match single_char(s) {
+                Some(c) => int_index(
+                    &Integer::from(*start as u32),
+                    step,
+                    &Integer::from(c as u32),
+                )
+                .is_some_and(|index| index < len),
+                None => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Iterates over the values of the range, computing them one at a time.
+    pub fn iter<'a>(&self) -> RangeIter<'a> {
+        RangeIter {
+            len: self.len(),
+            range: self.to_owned(),
+            index: Integer::new(),
+            marker: PhantomData,
+        }
+    }
+
+    fn inclusive(&self) -> bool {
+        match self {
+            AspenRange::Int { inclusive, .. }
+            | AspenRange::Float { inclusive, .. }
+            | AspenRange::Char { inclusive, .. } => *inclusive,
+        }
+    }
+}
+
+/// Iterator over the values of an [`AspenRange`].
+pub struct RangeIter<'a> {
+    range: AspenRange,
+    index: Integer,
+    len: Integer,
+    marker: PhantomData<AspenValue<'a>>,
+}
+
+impl<'a> Iterator for RangeIter<'a> {
+    type Item = AspenValue<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.len {
+            return None;
+        }
+
+        let value = self.range.get(&self.index);
+        self.index += 1;
+        value
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = Integer::from(&self.len - &self.index);
+        match remaining.to_usize() {
+            Some(remaining) => (remaining, Some(remaining)),
+            None => (usize::MAX, None),
+        }
+    }
+}
+
+/// Number of values from `start` to `end` going by `step`.
+fn int_len(start: &Integer, end: &Integer, step: &Integer, inclusive: bool) -> Integer {
+    // distance to the end in the direction of the step
+    let mut distance = Integer::from(end - start);
+    if *step < 0 {
+        distance = -distance;
+    }
+    let step = Integer::from(step.abs_ref());
+
+    match inclusive {
+        true if distance >= 0 => distance.div_floor(step) + 1u32,
+        false if distance > 0 => (distance - 1u32).div_floor(step) + 1u32,
+        _ => Integer::new(),
+    }
+}
+
+/// Returns the index `value` would have in a range starting at `start` and going by `step`,
+/// or `None` if the range never reaches it.
+fn int_index(start: &Integer, step: &Integer, value: &Integer) -> Option<Integer> {
+    let (index, remainder) = Integer::from(value - start).div_rem_floor(step.to_owned());
+
+    (remainder == 0 && index >= 0).then_some(index)
+}
+
+/// Rounding errors allowed on a number of steps, so that `0:0.3:0.1` includes `0.3`
+/// even though `0.3 / 0.1` is slightly less than 3.
+fn float_tolerance(steps: &Float) -> Float {
+    let prec = steps.prec();
+    let magnitude = Float::with_val(prec, steps.abs_ref()).max(&Float::with_val(prec, 1));
+
+    // the last bits of the precision are considered as noise
+    magnitude >> (prec.saturating_sub(4))
+}
+
+fn to_float(value: AspenValue<'_>, precision: u32) -> Float {
+    match value {
+        AspenValue::Int(i) => Float::with_val(precision, i),
+        AspenValue::Float(f) => Float::with_val(precision, f.as_float()),
+        _ => unreachable!("only called on numbers"),
+    }
+}
+
+fn single_char(s: &str) -> Option<char> {
+    let mut chars = s.chars();
+
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+fn is_bound(value: &AspenValue<'_>) -> bool {
+    matches!(
+        value,
+        AspenValue::Int(_) | AspenValue::Float(_) | AspenValue::Str(_)
+    )
+}
+
+impl fmt::Display for AspenRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let separator = if self.inclusive() { ":" } else { ":<" };
+
+        // the step is only displayed when it is not the default one
+        match self {
+            AspenRange::Int {
+                start, end, step, ..
+            } => {
+                write!(f, "{}{}{}", start, separator, end)?;
+                if *step != if end < start { -1 } else { 1 } {
+                    write!(f, ":{}", step)?;
+                }
+            }
+            AspenRange::Float {
+                start, end, step, ..
+            } => {
+                write!(f, "{}{}{}", start, separator, end)?;
+                if *step != if end < start { -1 } else { 1 } {
+                    write!(f, ":{}", step)?;
+                }
+            }
+            AspenRange::Char {
+                start, end, step, ..
+            } => {
+                write!(f, "'{}'{}'{}'", start, separator, end)?;
+                if *step != if end < start { -1 } else { 1 } {
+                    write!(f, ":{}", step)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{error_of, value_of};
+
+    fn values_of(range: &str) -> String {
+        let code = format!(
+            "let values []\nfor value in {} {{\n    values.push(value)\n}}\n",
+            range
+        );
+        value_of(&code, "values")
+    }
+
+    #[test]
+    fn ranges_are_iterated_lazily_with_steps() {
+        assert_eq!(values_of("1:4"), "[1, 2, 3, 4]");
+        assert_eq!(values_of("0:<10:3"), "[0, 3, 6, 9]");
+        assert_eq!(values_of("10:0:-5"), "[10, 5, 0]");
+        assert_eq!(values_of("3:1"), "[3, 2, 1]");
+        assert_eq!(values_of("0:5:-1"), "[]");
+        assert_eq!(values_of("'a':'e':2"), "[a, c, e]");
+        assert_eq!(values_of("0:1:0.25").matches(',').count(), 4);
+    }
+
+    #[test]
+    fn big_ranges_are_never_materialised() {
+        let code = r#"
+let bound 10 ** 30
+let range 0:bound
+let len range.len()
+let contains range.contains(10 ** 29)
+let excluded (0:<bound).contains(bound)
+"#;
+
+        assert_eq!(value_of(code, "len"), "1000000000000000000000000000001");
+        assert_eq!(value_of(code, "contains"), "true");
+        assert_eq!(value_of(code, "excluded"), "false");
+    }
+
+    #[test]
+    fn invalid_ranges() {
+        assert!(error_of("let r 0:10:0").contains("Step of a range cannot be zero"));
+        assert!(error_of("let r 'ab':'c'").contains("must be one character long"));
+        assert!(error_of("let r 1:'c'").contains("cannot be created from type 'Int'"));
+    }
+}
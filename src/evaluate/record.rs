@@ -0,0 +1,143 @@
+use super::{
+    error::EvaluateError,
+    value::{AspenObject, AspenValue},
+    EvaluateResult,
+};
+use std::fmt;
+
+/// A type declared with `$type`, calling it creates a record of the type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordType<'a> {
+    pub name: &'a str,
+    pub fields: Vec<&'a str>,
+    /// Values of the fields that can be left out, evaluated with the declaration.
+    pub defaults: AspenObject<'a>,
+}
+
+/// A value of a record type, its fields are kept in the order of the declaration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AspenRecord<'a> {
+    pub name: &'a str,
+    pub fields: AspenObject<'a>,
+}
+
+impl<'a> RecordType<'a> {
+    /// Creates a record from the arguments of the constructor, an object having a value for each field.
+    pub fn construct(&self, args: Vec<AspenValue<'a>>) -> EvaluateResult<AspenRecord<'a>> {
+        if args.len() > 1 {
+            return Err(EvaluateError::TooMuchArgs {
+                expected_num: 1,
+                found: args.len(),
+            });
+        }
+
+        let mut values = match args.into_iter().next() {
+            Some(AspenValue::Object(values)) => values,
+            Some(_) => {
+                return Err(EvaluateError::Custom(format!(
+                    "The fields of '{}' must be given in an object, e.g {}({{{}! value}})",
+                    self.name,
+                    self.name,
+                    self.fields.first().unwrap_or(&"field")
+                )))
+            }
+            None => AspenObject::new(),
+        };
+
+        if let Some(field) = values
+            .keys()
+            .find(|key| !self.fields.contains(&key.as_str()))
+        {
+            return Err(EvaluateError::UnknownField {
+                record: self.name.to_owned(),
+                field: field.to_owned(),
+            });
+        }
+
+        let fields = self
+            .fields
+            .iter()
+            .map(|field| match values.swap_remove(*field) {
+                Some(value) => Ok((field.to_string(), value)),
+                None if self.defaults.contains_key(*field) => {
+                    Ok((field.to_string(), self.defaults[*field].to_owned()))
+                }
+                None => Err(EvaluateError::MissingField {
+                    record: self.name.to_owned(),
+                    field: field.to_string(),
+                }),
+            })
+            .collect::<EvaluateResult<_>>()?;
+
+        Ok(AspenRecord {
+            name: self.name,
+            fields,
+        })
+    }
+}
+
+impl<'a> fmt::Display for AspenRecord<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}",
+            self.name,
+            AspenValue::Object(self.fields.to_owned())
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{error_of, value_of};
+
+    const POINT: &str = "$type Point { x!, y!, z! 0 }\n";
+
+    fn run_with_point(code: &str, name: &str) -> String {
+        value_of(&format!("{}{}", POINT, code), name)
+    }
+
+    fn error_with_point(code: &str) -> String {
+        error_of(&format!("{}{}", POINT, code))
+    }
+
+    #[test]
+    fn records_are_created_with_their_fields_in_order() {
+        let code = r#"
+let p Point({y! 2, x! 1})
+let x p.x
+let same p == Point({x! 1, y! 2, z! 0})
+let kind type_of(p)
+let {y!} p
+"#;
+
+        assert_eq!(run_with_point(code, "p"), "Point {x! 1, y! 2, z! 0}");
+        assert_eq!(run_with_point(code, "x"), "1");
+        assert_eq!(run_with_point(code, "same"), "true");
+        assert_eq!(run_with_point(code, "kind"), "Point");
+        assert_eq!(run_with_point(code, "y"), "2");
+    }
+
+    #[test]
+    fn records_are_matched_by_type() {
+        let code = r#"
+$type Person { name! }
+let kind match Point({x! 1, y! 2}) {
+    Person => "person",
+    Point => "point",
+    _ => "other"
+}
+"#;
+
+        assert_eq!(run_with_point(code, "kind"), "point");
+    }
+
+    #[test]
+    fn invalid_records() {
+        assert!(error_with_point("let p Point({x! 1})")
+            .contains("Missing field 'y' when creating a 'Point'"));
+        assert!(error_with_point("let p Point({x! 1, y! 2, w! 3})")
+            .contains("Type 'Point' does not have a 'w' field"));
+        assert!(error_with_point("let p Point(1, 2)").contains("Expected at most 1, found 2"));
+    }
+}
@@ -11,16 +11,58 @@ pub enum AspenType {
     Object,
 
     Range,
+    Iterator,
+    Done,
 
     Func,
     Nil,
 
     Err,
+
+    /// A record of the type declared with this name.
+    Record(String),
+    /// A variant of the enum declared with this name.
+    Enum(String),
 }
 
 use std::fmt;
 
-use super::AspenValue;
+use super::{enums::RESULT, AspenValue};
+
+impl AspenType {
+    /// Returns the type written `name` in the code, e.g `Int` in a pattern.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let aspen_type = match name {
+            "Int" => AspenType::Int,
+            "Float" => AspenType::Float,
+            "Number" => AspenType::Number,
+            "Bool" => AspenType::Bool,
+            "String" => AspenType::String,
+            "Array" => AspenType::Array,
+            "Object" => AspenType::Object,
+            "Range" => AspenType::Range,
+            "Iterator" => AspenType::Iterator,
+            "Done" => AspenType::Done,
+            "Func" => AspenType::Func,
+            "Nil" => AspenType::Nil,
+            "Err" => AspenType::Err,
+            RESULT => AspenType::Enum(RESULT.to_owned()),
+            _ => return None,
+        };
+
+        Some(aspen_type)
+    }
+
+    /// Returns true if a value of type `other` is of this type, `Number` includes both integers and floats,
+    /// `Result` includes errors.
+    pub fn includes(&self, other: &AspenType) -> bool {
+        match self {
+            AspenType::Number => matches!(other, AspenType::Int | AspenType::Float),
+            AspenType::Enum(name) if name == RESULT => self == other || other == &AspenType::Err,
+            _ => self == other,
+        }
+    }
+}
 
 impl fmt::Display for AspenType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -33,10 +75,13 @@ impl fmt::Display for AspenType {
             AspenType::Array => write!(f, "Array"),
             AspenType::Object => write!(f, "Object"),
             AspenType::Range => write!(f, "Range"),
+            AspenType::Iterator => write!(f, "Iterator"),
+            AspenType::Done => write!(f, "Done"),
             AspenType::Func => write!(f, "Func"),
             AspenType::Nil => write!(f, "Nil"),
             AspenType::Func => write!(f, "Func"),
             AspenType::Err => write!(f, "Err"),
+            AspenType::Record(name) | AspenType::Enum(name) => write!(f, "{}", name),
         }
     }
 }
@@ -51,10 +96,15 @@ impl<'a> From<AspenValue<'a>> for AspenType {
             AspenValue::Float(_) => AspenType::Float,
             AspenValue::Array(_) => AspenType::Array,
             AspenValue::Object(_) => AspenType::Object,
-            AspenValue::Range { .. } => AspenType::Range,
+            AspenValue::Range(_) => AspenType::Range,
+            AspenValue::Iterator(_) => AspenType::Iterator,
+            AspenValue::Done => AspenType::Done,
             AspenValue::Func(_) => AspenType::Func,
-            AspenValue::RustBindFn { .. } => AspenType::Func,
-            AspenValue::Error(_) => AspenType::Err,
+            AspenValue::RustBindFn { .. } | AspenValue::RecordType(_) => AspenType::Func,
+            AspenValue::Record(record) => AspenType::Record(record.name.to_owned()),
+            AspenValue::Variant(variant) if variant.error.is_some() => AspenType::Err,
+            AspenValue::Variant(variant) => AspenType::Enum(variant.enum_name.to_owned()),
+            AspenValue::EnumType(_) => AspenType::Object,
         }
     }
 }
@@ -69,10 +119,15 @@ impl<'a, T: AsRef<AspenValue<'a>>> From<T> for AspenType {
             AspenValue::Float(_) => AspenType::Float,
             AspenValue::Array(_) => AspenType::Array,
             AspenValue::Object(_) => AspenType::Object,
-            AspenValue::Range { .. } => AspenType::Range,
+            AspenValue::Range(_) => AspenType::Range,
+            AspenValue::Iterator(_) => AspenType::Iterator,
+            AspenValue::Done => AspenType::Done,
             AspenValue::Func(_) => AspenType::Func,
-            AspenValue::RustBindFn { .. } => AspenType::Func,
-            AspenValue::Error(_) => AspenType::Err,
+            AspenValue::RustBindFn { .. } | AspenValue::RecordType(_) => AspenType::Func,
+            AspenValue::Record(record) => AspenType::Record(record.name.to_owned()),
+            AspenValue::Variant(variant) if variant.error.is_some() => AspenType::Err,
+            AspenValue::Variant(variant) => AspenType::Enum(variant.enum_name.to_owned()),
+            AspenValue::EnumType(_) => AspenType::Object,
         }
     }
 }
@@ -1,96 +1,196 @@
-use super::{error::EvaluateError, value::AspenValue, EvaluateResult};
+use super::{
+    error::EvaluateError,
+    range::AspenRange,
+    types::AspenType,
+    value::{AspenObject, AspenValue},
+    EvaluateResult,
+};
+use crate::parser::string::{Alignment, FormatSpec};
+use rug::{Float, Integer};
 
-pub fn extract_range<'a>(value: AspenValue<'a>) -> EvaluateResult<(usize, usize, Option<usize>)> {
+/// The property an object delegates the properties it does not have to.
+pub const PROTO: &str = "proto";
+
+/// Returns a property of an object, a property it does not have is looked up in `obj.proto`, then in its own proto...
+pub fn find_property<'o, 'a>(obj: &'o AspenObject<'a>, name: &str) -> Option<&'o AspenValue<'a>> {
+    let mut obj = obj;
+
+    loop {
+        if let Some(value) = obj.get(name) {
+            return Some(value);
+        }
+
+        match obj.get(PROTO) {
+            Some(AspenValue::Object(proto)) => obj = proto,
+            _ => return None,
+        }
+    }
+}
+
+/// Returns the property `name` of an object or of a record if it is a function,
+/// it is called instead of the builtin method with the same name, e.g `object.keys(o)` on the `object` module.
+pub fn find_callable_property<'v, 'a>(
+    value: &'v AspenValue<'a>,
+    name: &str,
+) -> Option<&'v AspenValue<'a>> {
+    let property = match value {
+        AspenValue::Object(obj) => find_property(obj, name)?,
+        AspenValue::Record(record) => record.fields.get(name)?,
+        _ => return None,
+    };
+
+    match property {
+        AspenValue::Func(_) | AspenValue::RustBindFn { .. } | AspenValue::RecordType(_) => {
+            Some(property)
+        }
+        _ => None,
+    }
+}
+
+/// Returns the properties of an object, or the fields of a record if `property` is one of them, to assign `property`.
+pub fn properties_mut<'v, 'a>(
+    value: &'v mut AspenValue<'a>,
+    property: &str,
+) -> EvaluateResult<&'v mut AspenObject<'a>> {
     match value {
-        AspenValue::Range { start, end, step } => {
-            let start = match *start {
-                AspenValue::Int(i) => i,
-                _ => {
-                    return Err(EvaluateError::Custom(
-                        "Start of the range must be an integer".to_string(),
-                    ))
-                }
-            };
-
-            let end = match *end {
-                AspenValue::Int(i) => i,
-                _ => {
-                    return Err(EvaluateError::Custom(
-                        "End of the range must be an integer".to_string(),
-                    ))
-                }
-            };
-
-            let step = match step {
-                Some(val) => match *val {
-                    AspenValue::Int(i) => {
-                        if i == 1 {
-                            None
-                        } else {
-                            Some(i)
-                        }
-                    }
-                    _ => {
-                        return Err(EvaluateError::Custom(
-                            "Step of the range must be an integer".to_string(),
-                        ))
-                    }
-                },
-                None => None,
-            };
-
-            if start >= end {
-                return Err(EvaluateError::Custom(
-                    "Start of the range must be less than end of the range".to_string(),
-                ));
-            }
-            if end > usize::MAX {
-                return Err(EvaluateError::Custom(format!(
-                    "A range end index cannot exceed {}!!",
-                    usize::MAX
-                )));
-            }
-            if start < usize::MIN {
-                return Err(EvaluateError::Custom(format!(
-                    "A range end index cannot be less than {}!!",
-                    usize::MIN
-                )));
-            }
-
-            if let Some(step) = &step {
-                if step < &usize::MIN {
-                    return Err(EvaluateError::Custom(format!(
-                        "A range step cannot be less than {}!!",
-                        usize::MIN
-                    )));
-                }
-                if step > &usize::MAX {
-                    return Err(EvaluateError::Custom(format!(
-                        "A range step cannot be more than {}!!",
-                        usize::MAX
-                    )));
-                }
-            }
-
-            let standart_deviation = end.to_owned() - start.to_owned();
-
-            if standart_deviation > 1_000_000 {
-                return Err(EvaluateError::Custom(
-                    "A range start and end index cannot have a gap of more than 1_000_000!!"
-                        .to_string(),
-                ));
-            }
-
-            let start = start.to_usize().unwrap();
-            let end = end.to_usize().unwrap();
-            let step = step.map(|i| i.to_usize().unwrap());
-
-            Ok((start, end, step))
+        AspenValue::Object(obj) => Ok(obj),
+        AspenValue::Record(record) => match record.fields.contains_key(property) {
+            true => Ok(&mut record.fields),
+            false => Err(EvaluateError::UnknownField {
+                record: record.name.to_owned(),
+                field: property.to_owned(),
+            }),
+        },
+        x => Err(EvaluateError::InvalidType {
+            expected: AspenType::Object,
+            found: x.to_owned().into(),
+        }),
+    }
+}
+
+/// Formats a value embedded in an interpolated string according to its format spec.
+pub fn format_value(value: &AspenValue<'_>, spec: &FormatSpec) -> EvaluateResult<String> {
+    let text = match (value, spec.precision) {
+        (AspenValue::Float(f), Some(precision)) => {
+            format!("{:.*}", precision, Float::from(f.to_owned()).to_f64())
+        }
+        (AspenValue::Int(i), Some(precision)) => format!("{:.*}", precision, i.to_f64()),
+        (x, Some(_)) => {
+            return Err(EvaluateError::InvalidType {
+                expected: AspenType::Number,
+                found: x.to_owned().into(),
+            })
         }
-        _ => {
-            return Err(EvaluateError::Custom(
-                "The argument is expected to be of type range".to_string(),
+        (x, None) => x.to_string(),
+    };
+
+    let length = text.chars().count();
+    let padding = match spec.width {
+        Some(width) if width > length => width - length,
+        _ => return Ok(text),
+    };
+
+    let is_number = matches!(value, AspenValue::Int(_) | AspenValue::Float(_));
+
+    if spec.zero && spec.align.is_none() && is_number {
+        let (sign, digits) = text.split_at(usize::from(text.starts_with('-')));
+        return Ok(format!("{}{}{}", sign, "0".repeat(padding), digits));
+    }
+
+    // numbers are aligned to the right by default, like in a table
+    let default_align = if is_number {
+        Alignment::Right
+    } else {
+        Alignment::Left
+    };
+
+    let (left, right) = match spec.align.unwrap_or(default_align) {
+        Alignment::Left => (0, padding),
+        Alignment::Right => (padding, 0),
+        Alignment::Center => (padding / 2, padding - padding / 2),
+    };
+
+    let fill = spec.fill.to_string();
+    Ok(format!(
+        "{}{}{}",
+        fill.repeat(left),
+        text,
+        fill.repeat(right)
+    ))
+}
+
+/// Evaluates `indexed[indexer]`: an integer gives an element of an array or a char of a string,
+/// a range gives a slice of them.
+///
+/// Strings are indexed by chars and not by bytes, a slice can never split a char.
+pub fn index_value<'a>(
+    indexed: AspenValue<'a>,
+    indexer: AspenValue<'a>,
+) -> EvaluateResult<AspenValue<'a>> {
+    match (indexed, indexer) {
+        (AspenValue::Array(values), AspenValue::Int(i)) => {
+            let i = to_index(i, values.len())?;
+            Ok(values.into_iter().nth(i).unwrap())
+        }
+        (AspenValue::Str(s), AspenValue::Int(i)) => {
+            let i = to_index(i, s.chars().count())?;
+            Ok(AspenValue::Str(s.chars().nth(i).unwrap().to_string()))
+        }
+        (AspenValue::Array(values), AspenValue::Range(range)) => {
+            let indices = slice_indices(range, values.len())?;
+            Ok(AspenValue::Array(
+                indices.into_iter().map(|i| values[i].clone()).collect(),
             ))
         }
+        (AspenValue::Str(s), AspenValue::Range(range)) => {
+            let chars: Vec<char> = s.chars().collect();
+            let indices = slice_indices(range, chars.len())?;
+            Ok(AspenValue::Str(
+                indices.into_iter().map(|i| chars[i]).collect(),
+            ))
+        }
+        (AspenValue::Object(mut values), AspenValue::Str(key)) => match values.swap_remove(&key) {
+            Some(value) => Ok(value),
+            None => Err(EvaluateError::UnknownProperty(key)),
+        },
+        (AspenValue::Object(_), x) => Err(EvaluateError::InvalidType {
+            expected: AspenType::String,
+            found: x.into(),
+        }),
+        (AspenValue::Array(_) | AspenValue::Str(_), x) => Err(EvaluateError::InvalidType {
+            expected: AspenType::Int,
+            found: x.into(),
+        }),
+        (x, _) => Err(EvaluateError::InvalidType {
+            expected: AspenType::Array,
+            found: x.into(),
+        }),
+    }
+}
+
+fn to_index(i: Integer, len: usize) -> EvaluateResult<usize> {
+    match i.to_usize() {
+        Some(index) if index < len => Ok(index),
+        _ => Err(EvaluateError::IndexOutOfBounds { index: i, len }),
     }
 }
+
+/// Returns the indices of the elements of a slice, in the order of the range.
+///
+/// A range with a negative step gives the elements in reverse order, e.g `arr[4:0:-1]`.
+fn slice_indices(range: AspenRange, len: usize) -> EvaluateResult<Vec<usize>> {
+    if !matches!(range, AspenRange::Int { .. }) {
+        return Err(EvaluateError::Custom(
+            "Only a range of integers can be used to slice an array or a string".to_owned(),
+        ));
+    }
+
+    // the values of a range never repeat, an index out of bounds is always reached after at most `len` values
+    range
+        .iter()
+        .map(|value| match value {
+            AspenValue::Int(i) => to_index(i, len),
+            _ => unreachable!("range of integers"),
+        })
+        .collect()
+}
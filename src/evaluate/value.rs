@@ -1,34 +1,78 @@
-use super::{func::AspenFn, EvaluateResult};
-use hashbrown::HashMap;
+use super::{
+    enums::{AspenVariant, EnumType},
+    err::AspenErr,
+    func::AspenFn,
+    iterator::AspenIterator,
+    native::NativeFn,
+    range::AspenRange,
+    record::{AspenRecord, RecordType},
+};
+use indexmap::IndexMap;
 use rug::{float::OrdFloat, Float, Integer};
 use std::fmt;
 
-#[derive(Debug, Clone, PartialEq)]
+/// Properties of an object, kept in insertion order.
+pub type AspenObject<'a> = IndexMap<String, AspenValue<'a>>;
+
+#[derive(Debug, Clone)]
 pub enum AspenValue<'a> {
     Nil,
     Str(String),
     Bool(bool),
 
-    Error(String),
-
     Int(Integer),
     Float(OrdFloat),
 
     Array(Vec<AspenValue<'a>>),
-    Object(HashMap<&'a str, AspenValue<'a>>),
+    Object(AspenObject<'a>),
+    Record(AspenRecord<'a>),
+    Variant(AspenVariant<'a>),
 
-    Range {
-        start: Box<AspenValue<'a>>,
-        end: Box<AspenValue<'a>>,
-        step: Option<Box<AspenValue<'a>>>,
-    },
+    Range(AspenRange),
+    Iterator(AspenIterator<'a>),
+    /// Returned by the `next()` method of an exhausted iterator.
+    Done,
 
     Func(AspenFn<'a>),
 
     RustBindFn {
         name: &'a str,
-        code: fn(args: Vec<AspenValue<'a>>) -> EvaluateResult<AspenValue<'a>>,
+        code: NativeFn<'a>,
     },
+    /// The constructor of a record type.
+    RecordType(RecordType<'a>),
+    /// An enum, its variants are its properties.
+    EnumType(EnumType<'a>),
+}
+
+impl<'a> PartialEq for AspenValue<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (AspenValue::Nil, AspenValue::Nil) | (AspenValue::Done, AspenValue::Done) => true,
+            (AspenValue::Str(a), AspenValue::Str(b)) => a == b,
+            (AspenValue::Bool(a), AspenValue::Bool(b)) => a == b,
+            (AspenValue::Int(a), AspenValue::Int(b)) => a == b,
+            (AspenValue::Float(a), AspenValue::Float(b)) => a == b,
+            (AspenValue::Array(a), AspenValue::Array(b)) => a == b,
+            (AspenValue::Object(a), AspenValue::Object(b)) => a == b,
+            (AspenValue::Record(a), AspenValue::Record(b)) => a == b,
+            (AspenValue::Variant(a), AspenValue::Variant(b)) => a == b,
+            (AspenValue::Range(a), AspenValue::Range(b)) => a == b,
+            (AspenValue::Iterator(a), AspenValue::Iterator(b)) => a == b,
+            (AspenValue::Func(a), AspenValue::Func(b)) => a == b,
+            // functions of different modules can share a name, the same code is the same function
+            (
+                AspenValue::RustBindFn { name, code },
+                AspenValue::RustBindFn {
+                    name: other_name,
+                    code: other_code,
+                },
+            ) => name == other_name && std::ptr::fn_addr_eq(*code, *other_code),
+            (AspenValue::RecordType(a), AspenValue::RecordType(b)) => a == b,
+            (AspenValue::EnumType(a), AspenValue::EnumType(b)) => a == b,
+            _ => false,
+        }
+    }
 }
 
 impl<'a> fmt::Display for AspenValue<'a> {
@@ -39,7 +83,6 @@ impl<'a> fmt::Display for AspenValue<'a> {
             AspenValue::Bool(b) => write!(f, "{}", b),
             AspenValue::Int(i) => write!(f, "{}", i.to_string()),
             AspenValue::Float(fl) => write!(f, "{}", Float::from(fl.to_owned()).to_string()),
-            AspenValue::Error(s) => write!(f, "Err(\"{}\")", s),
             AspenValue::Array(arr) => {
                 write!(f, "[")?;
                 for (i, v) in arr.iter().enumerate() {
@@ -60,15 +103,15 @@ impl<'a> fmt::Display for AspenValue<'a> {
                 }
                 write!(f, "}}")
             }
-            AspenValue::Range { start, end, step } => {
-                write!(f, "{}:{}", start, end)?;
-                if let Some(step) = step {
-                    write!(f, ":{}", step)?;
-                }
-                Ok(())
-            }
+            AspenValue::Record(record) => write!(f, "{}", record),
+            AspenValue::Variant(variant) => write!(f, "{}", variant),
+            AspenValue::Range(range) => write!(f, "{}", range),
+            AspenValue::Iterator(_) => write!(f, "Iterator"),
+            AspenValue::Done => write!(f, "Done"),
             AspenValue::Func(func) => write!(f, "Func<{}>", func.name),
             AspenValue::RustBindFn { name, .. } => write!(f, "RustFunc<{}>", name),
+            AspenValue::RecordType(record_type) => write!(f, "Type<{}>", record_type.name),
+            AspenValue::EnumType(enum_type) => write!(f, "Enum<{}>", enum_type.name),
         }
     }
 }
@@ -78,6 +121,13 @@ impl<'a> From<String> for AspenValue<'a> {
         AspenValue::Str(value)
     }
 }
+/// An error value is the `Err` variant of `Result`.
+impl<'a> From<AspenErr<'a>> for AspenValue<'a> {
+    fn from(err: AspenErr<'a>) -> Self {
+        AspenValue::Variant(AspenVariant::err(err))
+    }
+}
+
 impl<'a> From<Integer> for AspenValue<'a> {
     fn from(value: Integer) -> Self {
         AspenValue::Int(value)
@@ -104,3 +154,21 @@ impl<'a> From<bool> for AspenValue<'a> {
         AspenValue::Bool(value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::value_of;
+
+    #[test]
+    fn native_functions_are_equal_to_themselves_only() {
+        let code = r#"
+let array $import("array")
+let string $import("string")
+let same array.len == array.len
+let differ array.len == string.len
+"#;
+
+        assert_eq!(value_of(code, "same"), "true");
+        assert_eq!(value_of(code, "differ"), "false");
+    }
+}
@@ -6,8 +6,39 @@ use std::fmt::Display;
 
 pub type AspenLexer<'s> = Lexer<'s, Token<'s>>;
 
+/// State kept by the lexer between two tokens.
+#[derive(Default, Debug, Clone)]
+pub struct LexerState {
+    /// End of the last token that can be the left operand of a binary operator.
+    operand_end: Option<usize>,
+}
+
+/// Records the token just lexed as a possible left operand of a binary operator.
+fn operand(lex: &mut AspenLexer<'_>) {
+    lex.extras.operand_end = Some(lex.span().end);
+}
+
+/// Lexes a `//` following an operand on the same line as an integer division, e.g `7 // 2`,
+/// any other `//` starts a comment going up to the end of the line.
+fn int_divide_or_comment<'s>(lex: &mut AspenLexer<'s>) -> Token<'s> {
+    let start = lex.span().start;
+    let follows_operand = lex.extras.operand_end.is_some_and(|end| {
+        lex.source()[end..start]
+            .chars()
+            .all(|c| matches!(c, ' ' | '\t' | '\r'))
+    });
+
+    if follows_operand {
+        return Token::BinaryOperator(BinaryOperator::IntDivide);
+    }
+
+    let comment = lex.remainder().split('\n').next().unwrap_or_default();
+    lex.bump(comment.len());
+    Token::LineComment(comment.trim())
+}
+
 #[derive(Logos, Debug, PartialEq, Clone)]
-#[logos(error = LexingError)]
+#[logos(error = LexingError, extras = LexerState)]
 pub enum Token<'a> {
     #[token("\n")]
     Newline,
@@ -25,18 +56,18 @@ pub enum Token<'a> {
 
     #[token("[")]
     OpenBracket,
-    #[token("]")]
+    #[token("]", operand)]
     CloseBracket,
 
     #[token("(")]
     OpenParen,
-    #[token(")")]
+    #[token(")", operand)]
     CloseParen,
 
     #[regex(r"->|in")]
     In,
 
-    #[token("nil")]
+    #[token("nil", operand)]
     Nil,
 
     #[token("if")]
@@ -46,13 +77,35 @@ pub enum Token<'a> {
     Other,
     #[token("else")]
     Else,
+    #[token("match")]
+    Match,
+    /// Separates the pattern of a match arm from its value
+    #[token("=>")]
+    FatArrow,
+    #[token("try")]
+    Try,
+    #[token("catch")]
+    Catch,
 
     #[regex(r"\$import|\$imp|\$")]
     Import,
-    #[regex(r"return|>>")]
+    #[token("$type")]
+    TypeDecl,
+    #[token("$enum")]
+    EnumDecl,
+    /// `return`, a `>>` starting a statement is lexed as a shift and read as a return by the parser.
+    #[token("return")]
     Return,
+    #[token("yield")]
+    Yield,
+    #[token("break")]
+    Break,
+    #[token("continue")]
+    Continue,
     #[token("let")]
     Let,
+    #[token("const")]
+    Const,
     #[token("for")]
     For,
     #[token("while")]
@@ -65,10 +118,17 @@ pub enum Token<'a> {
     Dot,
     #[token(":")]
     Range,
-    #[token("?")]
+    #[token(":<")]
+    ExclusiveRange,
+    /// `~value`, the bitwise negation of an integer.
+    #[token("~")]
+    BitNot,
+    #[token("?", operand)]
     PropagationOperator,
 
-    #[regex(r"//[^\n]*", |lex| let raw=lex.slice();raw[2..=raw.len()-1].trim())]
+    /// `//`, never returned: it is lexed as [`BinaryOperator::IntDivide`] or as a [`Token::LineComment`].
+    #[token("//", int_divide_or_comment)]
+    DoubleSlash,
     LineComment(&'a str),
     #[regex(r"///[^\n]*", |lex| let raw=lex.slice();raw[2..=raw.len()-1].trim())]
     DocComment(&'a str),
@@ -78,18 +138,25 @@ pub enum Token<'a> {
     #[regex("@[a-zA-Z_][a-zA-Z0-9_]*", |lex| &lex.slice()[1..])]
     Func(&'a str),
 
-    #[regex("[a-zA-Z_][a-zA-Z0-9_]*", |lex| lex.slice())]
+    /// The label of a loop, e.g `#outer for x in xs {}`, targeted with `break #outer`
+    #[regex("#[a-zA-Z_][a-zA-Z0-9_]*", |lex| &lex.slice()[1..])]
+    Label(&'a str),
+
+    #[regex("[a-zA-Z_][a-zA-Z0-9_]*", |lex| {operand(lex); lex.slice()})]
     Identifier(&'a str),
 
-    #[regex(r#""([^"\\]|\\["\\bnfrt]|\\u\{[a-fA-F0-9]+})*"|'([^'\\]|\\['\\bnfrt]|\\u\{[a-fA-F0-9]+})*'"#, |lex| let raw=lex.slice();&raw[1..raw.len()-1])]
+    #[regex(r#""([^"\\]|\\["\\bnfrt]|\\u\{[a-fA-F0-9]+})*"|'([^'\\]|\\['\\bnfrt]|\\u\{[a-fA-F0-9]+})*'"#, |lex| {operand(lex); let raw=lex.slice();&raw[1..raw.len()-1]})]
     String(&'a str),
+    /// A string literal prefixed with `r` whose content is kept as is, escapes included
+    #[regex(r#"r"[^"]*"|r'[^']*'"#, |lex| {operand(lex); let raw=lex.slice();&raw[2..raw.len()-1]})]
+    RawString(&'a str),
 
-    #[regex("true|false", |lex| lex.slice() == "true")]
+    #[regex("true|false", |lex| {operand(lex); lex.slice() == "true"})]
     Bool(bool),
 
-    #[regex(r"-?\d+(_?\d)*", |lex| Integer::parse(lex.slice()).unwrap(/* the number is valid */).complete(), priority = 5)]
+    #[regex(r"-?\d+(_?\d)*", |lex| {operand(lex); Integer::parse(lex.slice()).unwrap(/* the number is valid */).complete()}, priority = 5)]
     Int(Integer),
-    #[regex(r"-?\d+(_?\d)*(\.\d+)([eE][-+]?\d+)?", |lex| OrdFloat::from(Float::with_val(18, Float::parse(lex.slice()).unwrap(/* the number is valid */))), priority = 4)]
+    #[regex(r"-?\d+(_?\d)*(\.\d+)([eE][-+]?\d+)?", |lex| {operand(lex); OrdFloat::from(Float::with_val(18, Float::parse(lex.slice()).unwrap(/* the number is valid */)))}, priority = 4)]
     Float(OrdFloat),
 
     #[regex("[a-zA-Z_][a-zA-Z0-9_]*!", |lex| {let raw=lex.slice();&raw[..raw.len()-1]})]
@@ -108,22 +175,28 @@ pub enum Token<'a> {
     })]
     AssignOperator(AssignOperator),
 
-    #[regex(r#"\+|-|\*\*|\*|/|%|!=|==|>=|>|<=|<|&&|\|\|"#, |lex| {
+    #[regex(r#"\+|-|\*\*|\*|/|%|!=|==|>=|>>|>|<<|<=|<|&&|&|\|\||\||\^|\?\?"#, |lex| {
         match lex.slice() {
             "+" => BinaryOperator::Plus,
             "-" => BinaryOperator::Sub,
             "*" => BinaryOperator::Times,
             "**" => BinaryOperator::Exponent,
             "/" => BinaryOperator::Divide,
-            "%" => BinaryOperator::Modulo,
+                        "%" => BinaryOperator::Modulo,
             "==" => BinaryOperator::Equal,
             "!=" => BinaryOperator::NotEqual,
             ">"=> BinaryOperator::GreaterThan,
             ">="=> BinaryOperator::GreaterThanOrEqual,
             "<"=> BinaryOperator::LessThan,
             "<="=> BinaryOperator::LessThanOrEqual,
+            "<<" => BinaryOperator::ShiftLeft,
+            ">>" => BinaryOperator::ShiftRight,
             "&&" => BinaryOperator::And,
             "||" => BinaryOperator::Or,
+            "&" => BinaryOperator::BitAnd,
+            "|" => BinaryOperator::BitOr,
+            "^" => BinaryOperator::BitXor,
+            "??" => BinaryOperator::Coalesce,
             _ => unreachable!(),
         }
     })]
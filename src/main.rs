@@ -1,4 +1,9 @@
-use crate::{errors::build_error, evaluate::AspenTable, lexer::Token, parser::parse_aspen};
+use crate::{
+    errors::{build_error, build_evaluate_error, build_propagated_error, build_warning},
+    evaluate::AspenTable,
+    lexer::Token,
+    parser::parse_aspen,
+};
 use dialoguer::{theme::ColorfulTheme, Select};
 use logos::Logos;
 use parser::error::AspenResult;
@@ -8,6 +13,8 @@ mod errors;
 mod evaluate;
 mod lexer;
 mod parser;
+#[cfg(test)]
+mod test_utils;
 
 fn main() -> AspenResult<()> {
     let names: Vec<_> = fs::read_dir("./aspen/")
@@ -49,6 +56,9 @@ fn main() -> AspenResult<()> {
             if let Err(e) = parse_aspen(&mut parser) {
                 build_error(parser.lexer.source(), e, &file_name)
             };
+            for warning in parser.warnings() {
+                build_warning(parser.lexer.source(), warning, &file_name)
+            }
 
             // println!("stmts: {:?}", parser.statements());
             // println!("comments: {:?}", parser.comments());
@@ -58,7 +68,11 @@ fn main() -> AspenResult<()> {
             let start = Instant::now();
             let mut table = AspenTable::global();
             if let Err(e) = table.evaluate_block(parser.statements()) {
-                println!("{}", e)
+                match table.propagated_error(e) {
+                    Ok(err) => build_propagated_error(parser.lexer.source(), &err, &file_name),
+                    Err(e) => build_evaluate_error(parser.lexer.source(), e, &file_name),
+                }
+                std::process::exit(1);
             };
             println!("Executing took {} ms!", start.elapsed().as_millis());
         }
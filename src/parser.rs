@@ -1,65 +1,101 @@
 use self::conditional::If;
+use self::enum_decl::Enum;
 use self::error::AspenError;
 use self::for_loop::For;
 use self::func::Func;
+use self::loop_control::{parse_labelled_loop, Break, Continue};
+use self::match_expr::Match;
 use self::operator::{AssignOperator, BinaryOperator};
+use self::record::Record;
 use self::return_stmt::Return;
+use self::string::FormatSpec;
+use self::try_catch::Try;
 use self::utils::Block;
 use self::while_loop::While;
-use self::{comment::Comment, error::AspenResult, value::Value, var::Var};
+use self::yield_stmt::Yield;
+use self::{
+    comment::Comment,
+    error::{AspenResult, AspenWarning},
+    value::Value,
+    var::Var,
+};
 use crate::lexer::{AspenLexer, Token};
-use hashbrown::HashMap;
+use indexmap::IndexMap;
 use logos::Lexer;
 
 pub mod comment;
 pub mod conditional;
+pub mod enum_decl;
 pub mod error;
 mod expr;
 pub mod for_loop;
 pub mod func;
+pub mod loop_control;
 mod macros;
+pub mod match_expr;
 pub mod operator;
+pub mod record;
 pub mod return_stmt;
+pub mod string;
+pub mod try_catch;
 pub mod utils;
 pub mod value;
 pub mod var;
 pub mod while_loop;
+pub mod yield_stmt;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Statement<'a> {
     Var(Var<'a>),
     Func(Func<'a>),
 
-    // change to Expr{value: Box<Expr<'a>>, is_returned: bool} to handle implicit return in the future, when followed by ';' then is not returned
-    Expr(Box<Expr<'a>>),
+    /// An expression evaluated for its effects, or for its value when `is_returned`:
+    /// the last expression of a block without a trailing `;` is the value of the block.
+    Expr {
+        value: Box<Expr<'a>>,
+        is_returned: bool,
+    },
 
     For(For<'a>),
     While(While<'a>),
     Return(Return<'a>),
+    Yield(Yield<'a>),
+    Break(Break<'a>),
+    Continue(Continue<'a>),
     If(If<'a>),
+    Record(Record<'a>),
+    Enum(Enum<'a>),
 }
 
 pub type Container<T> = Vec<Box<T>>;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 /// Represents an Aspen Expression!
 pub enum Expr<'a> {
     Value(Value<'a>),
     Import(&'a str),
 
     Array(Container<Expr<'a>>),
-    Object(HashMap<&'a str, Box<Expr<'a>>>),
+    Object(IndexMap<&'a str, Box<Expr<'a>>>),
 
     Id(&'a str),
     SpeadId(&'a str),
 
     Parenthesized(Box<Expr<'a>>),
-    PropagatedFailible(Box<Expr<'a>>),
+    /// `value?`, `span` is the position of the '?'.
+    PropagatedFailible {
+        value: Box<Expr<'a>>,
+        span: std::ops::Range<usize>,
+    },
+    /// `~value`, flips the bits of an integer.
+    BitNot(Box<Expr<'a>>),
 
     Assign {
         target: Box<Expr<'a>>,
         operator: AssignOperator,
         value: Box<Expr<'a>>,
+        /// Position of the assignment operator.
+        span: std::ops::Range<usize>,
     },
     Binary {
         lhs: Box<Expr<'a>>,
@@ -76,6 +112,8 @@ pub enum Expr<'a> {
         start: Box<Expr<'a>>,
         end: Box<Expr<'a>>,
         step: Option<Box<Expr<'a>>>,
+        /// False when the end is excluded: `start:<end`.
+        inclusive: bool,
     },
 
     ArrayIndexing {
@@ -92,13 +130,33 @@ pub enum Expr<'a> {
         left: Box<Expr<'a>>,
         right: Box<Expr<'a>>,
     },
+    /// An expression embedded in an interpolated string, evaluates to a string.
+    Formatted {
+        value: Box<Expr<'a>>,
+        spec: Option<FormatSpec>,
+    },
+
+    /// An `if` used as a value, evaluates to the value of the selected block, or nil.
+    If(Box<If<'a>>),
+    Match(Match<'a>),
+    /// A `for` or a `while` loop used as a value, evaluates to the value given to `break`, or nil.
+    Loop(Box<Statement<'a>>),
+    Try(Box<Try<'a>>),
 }
 
+/// A name declared in a block, with the position of its declaration if it is a constant.
+type DeclaredName<'s> = (&'s str, Option<std::ops::Range<usize>>);
+
 #[derive(Debug, Clone)]
 pub struct AspenParser<'s> {
     pub lexer: AspenLexer<'s>,
     body: Block<'s>,
     comments: Container<Comment<'s>>,
+    /// Labels of the loops surrounding the code being parsed, innermost last.
+    loops: Vec<Option<&'s str>>,
+    /// Names declared in each block surrounding the code being parsed, innermost last.
+    names: Vec<Vec<DeclaredName<'s>>>,
+    warnings: Vec<AspenWarning>,
 }
 
 pub fn parse_aspen(parser: &mut AspenParser<'_>) -> AspenResult<()> {
@@ -119,10 +177,31 @@ pub fn parse_block<'s>(
     let mut statements = vec![];
     let mut expect_stmt_end = false;
     let mut semi_colon_found = false;
+    let mut line_start = true;
+    // the names declared in the block are not visible after it
+    parser.names.push(vec![]);
 
     while let Some(result_token) = parser.lexer.next() {
         let token = result_token.map_err(|e| AspenError::from_lexing_error(parser, e))?;
 
+        // '>>' shifts the value of a statement on the same line, it returns when it starts a statement
+        let token = match token {
+            Token::BinaryOperator(BinaryOperator::ShiftRight)
+                if line_start || !statements.last().map(Box::as_ref).is_some_and(has_value) =>
+            {
+                Token::Return
+            }
+            token => token,
+        };
+        line_start = match token {
+            Token::Newline | Token::SemiColon => true,
+            Token::Spaces
+            | Token::LineComment(_)
+            | Token::DocComment(_)
+            | Token::MultiLineComment(_) => line_start,
+            _ => false,
+        };
+
         match token {
             Token::Return => {
                 semi_colon_found = false;
@@ -132,9 +211,33 @@ pub fn parse_block<'s>(
                 expect_stmt_end = true;
                 continue;
             }
-            Token::Let => {
+            Token::Yield => {
+                semi_colon_found = false;
+                let stmt = Yield::parse(parser)?;
+                statements.push(Box::new(stmt));
+
+                expect_stmt_end = true;
+                continue;
+            }
+            Token::Break => {
+                semi_colon_found = false;
+                let stmt = Break::parse(parser)?;
+                statements.push(Box::new(stmt));
+
+                expect_stmt_end = true;
+                continue;
+            }
+            Token::Continue => {
+                semi_colon_found = false;
+                let stmt = Continue::parse(parser)?;
+                statements.push(Box::new(stmt));
+
+                expect_stmt_end = true;
+                continue;
+            }
+            Token::Let | Token::Const => {
                 semi_colon_found = false;
-                let stmt = Var::parse(parser)?;
+                let stmt = Var::parse(parser, token == Token::Const)?;
                 statements.push(Box::new(stmt));
 
                 expect_stmt_end = true;
@@ -147,8 +250,9 @@ pub fn parse_block<'s>(
 
                 if let Some(stmt) = statements.last_mut() {
                     match stmt.as_mut() {
-                        Statement::Var(_) => {
-                            let stmt = Var::parse_after_comma(parser)?;
+                        Statement::Var(Var { is_const, .. }) => {
+                            let is_const = *is_const;
+                            let stmt = Var::parse_after_comma(parser, is_const)?;
                             statements.push(Box::new(stmt));
                         }
                         _ => {
@@ -169,13 +273,31 @@ pub fn parse_block<'s>(
 
             Token::For => {
                 semi_colon_found = false;
-                let stmt = For::parse(parser)?;
+                let stmt = For::parse(parser, None)?;
+                statements.push(Box::new(stmt));
+                continue;
+            }
+            Token::TypeDecl => {
+                semi_colon_found = false;
+                let stmt = Record::parse(parser)?;
+                statements.push(Box::new(stmt));
+                continue;
+            }
+            Token::EnumDecl => {
+                semi_colon_found = false;
+                let stmt = Enum::parse(parser)?;
                 statements.push(Box::new(stmt));
                 continue;
             }
             Token::While => {
                 semi_colon_found = false;
-                let stmt = While::parse(parser)?;
+                let stmt = While::parse(parser, None)?;
+                statements.push(Box::new(stmt));
+                continue;
+            }
+            Token::Label(label) => {
+                semi_colon_found = false;
+                let stmt = parse_labelled_loop(parser, label)?;
                 statements.push(Box::new(stmt));
                 continue;
             }
@@ -246,7 +368,11 @@ pub fn parse_block<'s>(
                 continue;
             }
             _ if stop_on.is_some() && &token == stop_on.as_ref().unwrap() => {
-                return Ok(Block::new(statements));
+                parser.names.pop();
+                return Ok(Block::new(returning_last_expr(
+                    statements,
+                    semi_colon_found,
+                )));
             }
             Token::Nil
             | Token::Bool(_)
@@ -255,8 +381,13 @@ pub fn parse_block<'s>(
             | Token::OpenBrace
             | Token::SpreadOperator
             | Token::String(_)
+            | Token::RawString(_)
+            | Token::Match
+            | Token::Try
+            | Token::BitNot
             | Token::Identifier(_) => {
                 if let Ok(ex) = Expr::parse_with_token(parser, token) {
+                    semi_colon_found = false;
                     statements.push(Box::new(ex.into()))
                 }
             }
@@ -269,12 +400,29 @@ pub fn parse_block<'s>(
                 }
 
                 if let Some(stmt) = statements.last_mut() {
-                    if let Statement::Expr(base_expr) = stmt.as_mut() {
+                    if let Statement::Expr {
+                        value: base_expr, ..
+                    } = stmt.as_mut()
+                    {
+                        let span = parser.lexer.span();
+                        let constant = base_expr.assigned_name().and_then(|name| {
+                            Some((name, parser.constant_declaration(name)?.to_owned()))
+                        });
+
+                        if let Some((name, declaration)) = constant {
+                            return Err(AspenError::AssignToConstant {
+                                name: name.to_string(),
+                                assignment: span,
+                                declaration,
+                            });
+                        }
+
                         let expr = Expr::parse(parser)?;
                         **stmt = Expr::Assign {
                             target: base_expr.clone(),
                             operator: aop,
                             value: Box::new(expr),
+                            span,
                         }
                         .into();
                     } else {
@@ -300,16 +448,23 @@ pub fn parse_block<'s>(
 
                 if let Some(stmt) = statements.last_mut() {
                     match stmt.as_mut() {
-                        Statement::Expr(base_expr)
+                        Statement::Expr {
+                            value: base_expr, ..
+                        }
                         | Statement::Var(Var {
                             value: base_expr, ..
                         }) => {
                             let expr = Expr::parse(parser)?;
-                            Expr::modify_into_binary_op(parser, base_expr, expr, bop)?;
+                            Expr::modify_into_binary_op(base_expr, expr, bop)?;
                         }
-                        Statement::Return(Return(returned_expr)) => {
+                        Statement::Return(Return(returned_expr))
+                        | Statement::Yield(Yield(returned_expr))
+                        | Statement::Break(Break {
+                            value: Some(returned_expr),
+                            ..
+                        }) => {
                             let expr = Expr::parse(parser)?;
-                            Expr::modify_into_binary_op(parser, returned_expr, expr, bop)?;
+                            Expr::modify_into_binary_op(returned_expr, expr, bop)?;
                         }
                         _ => {
                             return Err(error::AspenError::unknown(
@@ -332,7 +487,9 @@ pub fn parse_block<'s>(
 
                 if let Some(stmt) = statements.last_mut() {
                     match stmt.as_mut() {
-                        Statement::Expr(base_expr) => {
+                        Statement::Expr {
+                            value: base_expr, ..
+                        } => {
                             Expr::modify_into_fn_call(parser, base_expr)?;
                             continue;
                         }
@@ -340,7 +497,12 @@ pub fn parse_block<'s>(
                             Expr::modify_into_fn_call(parser, value)?;
                             continue;
                         }
-                        Statement::Return(Return(returned_expr)) => {
+                        Statement::Return(Return(returned_expr))
+                        | Statement::Yield(Yield(returned_expr))
+                        | Statement::Break(Break {
+                            value: Some(returned_expr),
+                            ..
+                        }) => {
                             Expr::modify_into_fn_call(parser, returned_expr)?;
                             continue;
                         }
@@ -349,7 +511,8 @@ pub fn parse_block<'s>(
                 }
 
                 let expr = Expr::parse_parenthesized(parser)?;
-                statements.push(Box::new(Statement::Expr(Box::new(expr))))
+                semi_colon_found = false;
+                statements.push(Box::new(expr.into()))
             }
             Token::PropagationOperator => {
                 if semi_colon_found {
@@ -358,7 +521,9 @@ pub fn parse_block<'s>(
 
                 if let Some(stmt) = statements.last_mut() {
                     match stmt.as_mut() {
-                        Statement::Expr(base_expr) => {
+                        Statement::Expr {
+                            value: base_expr, ..
+                        } => {
                             Expr::modify_into_error_propagation(parser, base_expr)?;
                             continue;
                         }
@@ -366,7 +531,12 @@ pub fn parse_block<'s>(
                             Expr::modify_into_error_propagation(parser, value)?;
                             continue;
                         }
-                        Statement::Return(Return(returned_expr)) => {
+                        Statement::Return(Return(returned_expr))
+                        | Statement::Yield(Yield(returned_expr))
+                        | Statement::Break(Break {
+                            value: Some(returned_expr),
+                            ..
+                        }) => {
                             Expr::modify_into_error_propagation(parser, returned_expr)?;
                             continue;
                         }
@@ -376,27 +546,37 @@ pub fn parse_block<'s>(
 
                 return Err(AspenError::unknown(parser, "token '?' found".to_owned()));
             }
-            Token::Range => {
+            Token::Range | Token::ExclusiveRange => {
+                let inclusive = token == Token::Range;
+
                 if semi_colon_found {
-                    return Err(AspenError::unknown(parser, "token ':' found".to_owned()));
+                    return Err(AspenError::unknown(
+                        parser,
+                        format!("token '{}' found", parser.lexer.slice()),
+                    ));
                 }
 
                 if let Some(stmt) = statements.last_mut() {
                     match stmt.as_mut() {
-                        Statement::Expr(base_expr) => {
-                            Expr::modify_into_range(parser, base_expr)?;
+                        Statement::Expr {
+                            value: base_expr, ..
+                        } => {
+                            Expr::modify_into_range(parser, base_expr, inclusive)?;
                             continue;
                         }
                         Statement::Var(var) => {
                             let Var { value, .. } = var;
-                            Expr::modify_into_range(parser, value)?;
+                            Expr::modify_into_range(parser, value, inclusive)?;
                             continue;
                         }
                         _ => (),
                     };
                 }
 
-                return Err(AspenError::unknown(parser, "token ':' found".to_owned()));
+                return Err(AspenError::unknown(
+                    parser,
+                    format!("token '{}' found", parser.lexer.slice()),
+                ));
             }
             Token::Dot => {
                 if semi_colon_found {
@@ -405,13 +585,20 @@ pub fn parse_block<'s>(
 
                 if let Some(stmt) = statements.last_mut() {
                     match stmt.as_mut() {
-                        Statement::Expr(base_expr) => {
+                        Statement::Expr {
+                            value: base_expr, ..
+                        } => {
                             Expr::modify_into_obj_indexing(parser, base_expr)?;
                         }
                         Statement::Var(Var { value, .. }) => {
                             Expr::modify_into_obj_indexing(parser, value)?;
                         }
-                        Statement::Return(Return(returned_expr)) => {
+                        Statement::Return(Return(returned_expr))
+                        | Statement::Yield(Yield(returned_expr))
+                        | Statement::Break(Break {
+                            value: Some(returned_expr),
+                            ..
+                        }) => {
                             Expr::modify_into_obj_indexing(parser, returned_expr)?;
                         }
                         _ => return Err(AspenError::unknown(parser, "token '[' found".to_owned())),
@@ -425,13 +612,20 @@ pub fn parse_block<'s>(
 
                 if let Some(stmt) = statements.last_mut() {
                     match stmt.as_mut() {
-                        Statement::Expr(base_expr) => {
+                        Statement::Expr {
+                            value: base_expr, ..
+                        } => {
                             Expr::modify_into_string_concatenation(parser, base_expr)?;
                         }
                         Statement::Var(Var { value, .. }) => {
                             Expr::modify_into_string_concatenation(parser, value)?;
                         }
-                        Statement::Return(Return(returned_expr)) => {
+                        Statement::Return(Return(returned_expr))
+                        | Statement::Yield(Yield(returned_expr))
+                        | Statement::Break(Break {
+                            value: Some(returned_expr),
+                            ..
+                        }) => {
                             Expr::modify_into_string_concatenation(parser, returned_expr)?;
                         }
                         _ => {
@@ -451,13 +645,20 @@ pub fn parse_block<'s>(
 
                 if let Some(stmt) = statements.last_mut() {
                     match stmt.as_mut() {
-                        Statement::Expr(base_expr) => {
+                        Statement::Expr {
+                            value: base_expr, ..
+                        } => {
                             Expr::modify_into_array_indexing(parser, base_expr)?;
                         }
                         Statement::Var(Var { value, .. }) => {
                             Expr::modify_into_array_indexing(parser, value)?;
                         }
-                        Statement::Return(Return(returned_expr)) => {
+                        Statement::Return(Return(returned_expr))
+                        | Statement::Yield(Yield(returned_expr))
+                        | Statement::Break(Break {
+                            value: Some(returned_expr),
+                            ..
+                        }) => {
                             Expr::modify_into_array_indexing(parser, returned_expr)?;
                         }
                         _ => return Err(AspenError::unknown(parser, "token '[' found".to_owned())),
@@ -490,7 +691,34 @@ pub fn parse_block<'s>(
         ));
     }
 
-    Ok(Block::new(statements))
+    Ok(Block::new(returning_last_expr(
+        statements,
+        semi_colon_found,
+    )))
+}
+
+/// Tells if a statement ends with a value an operator can apply to.
+fn has_value(statement: &Statement<'_>) -> bool {
+    matches!(
+        statement,
+        Statement::Expr { .. }
+            | Statement::Var(_)
+            | Statement::Return(_)
+            | Statement::Yield(_)
+            | Statement::Break(Break { value: Some(_), .. })
+    )
+}
+
+/// Marks the last statement of a block as its value if it is an expression not followed by a `;`.
+fn returning_last_expr(
+    mut statements: Container<Statement<'_>>,
+    semi_colon_found: bool,
+) -> Container<Statement<'_>> {
+    if let Some(Statement::Expr { is_returned, .. }) = statements.last_mut().map(Box::as_mut) {
+        *is_returned = !semi_colon_found;
+    }
+
+    statements
 }
 
 impl<'a> AspenParser<'a> {
@@ -499,6 +727,9 @@ impl<'a> AspenParser<'a> {
             lexer,
             body: Block::default(),
             comments: vec![],
+            loops: vec![],
+            names: vec![],
+            warnings: vec![],
         }
     }
     pub fn add_comment(&mut self, comment: Comment<'a>) {
@@ -510,6 +741,60 @@ impl<'a> AspenParser<'a> {
     pub fn comments(&self) -> Container<Comment<'a>> {
         self.comments.to_owned()
     }
+    pub fn add_warning(&mut self, warning: AspenWarning) {
+        self.warnings.push(warning)
+    }
+    pub fn warnings(&self) -> &[AspenWarning] {
+        &self.warnings
+    }
+    /// Records names declared in the block being parsed, along with the position of their declaration if they are constants.
+    pub fn declare_names(
+        &mut self,
+        names: impl IntoIterator<Item = &'a str>,
+        constant: Option<std::ops::Range<usize>>,
+    ) {
+        if let Some(block_names) = self.names.last_mut() {
+            block_names.extend(names.into_iter().map(|name| (name, constant.to_owned())));
+        }
+    }
+    /// Returns the position of the declaration of the constant the name refers to, if it refers to one.
+    fn constant_declaration(&self, name: &str) -> Option<&std::ops::Range<usize>> {
+        self.names
+            .iter()
+            .rev()
+            .flat_map(|block_names| block_names.iter().rev())
+            .find(|(declared, _)| *declared == name)
+            .and_then(|(_, constant)| constant.as_ref())
+    }
+    /// Opens a block declaring names without 'let', e.g the arguments of a for loop.
+    ///
+    /// Warns for each name shadowing one of an enclosing block, shadowing is only intended with 'let'.
+    pub fn push_implicit_names(&mut self, names: Vec<&'a str>, span: std::ops::Range<usize>) {
+        for name in &names {
+            if self
+                .names
+                .iter()
+                .flatten()
+                .any(|(declared, _)| declared == name)
+            {
+                self.add_warning(AspenWarning::new(
+                    format!("'{}' shadows a variable of an enclosing block", name),
+                    format!(
+                        "Rename it to keep '{}' of the enclosing block visible, shadowing is intended only with 'let'",
+                        name
+                    ),
+                    span.to_owned(),
+                ));
+            }
+        }
+
+        self.names
+            .push(names.into_iter().map(|name| (name, None)).collect());
+    }
+    /// Closes a block opened with [`AspenParser::push_implicit_names`].
+    pub fn pop_implicit_names(&mut self) {
+        self.names.pop();
+    }
 }
 
 impl<'a> From<Lexer<'a, Token<'a>>> for AspenParser<'a> {
@@ -520,6 +805,9 @@ impl<'a> From<Lexer<'a, Token<'a>>> for AspenParser<'a> {
 
 impl<'a> From<Expr<'a>> for Statement<'a> {
     fn from(value: Expr<'a>) -> Self {
-        Statement::Expr(Box::new(value))
+        Statement::Expr {
+            value: Box::new(value),
+            is_returned: false,
+        }
     }
 }
@@ -5,7 +5,7 @@ use crate::lexer::Token;
 use super::{
     error::{AspenError, AspenResult},
     parse_block,
-    utils::{expect_space, expect_token, Block},
+    utils::{expect_space, expect_token, next_jump_multispace, peek_jump_multispace, Block},
     AspenParser, Expr, Statement,
 };
 
@@ -41,6 +41,36 @@ impl<'s> If<'s> {
         .into())
     }
 
+    /// Parses an if used as a value, e.g `let x if c {1} else {2}`, along with its 'other' and 'else' clauses.
+    ///
+    /// **NOTE: We assume "if" is already consumed by the parser!**
+    pub fn parse_expr(parser: &mut AspenParser<'s>) -> AspenResult<Expr<'s>> {
+        let mut if_expr = match Self::parse(parser)? {
+            Statement::If(if_expr) => if_expr,
+            _ => unreachable!("an if statement is parsed"),
+        };
+
+        loop {
+            let other = match peek_jump_multispace(parser) {
+                Some(Token::Other) => {
+                    next_jump_multispace(parser)?;
+                    Self::parse_other(parser)?
+                }
+                Some(Token::Else) => {
+                    next_jump_multispace(parser)?;
+                    let other = Self::parse_else(parser)?;
+                    if_expr.add_other_at_if_end(parser, other)?;
+                    break;
+                }
+                _ => break,
+            };
+
+            if_expr.add_other_at_if_end(parser, other)?;
+        }
+
+        Ok(Expr::If(Box::new(if_expr)))
+    }
+
     pub fn parse_other(parser: &mut AspenParser<'s>) -> AspenResult<IfOther<'s>> {
         expect_space(parser)?;
         let (condition, _) = Expr::parse_until(parser, &[Token::OpenBrace])?;
@@ -92,3 +122,59 @@ impl<'s> If<'s> {
         Ok(IfOther::Else(body))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::value_of;
+
+    #[test]
+    fn blocks_return_their_last_expression() {
+        let code = r#"
+@square x {
+    let y x * x
+    y
+}
+@nothing x {
+    x;
+}
+@early x {
+    if x > 0 {
+        >> "positive"
+    }
+    "other"
+}
+let a square(4)
+let b nothing(4)
+let c early(1)
+let d early(-1)
+"#;
+        let value = |name| value_of(code, name);
+
+        assert_eq!(value("a"), "16");
+        assert_eq!(value("b"), "nil");
+        assert_eq!(value("c"), "positive");
+        assert_eq!(value("d"), "other");
+    }
+
+    #[test]
+    fn if_is_an_expression() {
+        let code = r#"
+@sign x {
+    if x > 0 {
+        1
+    } other x < 0 {
+        -1
+    } else {
+        0
+    }
+}
+let signs [sign(5), sign(-5), sign(0)]
+let inline if true { "yes" } else { "no" }
+let missing if false { 1 }
+"#;
+
+        assert_eq!(value_of(code, "signs"), "[1, -1, 0]");
+        assert_eq!(value_of(code, "inline"), "yes");
+        assert_eq!(value_of(code, "missing"), "nil");
+    }
+}
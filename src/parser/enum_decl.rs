@@ -0,0 +1,114 @@
+use super::{
+    error::{AspenError, AspenResult},
+    utils::{expect_space, expect_token, next_jump_multispace, peek_jump_space},
+    AspenParser, Statement,
+};
+use crate::lexer::Token;
+
+/// A `$enum Shape { Circle(r), Rect(w, h), Empty }` declaration of a tagged union.
+///
+/// `Shape.Circle(1)` creates a value of the variant holding its payload, `Shape.Empty` one without payload.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Enum<'s> {
+    pub name: &'s str,
+    pub variants: Vec<Variant<'s>>,
+}
+
+crate::impl_from_for!(Enum, Statement);
+
+/// A variant of an enum, along with the names of the values of its payload.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variant<'s> {
+    pub name: &'s str,
+    pub fields: Vec<&'s str>,
+}
+
+impl<'s> Enum<'s> {
+    /// Parses an enum declaration.
+    ///
+    /// **NOTE: We assume "$enum" is already consumed by the parser!**
+    pub fn parse(parser: &mut AspenParser<'s>) -> AspenResult<Statement<'s>> {
+        expect_space(parser)?;
+
+        let name = match next_jump_multispace(parser)? {
+            Token::Identifier(name) if name.starts_with(char::is_uppercase) => name,
+            _ => {
+                return Err(AspenError::expected(
+                    parser,
+                    "a capitalized name for the enum, e.g 'Shape'".to_owned(),
+                ))
+            }
+        };
+        parser.declare_names([name], None);
+
+        expect_token(parser, Token::OpenBrace)?;
+        let mut variants: Vec<Variant> = vec![];
+
+        loop {
+            let variant = match next_jump_multispace(parser)? {
+                Token::CloseBrace => break,
+                Token::Identifier(variant)
+                    if variants.iter().any(|declared| declared.name == variant) =>
+                {
+                    return Err(AspenError::expected(
+                        parser,
+                        format!("a new variant, '{}' is already declared", variant),
+                    ))
+                }
+                Token::Identifier(variant) if variant.starts_with(char::is_uppercase) => {
+                    Variant::parse(parser, variant)?
+                }
+                _ => {
+                    return Err(AspenError::expected(
+                        parser,
+                        "a capitalized variant, e.g 'Circle(radius)'".to_owned(),
+                    ))
+                }
+            };
+            variants.push(variant);
+
+            match next_jump_multispace(parser)? {
+                Token::Comma => (),
+                Token::CloseBrace => break,
+                _ => return Err(AspenError::expected(parser, "a ',' or a '}'".to_owned())),
+            }
+        }
+
+        Ok(Enum { name, variants }.into())
+    }
+}
+
+impl<'s> Variant<'s> {
+    /// Parses the payload of a variant, if it has one.
+    ///
+    /// **NOTE: We assume the name of the variant is already consumed by the parser!**
+    fn parse(parser: &mut AspenParser<'s>, name: &'s str) -> AspenResult<Self> {
+        let mut fields = vec![];
+
+        if peek_jump_space(parser) != Some(Token::OpenParen) {
+            return Ok(Variant { name, fields });
+        }
+        expect_token(parser, Token::OpenParen)?;
+
+        loop {
+            match next_jump_multispace(parser)? {
+                Token::CloseParen if fields.is_empty() => break,
+                Token::Identifier(field) if !fields.contains(&field) => fields.push(field),
+                _ => {
+                    return Err(AspenError::expected(
+                        parser,
+                        "a new name for a value of the payload".to_owned(),
+                    ))
+                }
+            }
+
+            match next_jump_multispace(parser)? {
+                Token::Comma => (),
+                Token::CloseParen => break,
+                _ => return Err(AspenError::expected(parser, "a ',' or a ')'".to_owned())),
+            }
+        }
+
+        Ok(Variant { name, fields })
+    }
+}
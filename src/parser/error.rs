@@ -45,10 +45,40 @@ pub enum AspenError {
         end: usize,
         length: usize,
     },
+    InvalidString {
+        error: String,
+        start: usize,
+        length: usize,
+    },
+    AssignToConstant {
+        name: String,
+        assignment: std::ops::Range<usize>,
+        declaration: std::ops::Range<usize>,
+    },
 
     Eof,
 }
 
+/// A problem found while parsing that does not prevent the code from running.
+#[derive(Debug, Clone)]
+pub struct AspenWarning {
+    pub message: String,
+    pub note: String,
+    pub start: usize,
+    pub length: usize,
+}
+
+impl AspenWarning {
+    pub fn new(message: String, note: String, span: std::ops::Range<usize>) -> Self {
+        Self {
+            message,
+            note,
+            start: span.start,
+            length: span.len(),
+        }
+    }
+}
+
 impl<'a> Error for AspenError {}
 
 impl fmt::Display for AspenError {
@@ -64,6 +94,10 @@ impl fmt::Display for AspenError {
             AspenError::ExpectedSpace { .. } => write!(f, "Expected a space character"),
             AspenError::ExpectedNewline { .. } => write!(f, "Expected a newline character"),
             AspenError::Unknown { error, .. } => write!(f, "Unknown {}", error),
+            AspenError::InvalidString { error, .. } => write!(f, "Invalid string, {}", error),
+            AspenError::AssignToConstant { name, .. } => {
+                write!(f, "Cannot assign a value to constant '{}'", name)
+            }
         }
     }
 }
@@ -1,15 +1,23 @@
 use super::{
     comment::Comment,
+    conditional::If,
     error::{AspenError, AspenResult},
+    for_loop::For,
     func::Func,
+    loop_control::parse_labelled_loop,
+    match_expr::{Match, MatchArm, Pattern},
     operator::BinaryOperator,
-    utils::{expect_token, next_jump_multispace, next_token, TokenOption},
+    string::{is_interpolated, parse_interpolated_string},
+    try_catch::Try,
+    utils::{expect_token, next_jump_multispace, next_jump_space, next_token, TokenOption},
     value::{parse_value, Value},
-    Expr,
+    var::{Destructured, Variables},
+    while_loop::While,
+    Expr, Statement,
 };
 use crate::parser::{AspenParser, Token};
-use hashbrown::HashMap;
-use std::cmp::Ordering;
+use indexmap::IndexMap;
+use std::{cmp::Ordering, ops::Range};
 
 impl<'s> Expr<'s> {
     /// Parses an expression.
@@ -45,6 +53,7 @@ impl<'s> Expr<'s> {
                     }
                 }
             }
+            Token::BitNot => Expr::BitNot(Box::new(Expr::parse(parser)?)),
             Token::Import => {
                 expect_token(parser, Token::OpenParen)?;
 
@@ -57,6 +66,13 @@ impl<'s> Expr<'s> {
 
                 Expr::Import(name)
             }
+            Token::String(s) if is_interpolated(parser, s) => parse_interpolated_string(parser, s)?,
+            Token::If => If::parse_expr(parser)?,
+            Token::Match => Match::parse(parser)?,
+            Token::Try => Try::parse(parser)?,
+            Token::For => Expr::Loop(Box::new(For::parse(parser, None)?)),
+            Token::While => Expr::Loop(Box::new(While::parse(parser, None)?)),
+            Token::Label(label) => Expr::Loop(Box::new(parse_labelled_loop(parser, label)?)),
             token => parse_value(parser, token)?.into(),
         };
 
@@ -65,7 +81,7 @@ impl<'s> Expr<'s> {
 
     fn add_func_call_to_most_rhs(&mut self, args: Vec<Box<Expr<'s>>>) {
         let mut expr = self;
-        while let Expr::Binary { rhs, .. } = expr {
+        while let Expr::Binary { rhs, .. } | Expr::BitNot(rhs) = expr {
             expr = rhs;
         }
         *expr = Expr::FuncCall {
@@ -75,7 +91,7 @@ impl<'s> Expr<'s> {
     }
     fn add_array_indexing_to_most_rhs(&mut self, indexer: Box<Expr<'s>>) {
         let mut expr = self;
-        while let Expr::Binary { rhs, .. } = expr {
+        while let Expr::Binary { rhs, .. } | Expr::BitNot(rhs) = expr {
             expr = rhs;
         }
         *expr = Expr::ArrayIndexing {
@@ -85,7 +101,7 @@ impl<'s> Expr<'s> {
     }
     fn add_obj_indexing_to_most_rhs(&mut self, indexer: Box<Expr<'s>>) {
         let mut expr = self;
-        while let Expr::Binary { rhs, .. } = expr {
+        while let Expr::Binary { rhs, .. } | Expr::BitNot(rhs) = expr {
             expr = rhs;
         }
         *expr = Expr::ObjIndexing {
@@ -93,16 +109,19 @@ impl<'s> Expr<'s> {
             indexer,
         };
     }
-    fn add_error_propagation_to_most_rhs(&mut self) {
+    fn add_error_propagation_to_most_rhs(&mut self, span: Range<usize>) {
         let mut expr = self;
-        while let Expr::Binary { rhs, .. } = expr {
+        while let Expr::Binary { rhs, .. } | Expr::BitNot(rhs) = expr {
             expr = rhs;
         }
-        *expr = Expr::PropagatedFailible(Box::new(expr.clone()));
+        *expr = Expr::PropagatedFailible {
+            value: Box::new(expr.clone()),
+            span,
+        };
     }
     fn add_string_concatenation_to_most_rhs(&mut self, right: Box<Expr<'s>>) {
         let mut expr = self;
-        while let Expr::Binary { rhs, .. } = expr {
+        while let Expr::Binary { rhs, .. } | Expr::BitNot(rhs) = expr {
             expr = rhs;
         }
         *expr = Expr::StringConcatenation {
@@ -111,6 +130,17 @@ impl<'s> Expr<'s> {
         };
     }
 
+    /// Returns the variable modified by an assignment to the expression: `x`, `x.name` or `x[0]`.
+    pub fn assigned_name(&self) -> Option<&'s str> {
+        match self {
+            Expr::Id(name) => Some(name),
+            Expr::ObjIndexing { indexed, .. } | Expr::ArrayIndexing { indexed, .. } => {
+                indexed.assigned_name()
+            }
+            _ => None,
+        }
+    }
+
     /// Function to call after a '(' is consumed when the expression is expected to be a function call.
     pub fn modify_into_fn_call(
         parser: &mut AspenParser<'s>,
@@ -123,7 +153,7 @@ impl<'s> Expr<'s> {
             | Expr::ObjIndexing { .. }
             | Expr::ArrayIndexing { .. }
             | Expr::Parenthesized(_)
-            | Expr::PropagatedFailible(_) => {
+            | Expr::PropagatedFailible { .. } => {
                 *base_expr = Box::new(Expr::FuncCall {
                     callee: base_expr.clone(),
                     args,
@@ -156,6 +186,7 @@ impl<'s> Expr<'s> {
                 ref mut value,
                 ref target,
                 ref operator,
+                span: ref assign_span,
             } => match value.as_mut() {
                 Expr::Binary { rhs, .. } => {
                     rhs.add_func_call_to_most_rhs(args);
@@ -165,9 +196,10 @@ impl<'s> Expr<'s> {
                 | Expr::ObjIndexing { .. }
                 | Expr::ArrayIndexing { .. }
                 | Expr::Parenthesized(_)
-                | Expr::PropagatedFailible(_) => {
+                | Expr::PropagatedFailible { .. } => {
                     *base_expr = Box::new(Expr::Assign {
                         target: target.clone(),
+                        span: assign_span.clone(),
                         operator: operator.to_owned(),
                         value: Box::new(Expr::FuncCall {
                             callee: value.clone(),
@@ -205,12 +237,14 @@ impl<'s> Expr<'s> {
 
     /// Function to call after a '?' is consumed when the expression is expected to be a failible expression.
     pub fn modify_into_error_propagation(
-        _parser: &mut AspenParser<'s>,
+        parser: &mut AspenParser<'s>,
         base_expr: &mut Box<Expr<'s>>,
     ) -> AspenResult<()> {
+        let span = parser.lexer.span();
+
         match base_expr.as_mut() {
             Expr::Binary { rhs, .. } => {
-                rhs.add_error_propagation_to_most_rhs();
+                rhs.add_error_propagation_to_most_rhs(span);
             }
             Expr::Range {
                 ref mut end,
@@ -219,10 +253,16 @@ impl<'s> Expr<'s> {
             } => {
                 match step.is_some() {
                     true => {
-                        *step = Some(Box::new(Expr::PropagatedFailible(step.take().unwrap())));
+                        *step = Some(Box::new(Expr::PropagatedFailible {
+                            value: step.take().unwrap(),
+                            span,
+                        }));
                     }
                     false => {
-                        *end = Box::new(Expr::PropagatedFailible(end.clone()));
+                        **end = Expr::PropagatedFailible {
+                            value: end.clone(),
+                            span,
+                        };
                     }
                 };
             }
@@ -230,9 +270,10 @@ impl<'s> Expr<'s> {
                 ref mut value,
                 ref target,
                 ref operator,
+                span: ref assign_span,
             } => match value.as_mut() {
                 Expr::Binary { rhs, .. } => {
-                    rhs.add_error_propagation_to_most_rhs();
+                    rhs.add_error_propagation_to_most_rhs(span);
                 }
                 Expr::Range {
                     ref mut end,
@@ -241,23 +282,36 @@ impl<'s> Expr<'s> {
                 } => {
                     match step.is_some() {
                         true => {
-                            *step = Some(Box::new(Expr::PropagatedFailible(step.take().unwrap())));
+                            *step = Some(Box::new(Expr::PropagatedFailible {
+                                value: step.take().unwrap(),
+                                span,
+                            }));
                         }
                         false => {
-                            *end = Box::new(Expr::PropagatedFailible(end.clone()));
+                            **end = Expr::PropagatedFailible {
+                                value: end.clone(),
+                                span,
+                            };
                         }
                     };
                 }
                 _ => {
                     *base_expr = Box::new(Expr::Assign {
                         target: target.clone(),
+                        span: assign_span.clone(),
                         operator: operator.to_owned(),
-                        value: Box::new(Expr::PropagatedFailible(value.clone())),
+                        value: Box::new(Expr::PropagatedFailible {
+                            value: value.clone(),
+                            span,
+                        }),
                     });
                 }
             },
             _ => {
-                *base_expr = Box::new(Expr::PropagatedFailible(base_expr.clone()));
+                **base_expr = Expr::PropagatedFailible {
+                    value: base_expr.clone(),
+                    span,
+                };
             }
         };
 
@@ -277,7 +331,8 @@ impl<'s> Expr<'s> {
             | Expr::ArrayIndexing { .. }
             | Expr::Parenthesized(_)
             | Expr::Value(Value::Str(_))
-            | Expr::PropagatedFailible(_) => {
+            | Expr::Array(_)
+            | Expr::PropagatedFailible { .. } => {
                 *base_expr = Box::new(Expr::ArrayIndexing {
                     indexed: base_expr.clone(),
                     indexer: expr,
@@ -308,6 +363,7 @@ impl<'s> Expr<'s> {
                 value,
                 target,
                 operator,
+                span: assign_span,
             } => match value.as_mut() {
                 Expr::Binary { rhs, .. } => rhs.add_array_indexing_to_most_rhs(expr),
                 Expr::Id(_)
@@ -316,9 +372,11 @@ impl<'s> Expr<'s> {
                 | Expr::ArrayIndexing { .. }
                 | Expr::Parenthesized(_)
                 | Expr::Value(Value::Str(_))
-                | Expr::PropagatedFailible(_) => {
+                | Expr::Array(_)
+                | Expr::PropagatedFailible { .. } => {
                     *base_expr = Box::new(Expr::Assign {
                         target: target.clone(),
+                        span: assign_span.clone(),
                         operator: operator.clone(),
                         value: Box::new(Expr::ArrayIndexing {
                             indexed: value.clone(),
@@ -377,12 +435,14 @@ impl<'s> Expr<'s> {
         let expr = Box::new(e);
         match base_expr.as_mut() {
             Expr::Value(_)
+            | Expr::Array(_)
+            | Expr::Object(_)
             | Expr::Id(_)
             | Expr::FuncCall { .. }
             | Expr::ObjIndexing { .. }
             | Expr::ArrayIndexing { .. }
             | Expr::Parenthesized(_)
-            | Expr::PropagatedFailible(_) => {
+            | Expr::PropagatedFailible { .. } => {
                 *base_expr = Box::new(Expr::ObjIndexing {
                     indexed: base_expr.clone(),
                     indexer: expr,
@@ -413,17 +473,21 @@ impl<'s> Expr<'s> {
                 value,
                 target,
                 operator,
+                span: assign_span,
             } => match value.as_mut() {
                 Expr::Binary { rhs, .. } => rhs.add_obj_indexing_to_most_rhs(expr),
                 Expr::Value(_)
+                | Expr::Array(_)
+                | Expr::Object(_)
                 | Expr::Id(_)
                 | Expr::FuncCall { .. }
                 | Expr::ObjIndexing { .. }
                 | Expr::ArrayIndexing { .. }
                 | Expr::Parenthesized(_)
-                | Expr::PropagatedFailible(_) => {
+                | Expr::PropagatedFailible { .. } => {
                     *base_expr = Box::new(Expr::Assign {
                         target: target.clone(),
+                        span: assign_span.clone(),
                         operator: operator.clone(),
                         value: Box::new(Expr::ObjIndexing {
                             indexed: value.clone(),
@@ -474,7 +538,7 @@ impl<'s> Expr<'s> {
             | Expr::ObjIndexing { .. }
             | Expr::ArrayIndexing { .. }
             | Expr::Parenthesized(_)
-            | Expr::PropagatedFailible(_) => {
+            | Expr::PropagatedFailible { .. } => {
                 *base_expr = Box::new(Expr::StringConcatenation {
                     left: base_expr.clone(),
                     right: expr,
@@ -491,6 +555,7 @@ impl<'s> Expr<'s> {
                 value,
                 target,
                 operator,
+                span: assign_span,
             } => match value.as_mut() {
                 Expr::Binary { rhs, .. } => rhs.add_string_concatenation_to_most_rhs(expr),
                 Expr::Value(Value::Str(_))
@@ -499,9 +564,10 @@ impl<'s> Expr<'s> {
                 | Expr::ObjIndexing { .. }
                 | Expr::ArrayIndexing { .. }
                 | Expr::Parenthesized(_)
-                | Expr::PropagatedFailible(_) => {
+                | Expr::PropagatedFailible { .. } => {
                     *base_expr = Box::new(Expr::Assign {
                         target: target.clone(),
+                        span: assign_span.clone(),
                         operator: operator.clone(),
                         value: Box::new(Expr::StringConcatenation {
                             left: value.clone(),
@@ -525,28 +591,49 @@ impl<'s> Expr<'s> {
         Ok(())
     }
 
-    /// Function to call after a ':' is consumed when the expression is expected to be a range.
+    /// Function to call after a ':' or a ':<' is consumed when the expression is expected to be a range.
+    ///
+    /// `inclusive` is false when the token is ':<', only the end of a range can be excluded.
     pub fn modify_into_range(
         parser: &mut AspenParser<'s>,
         base_expr: &mut Box<Expr<'s>>,
+        inclusive: bool,
     ) -> AspenResult<()> {
+        let symbol = if inclusive { ":" } else { ":<" };
         let second_expr = Expr::parse(parser)?;
 
-        match base_expr.as_mut() {
-            Expr::Range { start, end, step } => {
-                if step.is_some() {
-                    return Err(AspenError::unknown(
-                        parser,
-                        "token ':' found, a Range has three parts: start:end:step".to_owned(),
-                    ));
-                }
+        /// Adds `second_expr` as the step of `range`.
+        fn add_step<'s>(
+            parser: &mut AspenParser<'s>,
+            range: &mut Expr<'s>,
+            second_expr: Expr<'s>,
+            inclusive: bool,
+        ) -> AspenResult<()> {
+            let Expr::Range { step, .. } = range else {
+                unreachable!("only called on ranges")
+            };
 
-                *base_expr = Box::new(Expr::Range {
-                    start: start.clone(),
-                    end: end.clone(),
-                    step: Some(Box::new(second_expr)),
-                });
+            if step.is_some() {
+                return Err(AspenError::unknown(
+                    parser,
+                    "token ':' found, a Range has three parts: start:end:step".to_owned(),
+                ));
             }
+            // the step is introduced by the second ':' of the range
+            if !inclusive {
+                return Err(AspenError::unknown(
+                    parser,
+                    "token ':<' found, only the end of a Range can be excluded: start:<end:step"
+                        .to_owned(),
+                ));
+            }
+
+            *step = Some(Box::new(second_expr));
+            Ok(())
+        }
+
+        match base_expr.as_mut() {
+            Expr::Range { .. } => add_step(parser, base_expr, second_expr, inclusive)?,
             Expr::Id(_)
             | Expr::Binary { .. }
             | Expr::FuncCall { .. }
@@ -554,37 +641,16 @@ impl<'s> Expr<'s> {
             | Expr::Parenthesized(_)
             | Expr::ObjIndexing { .. }
             | Expr::ArrayIndexing { .. }
-            | Expr::PropagatedFailible(_) => {
+            | Expr::PropagatedFailible { .. } => {
                 *base_expr = Box::new(Expr::Range {
                     start: base_expr.clone(),
                     end: Box::new(second_expr),
                     step: None,
+                    inclusive,
                 });
             }
-            Expr::Assign {
-                ref mut value,
-                operator,
-                target,
-            } => match value.as_mut() {
-                Expr::Range { start, end, step } => {
-                    if step.is_some() {
-                        return Err(AspenError::unknown(
-                            parser,
-                            "token ':' found, a Range has three parts: start:end:step".to_owned(),
-                        ));
-                    }
-
-                    *base_expr = Box::new(Expr::Assign {
-                        target: target.clone(),
-                        operator: operator.clone(),
-                        value: Expr::Range {
-                            start: start.clone(),
-                            end: end.clone(),
-                            step: Some(Box::new(second_expr)),
-                        }
-                        .into(),
-                    });
-                }
+            Expr::Assign { ref mut value, .. } => match value.as_mut() {
+                Expr::Range { .. } => add_step(parser, value, second_expr, inclusive)?,
                 Expr::Id(_)
                 | Expr::Binary { .. }
                 | Expr::FuncCall { .. }
@@ -592,21 +658,27 @@ impl<'s> Expr<'s> {
                 | Expr::Parenthesized(_)
                 | Expr::ObjIndexing { .. }
                 | Expr::ArrayIndexing { .. }
-                | Expr::PropagatedFailible(_) => {
-                    *base_expr = Box::new(Expr::Assign {
-                        target: target.clone(),
-                        operator: operator.clone(),
-                        value: Expr::Range {
-                            start: value.clone(),
-                            end: Box::new(second_expr),
-                            step: None,
-                        }
-                        .into(),
-                    });
+                | Expr::PropagatedFailible { .. } => {
+                    **value = Expr::Range {
+                        start: value.clone(),
+                        end: Box::new(second_expr),
+                        step: None,
+                        inclusive,
+                    };
+                }
+                _ => {
+                    return Err(AspenError::unknown(
+                        parser,
+                        format!("token '{}' found", symbol),
+                    ))
                 }
-                _ => return Err(AspenError::unknown(parser, "token ':' found".to_owned())),
             },
-            _ => return Err(AspenError::unknown(parser, "token ':' found".to_owned())),
+            _ => {
+                return Err(AspenError::unknown(
+                    parser,
+                    format!("token '{}' found", symbol),
+                ))
+            }
         };
 
         Ok(())
@@ -614,12 +686,20 @@ impl<'s> Expr<'s> {
 
     /// Function to call after a [`BinaryOperator`] is consumed when the expression is expected to be a binary operation.
     pub fn modify_into_binary_op(
-        parser: &mut AspenParser,
         base_expr: &mut Box<Expr<'s>>,
         right_expr: Expr<'s>,
         bop: BinaryOperator,
     ) -> AspenResult<()> {
         match base_expr.as_mut() {
+            // a range has the lowest precedence, the operation applies to its last part
+            Expr::Range {
+                ref mut end,
+                ref mut step,
+                ..
+            } => match step {
+                Some(step) => Expr::modify_into_binary_op(step, right_expr, bop)?,
+                None => Expr::modify_into_binary_op(end, right_expr, bop)?,
+            },
             Expr::Binary { lhs, operator, rhs } => {
                 let result = operator.get_precedence().cmp(&bop.get_precedence());
                 match result {
@@ -648,6 +728,7 @@ impl<'s> Expr<'s> {
                 target,
                 operator: aop,
                 value,
+                span: assign_span,
             } => match value.as_mut() {
                 Expr::Binary { rhs, operator, lhs } => {
                     let result = operator.get_precedence().cmp(&bop.get_precedence());
@@ -655,6 +736,7 @@ impl<'s> Expr<'s> {
                         Ordering::Greater => {
                             *base_expr = Expr::Assign {
                                 target: target.clone(),
+                                span: assign_span.clone(),
                                 operator: aop.clone(),
                                 value: Box::new(Expr::Binary {
                                     lhs: base_expr.clone(),
@@ -667,6 +749,7 @@ impl<'s> Expr<'s> {
                         Ordering::Equal | Ordering::Less => {
                             *base_expr = Expr::Assign {
                                 target: target.clone(),
+                                span: assign_span.clone(),
                                 operator: aop.clone(),
                                 value: Box::new(
                                     Expr::Binary {
@@ -688,6 +771,7 @@ impl<'s> Expr<'s> {
                 _ => {
                     *base_expr = Expr::Assign {
                         target: target.clone(),
+                        span: assign_span.clone(),
                         operator: aop.clone(),
                         value: Box::new(Expr::Binary {
                             lhs: value.clone(),
@@ -727,8 +811,16 @@ impl<'s> Expr<'s> {
         let mut base_expr = Box::new(Expr::parse(parser)?);
         let mut bop: Option<BinaryOperator> = None; // bop for binary operator
 
+        // a newline can only end the expression if it is a stop token
+        let stops_on_newline = stop_tokens.contains(&Token::Newline);
+
         loop {
-            match next_jump_multispace(parser)? {
+            let token = match stops_on_newline {
+                true => next_jump_space(parser)?,
+                false => next_jump_multispace(parser)?,
+            };
+
+            match token {
                 Token::LineComment(val) | Token::DocComment(val) | Token::MultiLineComment(val) => {
                     let start = parser.lexer.span().start;
                     let end = parser.lexer.span().end;
@@ -748,6 +840,10 @@ impl<'s> Expr<'s> {
                         Expr::modify_into_string_concatenation(parser, &mut base_expr)?
                     }
                     token if stop_tokens.contains(&token) => return Ok((base_expr, token)),
+                    Token::Range => Expr::modify_into_range(parser, &mut base_expr, true)?,
+                    Token::ExclusiveRange => {
+                        Expr::modify_into_range(parser, &mut base_expr, false)?
+                    }
                     _ => {
                         return Err(AspenError::unknown(
                             parser,
@@ -757,12 +853,7 @@ impl<'s> Expr<'s> {
                 },
                 token if bop.is_some() => {
                     let right_expr = Expr::parse_with_token(parser, token)?;
-                    Expr::modify_into_binary_op(
-                        parser,
-                        &mut base_expr,
-                        right_expr,
-                        bop.take().unwrap(),
-                    )?;
+                    Expr::modify_into_binary_op(&mut base_expr, right_expr, bop.take().unwrap())?;
                 }
                 _ => unreachable!(),
             }
@@ -818,7 +909,6 @@ fn parse_array<'s>(parser: &mut AspenParser<'s>) -> AspenResult<Vec<Box<Expr<'s>
                         token if bop.is_some() => {
                             let right_expr = Expr::parse_with_token(parser, token)?;
                             Expr::modify_into_binary_op(
-                                parser,
                                 base_expr,
                                 right_expr,
                                 bop.take().unwrap(),
@@ -839,9 +929,9 @@ fn parse_array<'s>(parser: &mut AspenParser<'s>) -> AspenResult<Vec<Box<Expr<'s>
 /// Parses an object.
 ///
 /// **NOTE: We assume "{" was already consumed!**
-fn parse_obj<'s>(parser: &mut AspenParser<'s>) -> AspenResult<HashMap<&'s str, Box<Expr<'s>>>> {
+fn parse_obj<'s>(parser: &mut AspenParser<'s>) -> AspenResult<IndexMap<&'s str, Box<Expr<'s>>>> {
     // we could simplify this function using Expr::parser_until, maybe in the future ?
-    let mut hash = HashMap::new();
+    let mut hash = IndexMap::new();
     let mut key = None;
     let mut value = None;
 
@@ -895,7 +985,7 @@ fn parse_obj<'s>(parser: &mut AspenParser<'s>) -> AspenResult<HashMap<&'s str, B
             Token::BinaryOperator(op) if key.is_some() => {
                 let expr = Expr::parse(parser)?;
                 let mut val = value.take().unwrap();
-                Expr::modify_into_binary_op(parser, &mut val, expr, op)?;
+                Expr::modify_into_binary_op(&mut val, expr, op)?;
                 value = Some(val);
             }
             Token::SpreadOperator if key.is_some() => return Err(AspenError::expected(
@@ -953,8 +1043,8 @@ impl<'a> From<Vec<Box<Expr<'a>>>> for Expr<'a> {
     }
 }
 
-impl<'a> From<HashMap<&'a str, Box<Expr<'a>>>> for Expr<'a> {
-    fn from(val: HashMap<&'a str, Box<Expr<'a>>>) -> Self {
+impl<'a> From<IndexMap<&'a str, Box<Expr<'a>>>> for Expr<'a> {
+    fn from(val: IndexMap<&'a str, Box<Expr<'a>>>) -> Self {
         Expr::Object(val)
     }
 }
@@ -995,10 +1085,12 @@ impl<'a> fmt::Display for Expr<'a> {
             Expr::Id(s) => write!(f, "{}", s),
             Expr::SpeadId(s) => write!(f, "...{}", s),
             Expr::Parenthesized(expr) => write!(f, "({})", expr),
+            Expr::BitNot(expr) => write!(f, "~{}", expr),
             Expr::Assign {
                 target,
                 operator,
                 value,
+                ..
             } => {
                 write!(f, "{} {} {}", target, operator, value)
             }
@@ -1015,10 +1107,15 @@ impl<'a> fmt::Display for Expr<'a> {
                 }
                 write!(f, ")")
             }
-            Expr::Range { start, end, step } => {
-                write!(f, "{}..{}", start, end)?;
+            Expr::Range {
+                start,
+                end,
+                step,
+                inclusive,
+            } => {
+                write!(f, "{}:{}{}", start, if *inclusive { "" } else { "<" }, end)?;
                 if let Some(step) = step {
-                    write!(f, "..{}", step)?;
+                    write!(f, ":{}", step)?;
                 }
                 Ok(())
             }
@@ -1031,9 +1128,176 @@ impl<'a> fmt::Display for Expr<'a> {
             Expr::StringConcatenation { left, right } => {
                 write!(f, "{}..{}", left, right)
             }
-            Expr::PropagatedFailible(expr) => {
+            Expr::PropagatedFailible { value: expr, .. } => {
                 write!(f, "{}?", expr)
             }
+            Expr::Formatted { value, spec } => {
+                write!(f, "{{{}", value)?;
+                if let Some(spec) = spec {
+                    write!(f, ":{}", spec)?;
+                }
+                write!(f, "}}")
+            }
+            Expr::If(if_expr) => {
+                write!(f, "if {} {{ ... }}", if_expr.condition)?;
+                if if_expr.other.is_some() {
+                    write!(f, " else {{ ... }}")?;
+                }
+                Ok(())
+            }
+            Expr::Match(Match { value, arms }) => {
+                write!(f, "match {} {{ ", value)?;
+                for (i, MatchArm { pattern, guard, .. }) in arms.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", pattern)?;
+                    if let Some(guard) = guard {
+                        write!(f, " if {}", guard)?;
+                    }
+                    write!(f, " => ...")?;
+                }
+                write!(f, " }}")
+            }
+            Expr::Try(try_expr) => match try_expr.binding {
+                Some(name) => write!(f, "try {{ ... }} catch {} {{ ... }}", name),
+                None => write!(f, "try {{ ... }} catch {{ ... }}"),
+            },
+            Expr::Loop(stmt) => match stmt.as_ref() {
+                Statement::For(For {
+                    label,
+                    args,
+                    indexed,
+                    ..
+                }) => {
+                    if let Some(label) = label {
+                        write!(f, "#{} ", label)?;
+                    }
+                    write!(f, "for {} in {} {{ ... }}", args, indexed)
+                }
+                Statement::While(While {
+                    label, condition, ..
+                }) => {
+                    if let Some(label) = label {
+                        write!(f, "#{} ", label)?;
+                    }
+                    write!(f, "while {} {{ ... }}", condition)
+                }
+                _ => unreachable!("only loops are expressions"),
+            },
         }
     }
 }
+
+impl<'a> fmt::Display for Pattern<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pattern::Wildcard => write!(f, "_"),
+            Pattern::Binding(name) | Pattern::Type(name) => write!(f, "{}", name),
+            Pattern::Literal(value) => write!(f, "{:?}", value),
+            Pattern::Range {
+                start,
+                end,
+                inclusive,
+            } => write!(
+                f,
+                "{:?}:{}{:?}",
+                start,
+                if *inclusive { "" } else { "<" },
+                end
+            ),
+            Pattern::Array { elements, rest } => {
+                write!(f, "[")?;
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", element)?;
+                }
+                if let Some(rest) = rest {
+                    if !elements.is_empty() {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "...{}", rest)?;
+                }
+                write!(f, "]")
+            }
+            Pattern::Object(properties) => {
+                write!(f, "{{")?;
+                for (i, (key, pattern)) in properties.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}! {}", key, pattern)?;
+                }
+                write!(f, "}}")
+            }
+            Pattern::Variant {
+                enum_name,
+                name,
+                payload,
+            } => {
+                if let Some(enum_name) = enum_name {
+                    write!(f, "{}.", enum_name)?;
+                }
+                write!(f, "{}", name)?;
+                if let Some(payload) = payload {
+                    write!(f, "(")?;
+                    for (i, pattern) in payload.iter().enumerate() {
+                        if i > 0 {
+                            write!(f, ", ")?;
+                        }
+                        write!(f, "{}", pattern)?;
+                    }
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<'a> fmt::Display for Variables<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (open, close, elements, rest) = match self {
+            Variables::Unique(name) => return write!(f, "{}", name),
+            Variables::Destructuring { elements, rest } => ("(", ")", elements, rest),
+            Variables::Array { elements, rest } => ("[", "]", elements, rest),
+            Variables::Object(properties) => {
+                write!(f, "{{")?;
+                for (i, (key, element)) in properties.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}! {}", key, element)?;
+                }
+                return write!(f, "}}");
+            }
+        };
+
+        write!(f, "{}", open)?;
+        for (i, element) in elements.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", element)?;
+        }
+        if let Some(rest) = rest {
+            if !elements.is_empty() {
+                write!(f, ", ")?;
+            }
+            write!(f, "...{}", rest)?;
+        }
+        write!(f, "{}", close)
+    }
+}
+
+impl<'a> fmt::Display for Destructured<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.variables)?;
+        if let Some(default) = &self.default {
+            write!(f, ": {}", default)?;
+        }
+        Ok(())
+    }
+}
@@ -1,14 +1,17 @@
 use super::{
     error::{AspenError, AspenResult},
     parse_block,
-    utils::{expect_space, next_jump_multispace, Block},
+    utils::{expect_space, Block},
+    var::Variables,
     Expr, Statement,
 };
 use crate::parser::{AspenParser, Token};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct For<'s> {
-    pub args: Vec<&'s str>,
+    /// Name given with `#label for ...`, targeted by `break #label` and `continue #label`.
+    pub label: Option<&'s str>,
+    pub args: Variables<'s>,
     pub indexed: Box<Expr<'s>>,
     pub body: Box<Block<'s>>,
 }
@@ -17,13 +20,24 @@ impl<'s> For<'s> {
     /// Parses a for loop.
     ///
     /// **NOTE: We assume "for" is already consumed by the parser!**
-    pub fn parse(parser: &mut AspenParser<'s>) -> AspenResult<Statement<'s>> {
+    pub fn parse(
+        parser: &mut AspenParser<'s>,
+        label: Option<&'s str>,
+    ) -> AspenResult<Statement<'s>> {
         expect_space(parser)?;
+        let start = parser.lexer.span().end;
         let args = Self::parse_args(parser)?;
+        let args_span = start..parser.lexer.span().start;
         let (indexed, _) = Expr::parse_until(parser, &[Token::OpenBrace])?;
+
+        parser.loops.push(label);
+        parser.push_implicit_names(args.names(), args_span);
         let body = Box::new(parse_block(parser, Some(Token::CloseBrace))?);
+        parser.pop_implicit_names();
+        parser.loops.pop();
 
         Ok(For {
+            label,
             args,
             indexed,
             body,
@@ -31,33 +45,23 @@ impl<'s> For<'s> {
         .into())
     }
 
-    /// Parses the arguments of a for loop.
+    /// Parses the arguments of a for loop, several arguments destructure each value.
     ///
-    /// **NOTE: This function also parses the following "->" Token!**
-    fn parse_args(parser: &mut AspenParser<'s>) -> AspenResult<Vec<&'s str>> {
-        let mut args = vec![];
-        let mut awaits_arg = true;
-
-        loop {
-            let token = next_jump_multispace(parser)?;
+    /// **NOTE: This function also parses the following "in" Token!**
+    fn parse_args(parser: &mut AspenParser<'s>) -> AspenResult<Variables<'s>> {
+        let (mut elements, rest) = Variables::parse_elements(parser, Token::In)?;
 
-            match token {
-                Token::Identifier(value) if awaits_arg => {
-                    args.push(value);
-                    awaits_arg = false
-                }
-                Token::In => break,
-                Token::Comma if !awaits_arg => awaits_arg = true,
-                _ => {
-                    return Err(AspenError::expected(
-                        parser,
-                        "a valid argument or '->'".to_owned(),
-                    ))
-                }
-            };
+        if elements.is_empty() && rest.is_none() {
+            return Err(AspenError::expected(
+                parser,
+                "a valid argument before 'in'".to_owned(),
+            ));
         }
 
-        Ok(args)
+        match (elements.len(), &rest) {
+            (1, None) if elements[0].default.is_none() => Ok(elements.remove(0).variables),
+            _ => Ok(Variables::Destructuring { elements, rest }),
+        }
     }
 }
 
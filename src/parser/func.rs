@@ -2,7 +2,8 @@ use super::{
     comment::Comment,
     error::{AspenError, AspenResult},
     parse_block,
-    utils::{next_jump_multispace, next_token, Block},
+    utils::{next_jump_multispace, next_token, peek_jump_multispace, Block},
+    var::Variables,
     Expr, Statement,
 };
 use crate::{lexer::Token, parser::AspenParser};
@@ -19,7 +20,8 @@ crate::impl_from_for!(Func, Statement);
 #[derive(Debug, Clone, PartialEq)]
 pub struct Argument<'s> {
     pub is_spread: bool,
-    pub identifier: &'s str,
+    /// The name of the argument, or the destructuring of its value.
+    pub variables: Variables<'s>,
     pub base_value: Option<Box<Expr<'s>>>,
 }
 
@@ -29,7 +31,22 @@ impl<'s> Func<'s> {
     /// **NOTE: We assume the function name is already consumed by the parser!**
     pub fn parse(parser: &mut AspenParser<'s>, name: &'s str) -> AspenResult<Statement<'s>> {
         let arguments = Func::parse_declaration_args(parser)?;
+
+        parser.declare_names([name], None);
+
+        // the loops around the declaration cannot be exited from inside the function,
+        // and its arguments can have the name of a constant declared around it
+        let loops = std::mem::take(&mut parser.loops);
+        parser.names.push(
+            arguments
+                .iter()
+                .flat_map(|arg| arg.variables.names())
+                .map(|name| (name, None))
+                .collect(),
+        );
         let body = Box::new(parse_block(parser, Some(Token::CloseBrace))?);
+        parser.names.pop();
+        parser.loops = loops;
 
         Ok(Func {
             name,
@@ -59,10 +76,12 @@ impl<'s> Func<'s> {
                     args.push(Box::new(expr));
                     awaits_arg = false
                 }
-                Token::Range if !awaits_arg => {
+                token @ (Token::Range | Token::ExclusiveRange) if !awaits_arg => {
+                    let inclusive = token == Token::Range;
+
                     // condition is sure to be true
                     if let Some(expr) = args.last_mut() {
-                        Expr::modify_into_range(parser, expr)?;
+                        Expr::modify_into_range(parser, expr, inclusive)?;
                     }
                 }
                 Token::Dot if !awaits_arg => {
@@ -89,6 +108,13 @@ impl<'s> Func<'s> {
                         Expr::modify_into_fn_call(parser, expr)?;
                     }
                 }
+                Token::BinaryOperator(bop) if !awaits_arg => {
+                    let right_expr = Expr::parse(parser)?;
+                    // condition is sure to be true
+                    if let Some(expr) = args.last_mut() {
+                        Expr::modify_into_binary_op(expr, right_expr, bop)?;
+                    }
+                }
                 Token::Comma if !awaits_arg => awaits_arg = true,
                 _ => {
                     return Err(AspenError::expected(
@@ -114,7 +140,6 @@ impl<'s> Func<'s> {
             let token = next_jump_multispace(parser)?;
 
             match token {
-                Token::OpenBrace => break,
                 // token is called range but it is just ':'
                 Token::Range if !awaits_arg => {
                     if let Some(val) = args.last_mut() {
@@ -143,25 +168,17 @@ impl<'s> Func<'s> {
                         }
                     }
                 }
-                Token::Identifier(value) if awaits_arg => {
-                    if spread_count > 0 {
-                        return Err(AspenError::unknown(
-                            parser,
-                            format!("spread argument, a spread argument can only be defined at the end of the arguments list"),
-                        ));
-                    }
-
-                    if args.iter().any(|arg| arg.identifier == value) {
-                        return Err(AspenError::unknown(
-                            parser,
-                            format!(
-                                "argument '{}', function already possesses such an identifier",
-                                value
-                            ),
-                        ));
-                    }
-
-                    args.push(value.into());
+                // a body cannot start with a property, so '{' followed by one destructures an object
+                Token::OpenBrace
+                    if awaits_arg
+                        && matches!(peek_jump_multispace(parser), Some(Token::ObjectKey(_))) =>
+                {
+                    Self::push_declaration_arg(parser, &mut args, token, spread_count)?;
+                    awaits_arg = false
+                }
+                Token::OpenBrace => break,
+                Token::Identifier(_) | Token::OpenBracket | Token::OpenParen if awaits_arg => {
+                    Self::push_declaration_arg(parser, &mut args, token, spread_count)?;
                     awaits_arg = false
                 }
                 Token::SpreadOperator if awaits_arg => {
@@ -176,7 +193,10 @@ impl<'s> Func<'s> {
 
                     match next_token {
                         Token::Identifier(value) => {
-                            if args.iter().any(|arg| arg.identifier == value) {
+                            if args
+                                .iter()
+                                .any(|arg| arg.variables.names().contains(&value))
+                            {
                                 return Err(AspenError::unknown(
                                     parser,
                                     format!(
@@ -210,13 +230,50 @@ impl<'s> Func<'s> {
 
         Ok(args)
     }
+
+    /// Parses an argument starting with `token`, a name or a destructuring, and adds it to `args`.
+    fn push_declaration_arg(
+        parser: &mut AspenParser<'s>,
+        args: &mut Vec<Argument<'s>>,
+        token: Token<'s>,
+        spread_count: u8,
+    ) -> AspenResult<()> {
+        if spread_count > 0 {
+            return Err(AspenError::unknown(
+                parser,
+                "spread argument, a spread argument can only be defined at the end of the arguments list".to_owned(),
+            ));
+        }
+
+        let variables = Variables::parse(parser, token)?;
+
+        for name in variables.names() {
+            if args.iter().any(|arg| arg.variables.names().contains(&name)) {
+                return Err(AspenError::unknown(
+                    parser,
+                    format!(
+                        "argument '{}', function already possesses such an identifier",
+                        name
+                    ),
+                ));
+            }
+        }
+
+        args.push(Argument {
+            is_spread: false,
+            variables,
+            base_value: None,
+        });
+
+        Ok(())
+    }
 }
 
 impl<'s> Argument<'s> {
     pub fn new(identifier: &'s str, is_spread: bool) -> Self {
         Self {
             is_spread,
-            identifier,
+            variables: Variables::Unique(identifier),
             base_value: None,
         }
     }
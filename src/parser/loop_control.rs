@@ -0,0 +1,182 @@
+use super::{
+    error::{AspenError, AspenResult},
+    for_loop::For,
+    utils::{next_jump_multispace, next_jump_space, peek_jump_space},
+    while_loop::While,
+    AspenParser, Expr, Statement,
+};
+use crate::lexer::Token;
+
+/// A `break` statement, optionally targeting a labelled loop and giving it a value: `break #outer value`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Break<'s> {
+    pub label: Option<&'s str>,
+    /// The value of the loop when it is used as an expression, nil if not given.
+    pub value: Option<Box<Expr<'s>>>,
+}
+crate::impl_from_for!(Break, Statement);
+
+/// A `continue` statement, optionally targeting a labelled loop: `continue #outer`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Continue<'s> {
+    pub label: Option<&'s str>,
+}
+crate::impl_from_for!(Continue, Statement);
+
+impl<'s> Break<'s> {
+    /// Parses a break statement.
+    ///
+    /// **NOTE: We assume "break" is already consumed by the lexer!**
+    pub fn parse(parser: &mut AspenParser<'s>) -> AspenResult<Statement<'s>> {
+        let label = parse_target(parser, "break")?;
+
+        let value = match peek_jump_space(parser) {
+            None
+            | Some(
+                Token::Newline
+                | Token::SemiColon
+                | Token::CloseBrace
+                | Token::LineComment(_)
+                | Token::DocComment(_)
+                | Token::MultiLineComment(_),
+            ) => None,
+            Some(_) => Some(Box::new(Expr::parse(parser)?)),
+        };
+
+        Ok(Break { label, value }.into())
+    }
+}
+
+impl<'s> Continue<'s> {
+    /// Parses a continue statement.
+    ///
+    /// **NOTE: We assume "continue" is already consumed by the lexer!**
+    pub fn parse(parser: &mut AspenParser<'s>) -> AspenResult<Statement<'s>> {
+        let label = parse_target(parser, "continue")?;
+
+        Ok(Continue { label }.into())
+    }
+}
+
+/// Parses the optional label following a `break` or a `continue`,
+/// and ensures that the loop it targets surrounds it.
+fn parse_target<'s>(parser: &mut AspenParser<'s>, keyword: &str) -> AspenResult<Option<&'s str>> {
+    let label = match peek_jump_space(parser) {
+        Some(Token::Label(label)) => {
+            next_jump_space(parser)?;
+            Some(label)
+        }
+        _ => None,
+    };
+
+    match label {
+        None if parser.loops.is_empty() => Err(AspenError::unknown(
+            parser,
+            format!("token '{}' found outside of a loop", keyword),
+        )),
+        Some(label) if !parser.loops.contains(&Some(label)) => Err(AspenError::unknown(
+            parser,
+            format!(
+                "token '{}' found, there is no loop labelled '#{}' around it",
+                keyword, label
+            ),
+        )),
+        _ => Ok(label),
+    }
+}
+
+/// Parses the loop following a label.
+///
+/// **NOTE: We assume the label is already consumed by the lexer!**
+pub fn parse_labelled_loop<'s>(
+    parser: &mut AspenParser<'s>,
+    label: &'s str,
+) -> AspenResult<Statement<'s>> {
+    match next_jump_multispace(parser)? {
+        Token::For => For::parse(parser, Some(label)),
+        Token::While => While::parse(parser, Some(label)),
+        _ => Err(AspenError::expected(
+            parser,
+            format!("a 'for' or a 'while' loop after the label '#{}'", label),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{try_parse, value_of};
+
+    #[test]
+    fn break_and_continue_the_innermost_loop() {
+        let code = r#"
+let odds []
+for i in 0:10 {
+    if i == 7 {
+        break
+    }
+    if i % 2 == 0 {
+        continue
+    }
+    odds.push(i)
+}
+let n 0
+while true {
+    n = n + 1
+    if n == 3 {
+        break
+    }
+}
+"#;
+
+        assert_eq!(value_of(code, "odds"), "[1, 3, 5]");
+        assert_eq!(value_of(code, "n"), "3");
+    }
+
+    #[test]
+    fn labels_target_an_outer_loop() {
+        let code = r#"
+let pairs []
+#outer for i in 1:3 {
+    for j in 1:3 {
+        if j == 2 {
+            continue #outer
+        }
+        if i == 3 {
+            break #outer
+        }
+        pairs.push([i, j])
+    }
+}
+"#;
+
+        assert_eq!(value_of(code, "pairs"), "[[1, 1], [2, 1]]");
+    }
+
+    #[test]
+    fn break_gives_a_value_to_the_loop() {
+        let code = r#"
+let found for x in [3, 8, 12] {
+    if x > 5 {
+        break x
+    }
+}
+let missing for x in [1, 2] {
+    if x > 5 {
+        break x
+    }
+}
+"#;
+
+        assert_eq!(value_of(code, "found"), "8");
+        assert_eq!(value_of(code, "missing"), "nil");
+    }
+
+    #[test]
+    fn loop_control_outside_of_its_loop_is_rejected() {
+        assert!(try_parse("break\n").is_err());
+        assert!(try_parse("continue\n").is_err());
+        assert!(try_parse("for i in 0:1 {\n    break #outer\n}\n").is_err());
+        assert!(try_parse("for i in 0:1 {\n    @f {\n        break\n    }\n}\n").is_err());
+        assert!(try_parse("#outer let x 1\n").is_err());
+    }
+}
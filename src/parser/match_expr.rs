@@ -0,0 +1,427 @@
+use super::{
+    comment::Comment,
+    error::{AspenError, AspenResult, AspenWarning},
+    parse_block,
+    utils::{next_jump_multispace, next_jump_space, peek_jump_space, Block},
+    value::{parse_value, Value},
+    AspenParser, Expr, Statement,
+};
+use crate::lexer::Token;
+
+/// A `match value { pattern => expr, ... }` expression, evaluates to the value of the first matching arm.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match<'s> {
+    pub value: Box<Expr<'s>>,
+    pub arms: Vec<MatchArm<'s>>,
+}
+
+/// A `pattern if guard => body` arm of a match.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchArm<'s> {
+    pub pattern: Pattern<'s>,
+    pub guard: Option<Box<Expr<'s>>>,
+    pub body: Block<'s>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern<'s> {
+    /// `_`, matches any value.
+    Wildcard,
+    /// A name, matches any value and binds it to the name.
+    Binding(&'s str),
+    /// A value equal to a literal: `1`, `"hello"`, `true`, `nil`.
+    Literal(Value<'s>),
+    /// A value contained in a range: `1:10` or `'a':<'z'`.
+    Range {
+        start: Value<'s>,
+        end: Value<'s>,
+        inclusive: bool,
+    },
+    /// An array whose elements match the patterns: `[a, b]`, or `[a, ...rest]` to bind the remaining elements.
+    Array {
+        elements: Vec<Pattern<'s>>,
+        rest: Option<&'s str>,
+    },
+    /// An object having the properties: `{name!, age! 18}`, a property without pattern is bound to its name.
+    Object(Vec<(&'s str, Pattern<'s>)>),
+    /// A value of a type, written with a capitalized name: `Int`, `String`, `Err`...
+    Type(&'s str),
+    /// A value of an enum variant: `Shape.Circle(r)`, or `Circle(r)` for the variant of any enum.
+    ///
+    /// `Shape.Circle` matches a circle whatever its payload, `Err(m)` binds the message of an error.
+    Variant {
+        enum_name: Option<&'s str>,
+        name: &'s str,
+        payload: Option<Vec<Pattern<'s>>>,
+    },
+}
+
+impl<'s> Match<'s> {
+    /// Parses a match expression.
+    ///
+    /// **NOTE: We assume "match" is already consumed by the parser!**
+    pub fn parse(parser: &mut AspenParser<'s>) -> AspenResult<Expr<'s>> {
+        let span = parser.lexer.span();
+        let (value, _) = Expr::parse_until(parser, &[Token::OpenBrace])?;
+        let mut arms = vec![];
+
+        loop {
+            match next_jump_multispace(parser)? {
+                Token::CloseBrace => break,
+                Token::Comma if !arms.is_empty() => (),
+                Token::LineComment(val) | Token::DocComment(val) | Token::MultiLineComment(val) => {
+                    let start = parser.lexer.span().start;
+                    let end = parser.lexer.span().end;
+                    parser.add_comment(Comment::new(val, start, end))
+                }
+                token => {
+                    let (arm, is_last) = MatchArm::parse(parser, token)?;
+                    arms.push(arm);
+
+                    if is_last {
+                        break;
+                    }
+                }
+            }
+        }
+
+        if !arms.iter().any(MatchArm::is_catch_all) {
+            parser.add_warning(AspenWarning::new(
+                "match without a wildcard arm".to_owned(),
+                "Add a '_ => ...' arm, a value matching no arm is an error".to_owned(),
+                span,
+            ));
+        }
+
+        Ok(Expr::Match(Match { value, arms }))
+    }
+}
+
+impl<'s> MatchArm<'s> {
+    /// Parses an arm starting with `token`, returns true as well if the match ends after it.
+    fn parse(parser: &mut AspenParser<'s>, token: Token<'s>) -> AspenResult<(Self, bool)> {
+        let start = parser.lexer.span().start;
+        let pattern = Pattern::parse(parser, token)?;
+        parser.push_implicit_names(pattern.names(), start..parser.lexer.span().end);
+
+        let guard = match next_jump_space(parser)? {
+            Token::If => Some(Expr::parse_until(parser, &[Token::FatArrow])?.0),
+            Token::FatArrow => None,
+            _ => {
+                return Err(AspenError::expected(
+                    parser,
+                    "'=>' after a pattern".to_owned(),
+                ))
+            }
+        };
+
+        let (body, is_last) = match peek_jump_space(parser) {
+            Some(Token::OpenBrace) => {
+                next_jump_space(parser)?;
+                (parse_block(parser, Some(Token::CloseBrace))?, false)
+            }
+            _ => {
+                let (value, stop_token) =
+                    Expr::parse_until(parser, &[Token::Newline, Token::Comma, Token::CloseBrace])?;
+                let body = Block::new(vec![Box::new(Statement::Expr {
+                    value,
+                    is_returned: true,
+                })]);
+
+                (body, stop_token == Token::CloseBrace)
+            }
+        };
+
+        parser.pop_implicit_names();
+
+        Ok((
+            MatchArm {
+                pattern,
+                guard,
+                body,
+            },
+            is_last,
+        ))
+    }
+
+    /// Returns true if the arm matches every value.
+    fn is_catch_all(&self) -> bool {
+        self.guard.is_none() && matches!(self.pattern, Pattern::Wildcard | Pattern::Binding(_))
+    }
+}
+
+impl<'s> Pattern<'s> {
+    /// Returns the names bound by the pattern when it matches.
+    pub fn names(&self) -> Vec<&'s str> {
+        match self {
+            Pattern::Binding(name) => vec![name],
+            Pattern::Array { elements, rest } => elements
+                .iter()
+                .flat_map(Pattern::names)
+                .chain(*rest)
+                .collect(),
+            Pattern::Object(properties) => properties
+                .iter()
+                .flat_map(|(_, pattern)| pattern.names())
+                .collect(),
+            Pattern::Variant {
+                payload: Some(payload),
+                ..
+            } => payload.iter().flat_map(Pattern::names).collect(),
+            Pattern::Wildcard
+            | Pattern::Literal(_)
+            | Pattern::Range { .. }
+            | Pattern::Type(_)
+            | Pattern::Variant { payload: None, .. } => vec![],
+        }
+    }
+
+    /// Parses a pattern starting with `token`.
+    fn parse(parser: &mut AspenParser<'s>, token: Token<'s>) -> AspenResult<Self> {
+        let pattern = match token {
+            Token::Identifier("_") => Pattern::Wildcard,
+            Token::Identifier(name) if name.starts_with(char::is_uppercase) => {
+                Self::parse_type_or_variant(parser, name)?
+            }
+            Token::Identifier(name) => Pattern::Binding(name),
+            Token::OpenBracket => Self::parse_array(parser)?,
+            Token::OpenBrace => Self::parse_object(parser)?,
+            token => {
+                let value = parse_value(parser, token)?;
+
+                match peek_jump_space(parser) {
+                    Some(token @ (Token::Range | Token::ExclusiveRange)) => {
+                        next_jump_space(parser)?;
+                        let token_after = next_jump_space(parser)?;
+                        let end = parse_value(parser, token_after)?;
+
+                        Pattern::Range {
+                            start: value,
+                            end,
+                            inclusive: token == Token::Range,
+                        }
+                    }
+                    _ => Pattern::Literal(value),
+                }
+            }
+        };
+
+        Ok(pattern)
+    }
+
+    /// Parses a pattern starting with a capitalized name: a type, `Shape.Circle(r)` or `Circle(r)`.
+    fn parse_type_or_variant(parser: &mut AspenParser<'s>, name: &'s str) -> AspenResult<Self> {
+        let (enum_name, name) = match peek_jump_space(parser) {
+            Some(Token::Dot) => {
+                next_jump_space(parser)?;
+                match next_jump_space(parser)? {
+                    Token::Identifier(variant) if variant.starts_with(char::is_uppercase) => {
+                        (Some(name), variant)
+                    }
+                    _ => {
+                        return Err(AspenError::expected(
+                            parser,
+                            format!("a variant of '{}' after the '.'", name),
+                        ))
+                    }
+                }
+            }
+            Some(Token::OpenParen) => (None, name),
+            _ => return Ok(Pattern::Type(name)),
+        };
+
+        let payload = match peek_jump_space(parser) {
+            Some(Token::OpenParen) => {
+                next_jump_space(parser)?;
+                Some(Self::parse_payload(parser)?)
+            }
+            _ => None,
+        };
+
+        Ok(Pattern::Variant {
+            enum_name,
+            name,
+            payload,
+        })
+    }
+
+    /// Parses the patterns of the payload of a variant.
+    ///
+    /// **NOTE: We assume "(" was already consumed!**
+    fn parse_payload(parser: &mut AspenParser<'s>) -> AspenResult<Vec<Self>> {
+        let mut payload = vec![];
+
+        loop {
+            match next_jump_multispace(parser)? {
+                Token::CloseParen if payload.is_empty() => break,
+                token => payload.push(Self::parse(parser, token)?),
+            }
+
+            match next_jump_multispace(parser)? {
+                Token::Comma => (),
+                Token::CloseParen => break,
+                _ => return Err(AspenError::expected(parser, "a ',' or a ')'".to_owned())),
+            }
+        }
+
+        Ok(payload)
+    }
+
+    /// Parses an array pattern.
+    ///
+    /// **NOTE: We assume "[" was already consumed!**
+    fn parse_array(parser: &mut AspenParser<'s>) -> AspenResult<Self> {
+        let mut elements = vec![];
+        let mut rest = None;
+        let mut awaits_comma = false;
+
+        loop {
+            match next_jump_multispace(parser)? {
+                Token::CloseBracket => break,
+                Token::Comma if awaits_comma => awaits_comma = false,
+                Token::SpreadOperator if !awaits_comma && rest.is_none() => {
+                    match next_jump_space(parser)? {
+                        Token::Identifier(name) => rest = Some(name),
+                        _ => {
+                            return Err(AspenError::expected(
+                                parser,
+                                "an identifier following the '...'".to_owned(),
+                            ))
+                        }
+                    }
+                    awaits_comma = true;
+                }
+                token if !awaits_comma && rest.is_none() => {
+                    elements.push(Self::parse(parser, token)?);
+                    awaits_comma = true;
+                }
+                _ => {
+                    return Err(AspenError::expected(
+                        parser,
+                        "a ',' or a ']', '...rest' must be the last element of an array pattern"
+                            .to_owned(),
+                    ))
+                }
+            }
+        }
+
+        Ok(Pattern::Array { elements, rest })
+    }
+
+    /// Parses an object pattern.
+    ///
+    /// **NOTE: We assume "{" was already consumed!**
+    fn parse_object(parser: &mut AspenParser<'s>) -> AspenResult<Self> {
+        let mut properties = vec![];
+
+        loop {
+            let key = match next_jump_multispace(parser)? {
+                Token::CloseBrace => break,
+                Token::ObjectKey(key) => key,
+                _ => {
+                    return Err(AspenError::expected(
+                        parser,
+                        "a property, e.g 'name!'".to_owned(),
+                    ))
+                }
+            };
+
+            // a property without pattern is bound to a variable of the same name
+            let end_token = match next_jump_multispace(parser)? {
+                token @ (Token::Comma | Token::CloseBrace) => {
+                    properties.push((key, Pattern::Binding(key)));
+                    token
+                }
+                token => {
+                    properties.push((key, Self::parse(parser, token)?));
+                    next_jump_multispace(parser)?
+                }
+            };
+
+            match end_token {
+                Token::Comma => (),
+                Token::CloseBrace => break,
+                _ => return Err(AspenError::expected(parser, "a ',' or a '}'".to_owned())),
+            }
+        }
+
+        Ok(Pattern::Object(properties))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{error_of, parse, value_of};
+
+    #[test]
+    fn literal_range_and_type_patterns() {
+        let code = r#"
+@describe x {
+    match x {
+        0 => "zero",
+        "hi" => "greeting",
+        nil => "nothing",
+        1:9 => "digit",
+        'a':<'n' => "first half",
+        Int => "number",
+        String => "text",
+        _ => "other"
+    }
+}
+let results [describe(0), describe("hi"), describe(nil), describe(5), describe('c'), describe(42), describe("z"), describe(true)]
+"#;
+
+        assert_eq!(
+            value_of(code, "results"),
+            "[zero, greeting, nothing, digit, first half, number, text, other]"
+        );
+    }
+
+    #[test]
+    fn array_and_object_patterns_bind_names() {
+        let code = r#"
+@head values {
+    match values {
+        [] => "empty",
+        [only] => "one {only}",
+        [first, ...rest] => "{first} then {rest.len()}"
+    }
+}
+let arrays [head([]), head([1]), head([1, 2, 3])]
+let person {name! "Ada", age! 36}
+let adult match person {
+    {name!, age! 0:17} => "{name} is a minor",
+    {name!} => "{name} is an adult"
+}
+"#;
+
+        assert_eq!(value_of(code, "arrays"), "[empty, one 1, 1 then 2]");
+        assert_eq!(value_of(code, "adult"), "Ada is an adult");
+    }
+
+    #[test]
+    fn guards_select_the_arm() {
+        let code = r#"
+@parity n {
+    match n {
+        x if x % 2 == 0 => "even",
+        _ => "odd"
+    }
+}
+let parities [parity(2), parity(3)]
+"#;
+
+        assert_eq!(value_of(code, "parities"), "[even, odd]");
+    }
+
+    #[test]
+    fn unmatched_values() {
+        let code = "let x match 3 {\n    1 => 1\n}\n";
+
+        assert!(error_of(code).contains("No arm of the match matches the value '3'"));
+        assert_eq!(
+            parse(code).warnings()[0].message,
+            "match without a wildcard arm"
+        );
+    }
+}
@@ -17,8 +17,16 @@ pub enum BinaryOperator {
     Times,
     Exponent,
     Divide,
+    /// `a // b`, the division rounded down to an integer.
+    IntDivide,
     Modulo,
 
+    BitAnd,
+    BitOr,
+    BitXor,
+    ShiftLeft,
+    ShiftRight,
+
     Equal,
     NotEqual,
     GreaterThan,
@@ -28,21 +36,46 @@ pub enum BinaryOperator {
 
     And,
     Or,
+
+    /// `value ?? fallback`, the fallback is used when the value is an error.
+    Coalesce,
+}
+
+impl AssignOperator {
+    /// The operation of a compound assignment, `a += b` assigns `a + b` to `a`.
+    pub fn binary_operator(&self) -> Option<BinaryOperator> {
+        match self {
+            AssignOperator::Plus => Some(BinaryOperator::Plus),
+            AssignOperator::Sub => Some(BinaryOperator::Sub),
+            AssignOperator::Times => Some(BinaryOperator::Times),
+            AssignOperator::Divide => Some(BinaryOperator::Divide),
+            AssignOperator::Modulo => Some(BinaryOperator::Modulo),
+            AssignOperator::Equal => None,
+        }
+    }
 }
 
 impl BinaryOperator {
     pub fn get_precedence(&self) -> u8 {
         match self {
-            BinaryOperator::Exponent => 4,
-            BinaryOperator::Times | BinaryOperator::Divide | BinaryOperator::Modulo => 3,
-            BinaryOperator::Plus | BinaryOperator::Sub => 2,
+            BinaryOperator::Exponent => 9,
+            BinaryOperator::Times
+            | BinaryOperator::Divide
+            | BinaryOperator::IntDivide
+            | BinaryOperator::Modulo => 8,
+            BinaryOperator::Plus | BinaryOperator::Sub => 7,
+            BinaryOperator::ShiftLeft | BinaryOperator::ShiftRight => 6,
+            BinaryOperator::BitAnd => 5,
+            BinaryOperator::BitXor => 4,
+            BinaryOperator::BitOr => 3,
             BinaryOperator::Equal
             | BinaryOperator::GreaterThan
             | BinaryOperator::GreaterThanOrEqual
             | BinaryOperator::LessThan
             | BinaryOperator::LessThanOrEqual
-            | BinaryOperator::NotEqual => 1,
-            BinaryOperator::And | BinaryOperator::Or => 0,
+            | BinaryOperator::NotEqual => 2,
+            BinaryOperator::And | BinaryOperator::Or => 1,
+            BinaryOperator::Coalesce => 0,
         }
     }
 
@@ -53,7 +86,13 @@ impl BinaryOperator {
             BinaryOperator::Times => "multiply",
             BinaryOperator::Exponent => "raise",
             BinaryOperator::Divide => "divide",
+            BinaryOperator::IntDivide => "divide",
             BinaryOperator::Modulo => "take the modulo of",
+            BinaryOperator::BitAnd => "use bitwise 'and' operator (&) on",
+            BinaryOperator::BitOr => "use bitwise 'or' operator (|) on",
+            BinaryOperator::BitXor => "use bitwise 'xor' operator (^) on",
+            BinaryOperator::ShiftLeft => "shift",
+            BinaryOperator::ShiftRight => "shift",
             BinaryOperator::Equal => "check for equality of",
             BinaryOperator::NotEqual => "check for non-equality of",
             BinaryOperator::GreaterThan => "check if",
@@ -62,6 +101,7 @@ impl BinaryOperator {
             BinaryOperator::LessThanOrEqual => "check if",
             BinaryOperator::And => "use 'and' operator (&&)",
             BinaryOperator::Or => "use 'or' operator (||)",
+            BinaryOperator::Coalesce => "fall back from",
         }
     }
     pub fn get_proposition(&self) -> &'static str {
@@ -70,8 +110,14 @@ impl BinaryOperator {
             BinaryOperator::Sub => "to",
             BinaryOperator::Times => "with",
             BinaryOperator::Divide => "with",
+            BinaryOperator::IntDivide => "with",
             BinaryOperator::Exponent => "to the power of",
             BinaryOperator::Modulo => "by",
+            BinaryOperator::BitAnd => "and",
+            BinaryOperator::BitOr => "and",
+            BinaryOperator::BitXor => "and",
+            BinaryOperator::ShiftLeft => "to the left by",
+            BinaryOperator::ShiftRight => "to the right by",
             BinaryOperator::Equal => "and",
             BinaryOperator::NotEqual => "and",
             BinaryOperator::GreaterThan => "is greater than",
@@ -80,6 +126,7 @@ impl BinaryOperator {
             BinaryOperator::LessThanOrEqual => "is less than or equal to",
             BinaryOperator::And => "with",
             BinaryOperator::Or => "with",
+            BinaryOperator::Coalesce => "to",
         }
     }
 }
@@ -103,9 +150,15 @@ impl fmt::Display for BinaryOperator {
             BinaryOperator::Plus => write!(f, "+"),
             BinaryOperator::Sub => write!(f, "-"),
             BinaryOperator::Times => write!(f, "*"),
-            BinaryOperator::Exponent => write!(f, "^"),
+            BinaryOperator::Exponent => write!(f, "**"),
             BinaryOperator::Divide => write!(f, "/"),
+            BinaryOperator::IntDivide => write!(f, "//"),
             BinaryOperator::Modulo => write!(f, "%"),
+            BinaryOperator::BitAnd => write!(f, "&"),
+            BinaryOperator::BitOr => write!(f, "|"),
+            BinaryOperator::BitXor => write!(f, "^"),
+            BinaryOperator::ShiftLeft => write!(f, "<<"),
+            BinaryOperator::ShiftRight => write!(f, ">>"),
             BinaryOperator::Equal => write!(f, "=="),
             BinaryOperator::NotEqual => write!(f, "!="),
             BinaryOperator::GreaterThan => write!(f, ">"),
@@ -114,6 +167,94 @@ impl fmt::Display for BinaryOperator {
             BinaryOperator::LessThanOrEqual => write!(f, "<="),
             BinaryOperator::And => write!(f, "&&"),
             BinaryOperator::Or => write!(f, "||"),
+            BinaryOperator::Coalesce => write!(f, "??"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BinaryOperator;
+    use crate::{
+        parser::{Expr, Statement},
+        test_utils::{error_of, parse, value_of},
+    };
+
+    #[test]
+    fn shift_right_returns_only_at_the_start_of_a_statement() {
+        let statements = parse("let a 8\n>> a >> 1\n").statements();
+
+        assert!(matches!(statements[0].as_ref(), Statement::Var(_)));
+        match statements[1].as_ref() {
+            Statement::Return(returned) => assert!(matches!(
+                returned.0.as_ref(),
+                Expr::Binary {
+                    operator: BinaryOperator::ShiftRight,
+                    ..
+                }
+            )),
+            stmt => panic!("expected a return, got {:?}", stmt),
         }
+        assert_eq!(statements.len(), 2);
+    }
+
+    #[test]
+    fn shifts_in_functions_and_statements() {
+        let code = r#"
+@half x {
+    >> x >> 1
+}
+@quarter x {
+    let h x >> 1; >> h >> 1
+}
+let a half(9)
+let b quarter(17)
+let c 1 << 10
+"#;
+
+        assert_eq!(value_of(code, "a"), "4");
+        assert_eq!(value_of(code, "b"), "4");
+        assert_eq!(value_of(code, "c"), "1024");
+    }
+
+    #[test]
+    fn double_slash_divides_after_an_operand() {
+        let code = r#"
+// a comment starting a line
+let x 7 // 2
+let y (7 + 8) // 2 - 4
+@half n { // a comment after a brace
+    >> n // 2
+}
+let z half(9) /* inline */ // and trailing
+let values [
+    // the first value
+    x,
+    y
+]
+"#;
+
+        assert_eq!(value_of(code, "x"), "3");
+        assert_eq!(value_of(code, "y"), "3");
+        assert_eq!(value_of(code, "z"), "4");
+        assert_eq!(value_of(code, "values"), "[3, 3]");
+        // a '//' after a value always divides
+        assert!(error_of("let w 10 // TODO\n").contains("'TODO'"));
+    }
+
+    #[test]
+    fn operators_in_call_arguments_and_array_elements() {
+        let code = r#"
+@pair a, b {
+    >> [a, b]
+}
+let called pair(7 >> 1, 7 // 2)
+let elements [7 >> 1, 7 // 2, 6 & 3 | 8, ~5]
+let mixed pair(1 + 2 * 3, 2 ** 3 << 1)
+"#;
+
+        assert_eq!(value_of(code, "called"), "[3, 3]");
+        assert_eq!(value_of(code, "elements"), "[3, 3, 10, -6]");
+        assert_eq!(value_of(code, "mixed"), "[7, 16]");
     }
 }
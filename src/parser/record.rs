@@ -0,0 +1,92 @@
+use super::{
+    error::{AspenError, AspenResult},
+    utils::{expect_space, expect_token, next_jump_multispace, peek_jump_multispace},
+    AspenParser, Expr, Statement,
+};
+use crate::lexer::Token;
+
+/// A `$type Point { x!, y!, z! 0 }` declaration of a record type.
+///
+/// The name of the type is a constructor, `Point({x! 1, y! 2})` creates a record having all the fields,
+/// a field having a default value can be left out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record<'s> {
+    pub name: &'s str,
+    pub fields: Vec<Field<'s>>,
+}
+
+/// A field of a record type, along with its default value.
+pub type Field<'s> = (&'s str, Option<Box<Expr<'s>>>);
+
+crate::impl_from_for!(Record, Statement);
+
+impl<'s> Record<'s> {
+    /// Parses a record type declaration.
+    ///
+    /// **NOTE: We assume "$type" is already consumed by the parser!**
+    pub fn parse(parser: &mut AspenParser<'s>) -> AspenResult<Statement<'s>> {
+        expect_space(parser)?;
+
+        let name = match next_jump_multispace(parser)? {
+            Token::Identifier(name) if name.starts_with(char::is_uppercase) => name,
+            _ => {
+                return Err(AspenError::expected(
+                    parser,
+                    "a capitalized name for the type, e.g 'Point'".to_owned(),
+                ))
+            }
+        };
+        parser.declare_names([name], None);
+
+        expect_token(parser, Token::OpenBrace)?;
+        let fields = Self::parse_fields(parser)?;
+
+        Ok(Record { name, fields }.into())
+    }
+
+    /// Parses the fields of the type.
+    ///
+    /// **NOTE: We assume "{" was already consumed!**
+    fn parse_fields(parser: &mut AspenParser<'s>) -> AspenResult<Vec<Field<'s>>> {
+        let mut fields: Vec<Field> = vec![];
+
+        loop {
+            let field = match next_jump_multispace(parser)? {
+                Token::CloseBrace => break,
+                Token::ObjectKey(field)
+                    if fields.iter().all(|(declared, _)| *declared != field) =>
+                {
+                    field
+                }
+                Token::ObjectKey(field) => {
+                    return Err(AspenError::expected(
+                        parser,
+                        format!("a new field, '{}' is already declared", field),
+                    ))
+                }
+                _ => {
+                    return Err(AspenError::expected(
+                        parser,
+                        "a field, e.g 'name!'".to_owned(),
+                    ))
+                }
+            };
+
+            let (default, end_token) = match peek_jump_multispace(parser) {
+                Some(Token::Comma | Token::CloseBrace) => (None, next_jump_multispace(parser)?),
+                _ => {
+                    let (default, end_token) =
+                        Expr::parse_until(parser, &[Token::Comma, Token::CloseBrace])?;
+                    (Some(default), end_token)
+                }
+            };
+            fields.push((field, default));
+
+            if end_token == Token::CloseBrace {
+                break;
+            }
+        }
+
+        Ok(fields)
+    }
+}
@@ -0,0 +1,432 @@
+use std::{borrow::Cow, fmt};
+
+use logos::Logos;
+
+use super::{
+    error::{AspenError, AspenResult},
+    value::Value,
+    AspenParser, Expr, Token,
+};
+
+/// Decodes the escape sequences of the string literal currently pointed at by the lexer.
+///
+/// The lexer already rejects unknown escapes, only the code point of a `\u{...}` escape can still be invalid.
+/// A string without any escape is borrowed from the source as is.
+pub fn decode_string<'s>(parser: &mut AspenParser<'s>, raw: &'s str) -> AspenResult<Cow<'s, str>> {
+    // skip the opening quote
+    decode_escapes(raw, parser.lexer.span().start + 1)
+}
+
+/// Decodes the escape sequences of `raw`, `offset` being its position in the source code.
+fn decode_escapes(raw: &str, offset: usize) -> AspenResult<Cow<'_, str>> {
+    if !raw.contains('\\') {
+        return Ok(Cow::Borrowed(raw));
+    }
+
+    let mut result = String::with_capacity(raw.len());
+    let mut chars = raw.char_indices();
+
+    while let Some((start, c)) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+
+        let decoded = match chars.next() {
+            Some((_, 'n')) => '\n',
+            Some((_, 't')) => '\t',
+            Some((_, 'r')) => '\r',
+            Some((_, 'b')) => '\u{8}',
+            Some((_, 'f')) => '\u{c}',
+            Some((_, 'u')) => {
+                // the lexer ensures the escape is written `\u{<hex digits>}`
+                let end = chars.find(|(_, c)| *c == '}').map(|(i, _)| i + 1).unwrap();
+                let digits = &raw[start + 3..end - 1];
+
+                match u32::from_str_radix(digits, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                {
+                    Some(c) => c,
+                    None => {
+                        return Err(invalid_string(
+                            format!(
+                                "unicode escape '\\u{{{}}}' is not a valid code point",
+                                digits
+                            ),
+                            offset + start,
+                            offset + end,
+                        ))
+                    }
+                }
+            }
+            // quotes and backslashes
+            Some((_, c)) => c,
+            None => unreachable!("an escape is always followed by a char"),
+        };
+
+        result.push(decoded);
+    }
+
+    Ok(Cow::Owned(result))
+}
+
+fn invalid_string(error: String, start: usize, end: usize) -> AspenError {
+    AspenError::InvalidString {
+        error,
+        start,
+        length: end - start,
+    }
+}
+
+/// Returns true if the string literal currently pointed at by the lexer must be interpolated.
+///
+/// Only double-quoted strings are interpolated, single-quoted and raw strings are kept as they are.
+pub fn is_interpolated(parser: &AspenParser<'_>, raw: &str) -> bool {
+    parser.lexer.slice().starts_with('"') && raw.contains(['{', '}'])
+}
+
+/// Parses a string containing `{<expr>}` or `{<expr>:<format spec>}` parts.
+///
+/// The literal parts and the formatted expressions are chained into [`Expr::StringConcatenation`] nodes,
+/// `{{` and `}}` are used to write braces.
+pub fn parse_interpolated_string<'s>(
+    parser: &mut AspenParser<'s>,
+    raw: &'s str,
+) -> AspenResult<Expr<'s>> {
+    // skip the opening quote
+    let offset = parser.lexer.span().start + 1;
+    let bytes = raw.as_bytes();
+
+    let mut parts = Vec::new();
+    let mut literal = String::new();
+    let mut literal_start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if bytes[i + 1] == b'u' => i += raw[i..].find('}').unwrap() + 1,
+            b'\\' => i += 2,
+            b @ (b'{' | b'}') if bytes.get(i + 1) == Some(&b) => {
+                literal.push_str(&decode_escapes(
+                    &raw[literal_start..=i],
+                    offset + literal_start,
+                )?);
+                i += 2;
+                literal_start = i;
+            }
+            b'}' => {
+                return Err(invalid_string(
+                    "unmatched '}', use '}}' to write a brace".to_owned(),
+                    offset + i,
+                    offset + i + 1,
+                ))
+            }
+            b'{' => {
+                literal.push_str(&decode_escapes(
+                    &raw[literal_start..i],
+                    offset + literal_start,
+                )?);
+                if !literal.is_empty() {
+                    parts.push(Expr::Value(Value::Str(Cow::Owned(std::mem::take(
+                        &mut literal,
+                    )))));
+                }
+
+                let (expr, end) = parse_embedded_expr(parser, raw, offset, i)?;
+                parts.push(expr);
+                i = end;
+                literal_start = i;
+            }
+            _ => i += 1,
+        }
+    }
+
+    literal.push_str(&decode_escapes(
+        &raw[literal_start..],
+        offset + literal_start,
+    )?);
+    if !literal.is_empty() || parts.is_empty() {
+        parts.push(Expr::Value(Value::Str(Cow::Owned(literal))));
+    }
+
+    let mut parts = parts.into_iter();
+    let first = parts.next().unwrap();
+
+    Ok(parts.fold(first, |left, right| Expr::StringConcatenation {
+        left: Box::new(left),
+        right: Box::new(right),
+    }))
+}
+
+/// Parses the expression starting at the `{` found at index `start` of `raw`.
+///
+/// Returns the [`Expr::Formatted`] expression and the index following the closing `}`.
+fn parse_embedded_expr<'s>(
+    parser: &mut AspenParser<'s>,
+    raw: &'s str,
+    offset: usize,
+    start: usize,
+) -> AspenResult<(Expr<'s>, usize)> {
+    let unclosed = || {
+        invalid_string(
+            "unclosed '{', use '{{' to write a brace".to_owned(),
+            offset + start,
+            offset + start + 1,
+        )
+    };
+
+    // the expression is lexed from the source code itself so that errors point at the right location,
+    // the source is cut at the end of the string so that the lexer cannot go past it
+    let source = &parser.lexer.source()[..offset + raw.len()];
+    let mut lexer = Token::lexer(source);
+    lexer.bump(offset + start + 1);
+    let mut sub_parser = AspenParser::new(lexer);
+
+    let (value, stop_token) = match Expr::parse_until(
+        &mut sub_parser,
+        &[Token::CloseBrace, Token::Range, Token::ExclusiveRange],
+    ) {
+        Ok(result) => result,
+        Err(AspenError::Eof) => return Err(unclosed()),
+        Err(e) => return Err(e),
+    };
+
+    let mut end = sub_parser.lexer.span().end - offset;
+
+    let spec = match stop_token {
+        // a left-aligned spec is lexed as an exclusive range: `{name:<10}`,
+        // the spec starts right after the ':' in both cases
+        Token::Range | Token::ExclusiveRange => {
+            end = sub_parser.lexer.span().start + 1 - offset;
+
+            let spec_len = raw[end..].find('}').ok_or_else(unclosed)?;
+            let spec = &raw[end..end + spec_len];
+
+            let format_spec = FormatSpec::parse(spec).ok_or_else(|| {
+                invalid_string(
+                    format!("invalid format spec '{}'", spec),
+                    offset + end,
+                    offset + end + spec_len,
+                )
+            })?;
+
+            end += spec_len + 1;
+            Some(format_spec)
+        }
+        _ => None,
+    };
+
+    Ok((Expr::Formatted { value, spec }, end))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    Left,
+    Right,
+    Center,
+}
+
+/// Represents the format spec of an interpolated expression: `[[fill]align][0][width][.precision]`.
+///
+/// e.g `{price:.2}`, `{name:<10}`, `{count:*^8}` or `{id:05}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatSpec {
+    pub fill: char,
+    pub align: Option<Alignment>,
+    /// Numbers are padded with zeros placed after their sign.
+    pub zero: bool,
+    pub width: Option<usize>,
+    /// Number of decimals of a number.
+    pub precision: Option<usize>,
+}
+
+impl FormatSpec {
+    pub fn parse(spec: &str) -> Option<Self> {
+        let mut result = Self {
+            fill: ' ',
+            align: None,
+            zero: false,
+            width: None,
+            precision: None,
+        };
+
+        let mut chars = spec.chars();
+        let mut rest = spec;
+
+        match (chars.next(), chars.next().and_then(Alignment::from_char)) {
+            (Some(fill), Some(align)) => {
+                result.fill = fill;
+                result.align = Some(align);
+                rest = chars.as_str();
+            }
+            (Some(c), _) if Alignment::from_char(c).is_some() => {
+                result.align = Alignment::from_char(c);
+                rest = &spec[1..];
+            }
+            _ => (),
+        }
+
+        if let Some(r) = rest.strip_prefix('0') {
+            result.zero = true;
+            rest = r;
+        }
+
+        let (width, precision) = match rest.split_once('.') {
+            Some((width, precision)) => (width, Some(precision)),
+            None => (rest, None),
+        };
+
+        if !width.is_empty() {
+            result.width = Some(parse_digits(width)?);
+        }
+        if let Some(precision) = precision {
+            result.precision = Some(parse_digits(precision)?);
+        }
+
+        Some(result)
+    }
+}
+
+fn parse_digits(s: &str) -> Option<usize> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    s.parse().ok()
+}
+
+impl Alignment {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            '<' => Some(Self::Left),
+            '>' => Some(Self::Right),
+            '^' => Some(Self::Center),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Alignment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Alignment::Left => write!(f, "<"),
+            Alignment::Right => write!(f, ">"),
+            Alignment::Center => write!(f, "^"),
+        }
+    }
+}
+
+impl fmt::Display for FormatSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(align) = self.align {
+            write!(f, "{}{}", self.fill, align)?;
+        }
+        if self.zero {
+            write!(f, "0")?;
+        }
+        if let Some(width) = self.width {
+            write!(f, "{}", width)?;
+        }
+        if let Some(precision) = self.precision {
+            write!(f, ".{}", precision)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_escapes, Alignment, FormatSpec};
+    use crate::test_utils::{try_parse, value_of};
+
+    #[test]
+    fn escapes_are_decoded() {
+        assert_eq!(decode_escapes(r"plain", 0).unwrap(), "plain");
+        assert_eq!(
+            decode_escapes(r#"a\tb\nc\\d\"e\'f"#, 0).unwrap(),
+            "a\tb\nc\\d\"e'f"
+        );
+        assert_eq!(decode_escapes(r"\r\b\f", 0).unwrap(), "\r\u{8}\u{c}");
+        assert_eq!(decode_escapes(r"\u{48}\u{1F600}", 0).unwrap(), "H😀");
+        assert!(decode_escapes(r"\u{D800}", 0).is_err());
+    }
+
+    #[test]
+    fn raw_strings_keep_their_backslashes() {
+        let code = r#"
+let escaped 'a\tb'
+let raw r'a\tb'
+let length raw.len()
+"#;
+
+        assert_eq!(value_of(code, "escaped"), "a\tb");
+        assert_eq!(value_of(code, "raw"), r"a\tb");
+        assert_eq!(value_of(code, "length"), "4");
+    }
+
+    #[test]
+    fn invalid_escapes_are_rejected() {
+        assert!(try_parse(r#"let s "\q""#).is_err());
+        assert!(try_parse(r#"let s "\u{110000}""#).is_err());
+    }
+
+    #[test]
+    fn format_specs_are_parsed() {
+        assert_eq!(
+            FormatSpec::parse("*^8.2"),
+            Some(FormatSpec {
+                fill: '*',
+                align: Some(Alignment::Center),
+                zero: false,
+                width: Some(8),
+                precision: Some(2),
+            })
+        );
+        assert_eq!(
+            FormatSpec::parse("05"),
+            Some(FormatSpec {
+                fill: ' ',
+                align: None,
+                zero: true,
+                width: Some(5),
+                precision: None,
+            })
+        );
+        assert_eq!(
+            FormatSpec::parse("<10").unwrap().align,
+            Some(Alignment::Left)
+        );
+        assert_eq!(FormatSpec::parse("x"), None);
+        assert_eq!(FormatSpec::parse("1."), None);
+        assert_eq!(FormatSpec::parse("*^8.2").unwrap().to_string(), "*^8.2");
+    }
+
+    #[test]
+    fn interpolated_expressions_are_formatted() {
+        let code = r#"
+let name "Ada", price 3.14159, id 42, debt -42, items [1, 2]
+let greeting "hello {name}!"
+let computed "{id * 2} {items[1]} {name.len()}"
+let formatted "[{price:.2}] [{name:<5}] [{id:>4}] [{id:*^6}] [{id:05}] [{debt:05}]"
+let braces "{{{id}}}"
+let single 'not {interpolated}'
+"#;
+
+        assert_eq!(value_of(code, "greeting"), "hello Ada!");
+        assert_eq!(value_of(code, "computed"), "84 2 3");
+        assert_eq!(
+            value_of(code, "formatted"),
+            "[3.14] [Ada  ] [  42] [**42**] [00042] [-0042]"
+        );
+        assert_eq!(value_of(code, "braces"), "{42}");
+        assert_eq!(value_of(code, "single"), "not {interpolated}");
+    }
+
+    #[test]
+    fn invalid_interpolations_are_rejected() {
+        assert!(try_parse(r#"let s "{name""#).is_err());
+        assert!(try_parse(r#"let s "name}""#).is_err());
+        assert!(try_parse(r#"let s "{name:x}""#).is_err());
+    }
+}
@@ -0,0 +1,125 @@
+use super::{
+    error::{AspenError, AspenResult},
+    parse_block,
+    utils::{expect_token, next_jump_multispace, Block},
+    AspenParser, Expr,
+};
+use crate::lexer::Token;
+
+/// A `try { ... } catch e { ... }` expression.
+///
+/// Evaluates to the value of its body, or to the value of its handler when an error is raised
+/// or propagated with `?` while evaluating the body.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Try<'s> {
+    pub body: Block<'s>,
+    /// Name given to the caught error, `catch { ... }` ignores it.
+    pub binding: Option<&'s str>,
+    pub handler: Block<'s>,
+}
+
+impl<'s> Try<'s> {
+    /// Parses a try expression along with its catch clause.
+    ///
+    /// **NOTE: We assume "try" is already consumed by the parser!**
+    pub fn parse(parser: &mut AspenParser<'s>) -> AspenResult<Expr<'s>> {
+        expect_token(parser, Token::OpenBrace)?;
+        let body = parse_block(parser, Some(Token::CloseBrace))?;
+
+        if next_jump_multispace(parser)? != Token::Catch {
+            return Err(AspenError::expected(
+                parser,
+                "'catch' after the body of a 'try'".to_owned(),
+            ));
+        }
+
+        let binding = match next_jump_multispace(parser)? {
+            Token::Identifier(name) => {
+                let span = parser.lexer.span();
+                expect_token(parser, Token::OpenBrace)?;
+                parser.push_implicit_names(vec![name], span);
+                Some(name)
+            }
+            Token::OpenBrace => None,
+            _ => {
+                return Err(AspenError::expected(
+                    parser,
+                    "a name for the error or '{' after 'catch'".to_owned(),
+                ))
+            }
+        };
+        let handler = parse_block(parser, Some(Token::CloseBrace))?;
+        if binding.is_some() {
+            parser.pop_implicit_names();
+        }
+
+        Ok(Expr::Try(Box::new(Try {
+            body,
+            binding,
+            handler,
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{error_of, value_of};
+
+    #[test]
+    fn try_evaluates_to_its_body_or_its_handler() {
+        let code = r#"
+let ok try {
+    1 + 1
+} catch e {
+    0
+}
+let kind try {
+    let x [1][5]
+} catch e {
+    e.kind
+}
+let ignored try {
+    1 % 0
+} catch {
+    "ignored"
+}
+let nested try {
+    try {
+        Err("inner")?
+    } catch e {
+        Err("outer", {cause! e})?
+    }
+} catch e {
+    "{e.message} {e.cause.message}"
+}
+"#;
+        let value = |name| value_of(code, name);
+
+        assert_eq!(value("ok"), "2");
+        assert_eq!(value("kind"), "IndexError");
+        assert_eq!(value("ignored"), "ignored");
+        assert_eq!(value("nested"), "outer inner");
+    }
+
+    #[test]
+    fn fallback_operator_replaces_errors_only() {
+        let code = r#"
+let value 1 ?? 2
+let raised 1 % 0 ?? 2
+let error_value Err("no") ?? 3
+let nil_value nil ?? 4
+"#;
+
+        assert_eq!(value_of(code, "value"), "1");
+        assert_eq!(value_of(code, "raised"), "2");
+        assert_eq!(value_of(code, "error_value"), "3");
+        assert_eq!(value_of(code, "nil_value"), "nil");
+    }
+
+    #[test]
+    fn errors_of_the_handler_are_not_caught() {
+        let code = "let x try {\n    1 % 0\n} catch e {\n    1 % 0\n}\n";
+
+        assert!(error_of(code).contains("Cannot divide by zero"));
+    }
+}
@@ -1,5 +1,8 @@
 use super::error::{AspenError, AspenResult};
-use crate::parser::{AspenParser, Container, Statement, Token};
+use crate::parser::{
+    conditional::{If, IfOther},
+    AspenParser, Container, Expr, Statement, Token,
+};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum TokenOption<'a, T> {
@@ -96,6 +99,32 @@ pub fn next_jump_space<'s>(parser: &mut AspenParser<'s>) -> AspenResult<Token<'s
     }
 }
 
+/// Returns the next token that is not a space, without consuming it.
+pub fn peek_jump_space<'s>(parser: &AspenParser<'s>) -> Option<Token<'s>> {
+    let mut lexer = parser.lexer.clone();
+
+    loop {
+        match lexer.next()? {
+            Ok(Token::Spaces) => (),
+            Ok(token) => return Some(token),
+            Err(_) => return None,
+        }
+    }
+}
+
+/// Returns the next token that is neither a space nor a newline, without consuming it.
+pub fn peek_jump_multispace<'s>(parser: &AspenParser<'s>) -> Option<Token<'s>> {
+    let mut lexer = parser.lexer.clone();
+
+    loop {
+        match lexer.next()? {
+            Ok(Token::Spaces | Token::Newline) => (),
+            Ok(token) => return Some(token),
+            Err(_) => return None,
+        }
+    }
+}
+
 impl<'a> Block<'a> {
     pub fn new(statements: Container<Statement<'a>>) -> Self {
         Self { statements }
@@ -103,6 +132,31 @@ impl<'a> Block<'a> {
     pub fn statements(&self) -> Container<Statement<'a>> {
         self.statements.to_owned()
     }
+
+    /// Returns true if the block contains a `yield`, the functions declared in it are not searched.
+    pub fn yields(&self) -> bool {
+        fn if_yields(if_stmt: &If<'_>) -> bool {
+            if_stmt.body.yields()
+                || match if_stmt.other.as_deref() {
+                    Some(IfOther::If(other)) => if_yields(other),
+                    Some(IfOther::Else(block)) => block.yields(),
+                    None => false,
+                }
+        }
+
+        self.statements.iter().any(|stmt| match stmt.as_ref() {
+            Statement::Yield(_) => true,
+            Statement::For(for_loop) => for_loop.body.yields(),
+            Statement::While(while_loop) => while_loop.body.yields(),
+            Statement::If(if_stmt) => if_yields(if_stmt),
+            Statement::Expr { value, .. } => match value.as_ref() {
+                Expr::Try(try_expr) => try_expr.body.yields() || try_expr.handler.yields(),
+                Expr::Match(match_expr) => match_expr.arms.iter().any(|arm| arm.body.yields()),
+                _ => false,
+            },
+            _ => false,
+        })
+    }
 }
 
 impl<'a, T> From<Token<'a>> for TokenOption<'a, T> {
@@ -1,17 +1,19 @@
 use rug::float::OrdFloat;
+use std::borrow::Cow;
 
 use crate::lexer::{Integer, Token};
 
 use super::{
     error::{AspenError, AspenResult},
     operator::AssignOperator,
+    string::decode_string,
     utils::TokenOption,
     AspenParser, Expr,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Value<'s> {
-    Str(&'s str),
+    Str(Cow<'s, str>),
     Int(Integer),
     Float(OrdFloat),
     Bool(bool),
@@ -24,7 +26,8 @@ pub enum Value<'s> {
 pub fn parse_value<'s>(parser: &mut AspenParser<'s>, token: Token<'s>) -> AspenResult<Value<'s>> {
     let value = match token {
         Token::Bool(b) => b.into(),
-        Token::String(s) => s.into(),
+        Token::String(s) => Value::Str(decode_string(parser, s)?),
+        Token::RawString(s) => s.into(),
         Token::Int(i) => i.into(),
         Token::Float(f) => f.into(),
         Token::Nil => Value::Nil,
@@ -47,7 +50,7 @@ pub fn parse_value<'s>(parser: &mut AspenParser<'s>, token: Token<'s>) -> AspenR
 pub fn parse_value_or_return_token(token: Token<'_>) -> AspenResult<TokenOption<'_, Value<'_>>> {
     let value: Value<'_> = match token {
         Token::Bool(b) => b.into(),
-        Token::String(s) => s.into(),
+        Token::RawString(s) => s.into(),
         Token::Int(i) => i.into(),
         Token::Float(f) => f.into(),
         _ => return Ok(token.into()),
@@ -71,7 +74,7 @@ impl<'s> From<bool> for Value<'s> {
 }
 impl<'s> From<&'s str> for Value<'s> {
     fn from(value: &'s str) -> Self {
-        Value::Str(value)
+        Value::Str(Cow::Borrowed(value))
     }
 }
 impl<'s> From<Integer> for Value<'s> {
@@ -1,63 +1,95 @@
 use super::{
     error::{AspenError, AspenResult},
-    utils::{expect_space, next_jump_multispace},
+    utils::{expect_space, next_jump_multispace, peek_jump_multispace},
     Expr, Statement,
 };
 use crate::parser::{AspenParser, Token};
+use std::ops::Range;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Var<'s> {
     pub variables: Variables<'s>,
     pub value: Box<Expr<'s>>,
+    /// True for a `const`, whose variables cannot be assigned and whose value cannot be modified.
+    pub is_const: bool,
+    /// Position of the declared variables.
+    pub span: Range<usize>,
 }
 
+/// The variables a value is bound to, in a `let`, a `for` loop head or a function parameter.
 #[derive(Debug, Clone, PartialEq)]
 pub enum Variables<'s> {
     Unique(&'s str),
-    Destructuring(Vec<&'s str>),
+    /// `(a, b: 0, ...rest)`: the elements of an array by position, or the properties of an object by name.
+    Destructuring {
+        elements: Vec<Destructured<'s>>,
+        rest: Option<&'s str>,
+    },
+    /// `[x, y, ...rest]`: the elements of an array by position.
+    Array {
+        elements: Vec<Destructured<'s>>,
+        rest: Option<&'s str>,
+    },
+    /// `{name!, age! a}`: the properties of an object, `name!` alone binds a variable of the same name.
+    Object(Vec<(&'s str, Destructured<'s>)>),
+}
+
+/// A destructured element, with the value used when it is missing: `b: 0`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Destructured<'s> {
+    pub variables: Variables<'s>,
+    pub default: Option<Box<Expr<'s>>>,
 }
 
 impl<'s> Var<'s> {
     /// Parses an variable declaration.
     ///
-    /// **NOTE: We assume "let" is already consumed by the parser!**
-    pub fn parse(parser: &mut AspenParser<'s>) -> AspenResult<Statement<'s>> {
+    /// **NOTE: We assume "let" or "const" is already consumed by the parser!**
+    pub fn parse(parser: &mut AspenParser<'s>, is_const: bool) -> AspenResult<Statement<'s>> {
         expect_space(parser)?;
 
-        Self::parse_after_comma(parser)
+        Self::parse_after_comma(parser, is_const)
     }
 
-    pub fn parse_after_comma(parser: &mut AspenParser<'s>) -> AspenResult<Statement<'s>> {
-        let mut variables = match next_jump_multispace(parser)? {
-            Token::Identifier(name) => Variables::Unique(name),
-            Token::OpenParen => {
-                let mut variables = vec![];
-                match next_jump_multispace(parser)? {
-                    Token::Identifier(name) => variables.push(name),
-                    _ => return Err(AspenError::expected(parser, "an identifier".to_owned())),
-                }
+    pub fn parse_after_comma(
+        parser: &mut AspenParser<'s>,
+        is_const: bool,
+    ) -> AspenResult<Statement<'s>> {
+        let token = next_jump_multispace(parser)?;
+        let start = parser.lexer.span().start;
+        let variables = Variables::parse(parser, token)?;
+        let span = start..parser.lexer.span().end;
 
-                loop {
-                    match next_jump_multispace(parser)? {
-                        Token::Comma => {
-                            match next_jump_multispace(parser)? {
-                                Token::Identifier(name) => variables.push(name),
-                                Token::CloseParen => break,
-                                _ => {
-                                    return Err(AspenError::expected(
-                                        parser,
-                                        "an identifier".to_owned(),
-                                    ))
-                                }
-                            };
-                        }
-                        Token::CloseParen => break,
-                        _ => return Err(AspenError::expected(parser, "an identifier".to_owned())),
-                    }
-                }
+        // the names are only visible after the value, a pattern of the value can reuse them
+        let value = Box::new(Expr::parse(parser)?);
+        parser.declare_names(variables.names(), is_const.then(|| span.to_owned()));
+
+        Ok(Var {
+            variables,
+            value,
+            is_const,
+            span,
+        }
+        .into())
+    }
+}
+
+crate::impl_from_for!(Var, Statement);
 
-                Variables::Destructuring(variables)
+impl<'s> Variables<'s> {
+    /// Parses the variables starting with `token`: a name, `(...)`, `[...]` or `{...}`.
+    pub fn parse(parser: &mut AspenParser<'s>, token: Token<'s>) -> AspenResult<Self> {
+        let variables = match token {
+            Token::Identifier(name) => Variables::Unique(name),
+            Token::OpenParen => {
+                let (elements, rest) = Self::parse_elements(parser, Token::CloseParen)?;
+                Variables::Destructuring { elements, rest }
             }
+            Token::OpenBracket => {
+                let (elements, rest) = Self::parse_elements(parser, Token::CloseBracket)?;
+                Variables::Array { elements, rest }
+            }
+            Token::OpenBrace => Self::parse_properties(parser)?,
             _ => {
                 return Err(AspenError::unknown(
                     parser,
@@ -66,19 +98,341 @@ impl<'s> Var<'s> {
             }
         };
 
-        let value = Box::new(Expr::parse(parser)?);
+        Ok(variables)
+    }
 
-        Ok(Var { variables, value }.into())
+    /// Parses destructured elements separated by commas until `end_token`, the last one can be a `...rest`.
+    ///
+    /// **NOTE: This function also parses `end_token`!**
+    pub fn parse_elements(
+        parser: &mut AspenParser<'s>,
+        end_token: Token<'s>,
+    ) -> AspenResult<(Vec<Destructured<'s>>, Option<&'s str>)> {
+        let mut elements = vec![];
+        let mut rest = None;
+
+        loop {
+            let token = next_jump_multispace(parser)?;
+
+            if token == end_token {
+                break;
+            }
+
+            if let Some(rest) = rest {
+                return Err(AspenError::expected(
+                    parser,
+                    format!(
+                        "{:?} after '...{}', it must be the last element",
+                        end_token, rest
+                    ),
+                ));
+            }
+
+            let separator = match token {
+                Token::SpreadOperator => {
+                    match next_jump_multispace(parser)? {
+                        Token::Identifier(name) => rest = Some(name),
+                        _ => {
+                            return Err(AspenError::expected(
+                                parser,
+                                "an identifier following the '...'".to_owned(),
+                            ))
+                        }
+                    }
+                    next_jump_multispace(parser)?
+                }
+                token => {
+                    let variables = Self::parse(parser, token)?;
+                    let (default, separator) =
+                        Destructured::parse_default(parser, &[Token::Comma, end_token.clone()])?;
+
+                    elements.push(Destructured { variables, default });
+                    separator
+                }
+            };
+
+            match separator {
+                Token::Comma => (),
+                token if token == end_token => break,
+                _ => {
+                    return Err(AspenError::expected(
+                        parser,
+                        format!("a ',' or {:?}", end_token),
+                    ))
+                }
+            }
+        }
+
+        Ok((elements, rest))
     }
-}
 
-crate::impl_from_for!(Var, Statement);
+    /// Parses the properties of an object destructuring.
+    ///
+    /// **NOTE: We assume "{" was already consumed!**
+    fn parse_properties(parser: &mut AspenParser<'s>) -> AspenResult<Self> {
+        let mut properties = vec![];
 
-impl<'s> Variables<'s> {
-    pub fn len(&self) -> usize {
+        loop {
+            let key = match next_jump_multispace(parser)? {
+                Token::CloseBrace => break,
+                Token::ObjectKey(key) => key,
+                _ => {
+                    return Err(AspenError::expected(
+                        parser,
+                        "a property, e.g 'name!'".to_owned(),
+                    ))
+                }
+            };
+
+            // a property without variables is bound to a variable of the same name
+            let variables = match peek_jump_multispace(parser) {
+                Some(Token::Comma | Token::CloseBrace | Token::Range) => Variables::Unique(key),
+                _ => {
+                    let token = next_jump_multispace(parser)?;
+                    Self::parse(parser, token)?
+                }
+            };
+            let (default, separator) =
+                Destructured::parse_default(parser, &[Token::Comma, Token::CloseBrace])?;
+
+            properties.push((key, Destructured { variables, default }));
+
+            if separator == Token::CloseBrace {
+                break;
+            }
+        }
+
+        Ok(Variables::Object(properties))
+    }
+
+    /// Returns the names of all the variables.
+    pub fn names(&self) -> Vec<&'s str> {
+        let mut names = vec![];
+        self.add_names(&mut names);
+        names
+    }
+
+    fn add_names(&self, names: &mut Vec<&'s str>) {
         match self {
-            Variables::Unique(_) => 1,
-            Variables::Destructuring(v) => v.len(),
+            Variables::Unique(name) => names.push(name),
+            Variables::Destructuring { elements, rest } | Variables::Array { elements, rest } => {
+                for element in elements {
+                    element.variables.add_names(names);
+                }
+                names.extend(rest);
+            }
+            Variables::Object(properties) => {
+                for (_, element) in properties {
+                    element.variables.add_names(names);
+                }
+            }
+        }
+    }
+}
+
+impl<'s> Destructured<'s> {
+    /// Parses the optional `: default` after destructured variables, followed by one of `separators`.
+    ///
+    /// **NOTE: This function also parses the separator, it is returned!**
+    fn parse_default(
+        parser: &mut AspenParser<'s>,
+        separators: &[Token<'s>],
+    ) -> AspenResult<(Option<Box<Expr<'s>>>, Token<'s>)> {
+        match next_jump_multispace(parser)? {
+            // token is called range but it is just ':'
+            Token::Range => {
+                let (default, separator) = Expr::parse_until(parser, separators)?;
+                Ok((Some(default), separator))
+            }
+            token if separators.contains(&token) => Ok((None, token)),
+            _ => Err(AspenError::expected(
+                parser,
+                format!("a default value after ':' or one of {:?}", separators),
+            )),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parser::error::AspenError,
+        test_utils::{error_of, parse, try_parse, value_of},
+    };
+
+    fn warnings(code: &str) -> Vec<String> {
+        parse(code)
+            .warnings()
+            .iter()
+            .map(|warning| warning.message.to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn patterns_of_the_value_can_reuse_the_declared_name() {
+        let code = r#"
+let m match [1, 2] {
+    [m, _] => m,
+    _ => 0
+}
+"#;
+
+        assert_eq!(warnings(code), Vec::<String>::new());
+        assert_eq!(value_of(code, "m"), "1");
+    }
+
+    #[test]
+    fn implicit_names_shadowing_a_variable_are_reported() {
+        let code = r#"
+let x 1
+for x in [1, 2] {
+    print(x)
+}
+"#;
+
+        assert_eq!(
+            warnings(code),
+            vec!["'x' shadows a variable of an enclosing block".to_owned()]
+        );
+    }
+
+    #[test]
+    fn let_can_shadow_in_an_inner_block() {
+        let code = r#"
+let x 1
+let y 0
+if true {
+    let x 2
+    y = x
+}
+"#;
+
+        assert_eq!(warnings(code), Vec::<String>::new());
+        assert_eq!(value_of(code, "x"), "1");
+        assert_eq!(value_of(code, "y"), "2");
+    }
+
+    #[test]
+    fn values_are_destructured_by_position_and_name() {
+        let code = r#"
+let person {name! "Ada", age! 36}
+let ([x, y], {name!}) [[1, 2], person]
+let {name! n, city! c: "Paris"} person
+let (first, second: 0, ...rest) [1]
+let (head, ...tail) [1, 2, 3]
+let (age, ...others) person
+"#;
+        let value = |name| value_of(code, name);
+
+        assert_eq!(value("x"), "1");
+        assert_eq!(value("y"), "2");
+        assert_eq!(value("name"), "Ada");
+        assert_eq!(value("n"), "Ada");
+        assert_eq!(value("c"), "Paris");
+        assert_eq!(value("first"), "1");
+        assert_eq!(value("second"), "0");
+        assert_eq!(value("rest"), "[]");
+        assert_eq!(value("tail"), "[2, 3]");
+        assert_eq!(value("age"), "36");
+        assert_eq!(value("others"), "{name! Ada}");
+    }
+
+    #[test]
+    fn parameters_and_loops_are_destructured() {
+        let code = r#"
+@greet {name!}, greeting: "Hello" {
+    "{greeting} {name}"
+}
+@first_and_more [p, _], ...more {
+    >> [p, more]
+}
+let person {name! "Ada"}
+let default greet(person)
+let given greet(person, "Hi")
+let spread first_and_more([5, 6], 7, 8)
+let words []
+for [_, word] in [[1, "one"], [2, "two"]] {
+    words.push(word)
+}
+"#;
+
+        assert_eq!(value_of(code, "default"), "Hello Ada");
+        assert_eq!(value_of(code, "given"), "Hi Ada");
+        assert_eq!(value_of(code, "spread"), "[5, [7, 8]]");
+        assert_eq!(value_of(code, "words"), "[one, two]");
+    }
+
+    #[test]
+    fn missing_values_without_default() {
+        assert!(error_of("let [z] []").contains("Array does not have anything at index '0'"));
+        assert!(error_of("let {a!} {b! 1}").contains("Object does not have a 'a' property"));
+    }
+
+    #[test]
+    fn constants_of_the_same_function_are_checked_when_parsing() {
+        let code = "const LIMIT 10\nLIMIT = 11\n";
+
+        match try_parse(code) {
+            Err(AspenError::AssignToConstant {
+                name,
+                assignment,
+                declaration,
+            }) => {
+                assert_eq!(name, "LIMIT");
+                assert_eq!(&code[declaration], "LIMIT");
+                assert_eq!(&code[assignment], "=");
+            }
+            result => panic!("expected an assignment error, got {:?}", result.err()),
+        }
+        assert!(try_parse("const (a, b) [1, 2]\nb = 3\n").is_err());
+    }
+
+    #[test]
+    fn constants_are_checked_when_running() {
+        // the constants are declared after the functions assigning them, the parser cannot know about them
+        let assign = "@f {\n    LIMIT = 11\n}\nconst LIMIT 10\nf()\n";
+        let push = "const VALUES [1]\nVALUES.push(2)\n";
+        let property = "@f {\n    O.a = 2\n}\nconst O {a! 1}\nf()\n";
+
+        assert!(error_of(assign).contains("constant 'LIMIT'"));
+        assert!(error_of(push).contains("'VALUES'"));
+        assert!(error_of(property).contains("constant 'O'"));
+        assert_eq!(value_of("const A 1, B A + 1", "B"), "2");
+    }
+
+    #[test]
+    fn compound_assignments_apply_their_operation() {
+        let code = r#"
+let x 1
+x += 2
+let y 10
+y -= 4
+y *= 3
+y %= 7
+let s "a"
+s += "b"
+let o {n! 1}
+o.n += 1
+"#;
+
+        assert_eq!(value_of(code, "x"), "3");
+        assert_eq!(value_of(code, "y"), "4");
+        assert_eq!(value_of(code, "s"), "ab");
+        assert_eq!(value_of(code, "o"), "{n! 2}");
+        assert!(try_parse("const LIMIT 10\nLIMIT += 1\n").is_err());
+        assert!(
+            error_of("@f {\n    LIMIT += 1\n}\nconst LIMIT 10\nf()\n").contains("constant 'LIMIT'")
+        );
+        assert!(error_of("let a [1]\nlet b ...a\n").contains("'...a' can only be used"));
+    }
+
+    #[test]
+    fn names_of_a_block_end_with_it() {
+        let leaked = "if true {\n    let z 1\n}\nlet w z\n";
+        let loop_leaked = "for i in 0:1 {\n    let z i\n}\nlet w i\n";
+
+        assert!(error_of(leaked).contains("Unknown variable or function: 'z'"));
+        assert!(error_of(loop_leaked).contains("'i'"));
+        assert!(error_of("let a 1\nlet a 2\n").contains("Identifier already in use: 'a'"));
+    }
+}
@@ -8,6 +8,8 @@ use crate::parser::{AspenParser, Token};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct While<'s> {
+    /// Name given with `#label while ...`, targeted by `break #label` and `continue #label`.
+    pub label: Option<&'s str>,
     pub condition: Box<Expr<'s>>,
     pub body: Box<Block<'s>>,
 }
@@ -17,11 +19,22 @@ impl<'s> While<'s> {
     /// Parses a while loop.
     ///
     /// **NOTE: We assume "while" is already consumed by the parser!**
-    pub fn parse(parser: &mut AspenParser<'s>) -> AspenResult<Statement<'s>> {
+    pub fn parse(
+        parser: &mut AspenParser<'s>,
+        label: Option<&'s str>,
+    ) -> AspenResult<Statement<'s>> {
         expect_space(parser)?;
         let (condition, _) = Expr::parse_until(parser, &[Token::OpenBrace])?;
+
+        parser.loops.push(label);
         let body = Box::new(parse_block(parser, Some(Token::CloseBrace))?);
+        parser.loops.pop();
 
-        Ok(While { condition, body }.into())
+        Ok(While {
+            label,
+            condition,
+            body,
+        }
+        .into())
     }
 }
@@ -0,0 +1,17 @@
+use super::{error::AspenResult, utils::expect_space, AspenParser, Expr, Statement};
+
+/// A `yield <expr>` statement, a function containing one is a generator.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Yield<'s>(pub Box<Expr<'s>>);
+crate::impl_from_for!(Yield, Statement);
+
+impl<'s> Yield<'s> {
+    /// Parses a yield statement.
+    ///
+    /// **NOTE: We assume "yield" is already consumed by the lexer!**
+    pub fn parse(parser: &mut AspenParser<'s>) -> AspenResult<Statement<'s>> {
+        expect_space(parser)?;
+        let expr = Expr::parse(parser).map(Box::new)?;
+        Ok(Statement::Yield(Yield(expr)))
+    }
+}
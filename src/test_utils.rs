@@ -0,0 +1,52 @@
+//! Helpers running Aspen code from the tests.
+
+use crate::{
+    evaluate::{AspenTable, EvaluateResult},
+    lexer::Token,
+    parser::{error::AspenResult, parse_aspen, AspenParser},
+};
+use logos::Logos;
+
+/// Parses `code` into a parser holding its statements and warnings.
+pub fn try_parse(code: &str) -> AspenResult<AspenParser<'_>> {
+    let mut parser: AspenParser<'_> = Token::lexer(code).into();
+    parse_aspen(&mut parser)?;
+
+    Ok(parser)
+}
+
+/// Parses `code`, panics if it is invalid.
+pub fn parse(code: &str) -> AspenParser<'_> {
+    try_parse(code).unwrap_or_else(|e| panic!("failed to parse {:?}: {}", code, e))
+}
+
+/// Evaluates `code` in a global context, returns the context once the code ran.
+pub fn run(code: &str) -> EvaluateResult<AspenTable<'_>> {
+    let mut table = AspenTable::global();
+    table.evaluate_block(parse(code).statements())?;
+
+    Ok(table)
+}
+
+/// Evaluates `code` and displays the value of the variable `name` once it ran.
+pub fn value_of(code: &str, name: &str) -> String {
+    let table = run(code).unwrap_or_else(|e| panic!("failed to evaluate {:?}: {}", code, e));
+
+    match table.get_value(name.to_owned()) {
+        Ok(value) => value.to_string(),
+        Err(e) => panic!("'{}' is not defined: {}", name, e),
+    }
+}
+
+/// Evaluates `code` and displays the error it fails with, including the errors propagated with `?`.
+pub fn error_of(code: &str) -> String {
+    let mut table = AspenTable::global();
+
+    match table.evaluate_block(parse(code).statements()) {
+        Ok(value) => panic!("evaluating {:?} should fail, got '{}'", code, value),
+        Err(e) => match table.propagated_error(e) {
+            Ok(err) => err.to_string(),
+            Err(e) => e.to_string(),
+        },
+    }
+}